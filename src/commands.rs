@@ -1,12 +1,16 @@
-use std::{fs, path::Path, process::Command};
+use std::{fs, io, path::Path, process::Command, time::Duration, collections::HashSet};
 use crate::{
-    parsing::{parse_file, MathFile},
+    parsing::{parse_file, parse_formula, proof_line_to_string, import_metamath, MathFile},
     compiling::{
-        compile_directory, add_syndef_to_lib,
+        compile_directory, compile_sorted_directory, dependency_order, add_syndef_to_lib,
         add_axiom_to_lib, add_theo_to_lib,
-        verify_theo
+        verify_theo, prove_tautology,
+        complete_proof, minimize, tidy, fill_proof,
+        syntax_names, LibraryItem, axioms_used, used_by, dependents, dependency_graph, Reference,
+        export_metamath, library_item_to_latex, theorem_to_latex, latex_document,
+        library_site, SiteSection, FormulaParser
     },
-    serializing::{read_file, write_lib}
+    serializing::{read_file, write_lib, library_to_json, library_from_json}
 };
 
 fn get_math_file(filepath: &str) -> Result<MathFile, String> {
@@ -91,17 +95,205 @@ fn move_entry_to_order_file(filepath: &str, dir: String, subdir: &str) -> Result
         .map_err(|e| format!("{e:?}"))
 }
 
-pub fn compile(dir: String) -> Result<String, String> {
-    let lib = match compile_directory(dir.clone()) {
-        Ok(lib) => lib,
+/// Replaces the proof lines of a theorem file that have the given line numbers (an empty line takes it out)
+fn rewrite_proof_lines(filepath: &str, new_lines: &[(usize, String)]) -> Result<(), String> {
+    let content = match fs::read_to_string(filepath) {
+        Ok(content) => content,
         Err(e) => return Err( format!("{e:?}") )
     };
-    match write_lib(dir + "/library.math", lib) {
-        Ok(()) => Ok("Compilation successful!".to_string()),
+    let mut in_proof = false;
+    let mut file_data = String::new();
+    for line in content.lines() {
+        if line.starts_with('#') { in_proof = line == "# Proof"; };
+        let line_no = line.split(';').next().and_then(|n| n.trim().parse::<usize>().ok());
+        match new_lines.iter().find(|(n, _)| in_proof && Some(*n) == line_no) {
+            Some((_, new_line)) if new_line.is_empty() => continue,
+            Some((_, new_line)) => file_data.push_str(new_line),
+            None => file_data.push_str(line)
+        };
+        file_data.push('\n');
+    };
+    fs::write(filepath, file_data)
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// The content of a theorem file with a new proof, which is its last section
+fn with_new_proof(content: &str, new_lines: &[String]) -> String {
+    let mut file_data = String::new();
+    for line in content.lines() {
+        file_data.push_str(line);
+        file_data.push('\n');
+        if line == "# Proof" { break; };
+    };
+    for line in new_lines {
+        file_data.push_str(line);
+        file_data.push('\n');
+    };
+    file_data
+}
+
+/// Replaces the proof of a theorem file
+fn replace_proof(filepath: &str, new_lines: &[String]) -> Result<(), String> {
+    let content = match fs::read_to_string(filepath) {
+        Ok(content) => content,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    fs::write(filepath, with_new_proof(&content, new_lines))
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// The lines of 'order.txt', with the items the entries define, counted the way the compiler numbers them
+fn order_entries(text: &str) -> Vec<(&str, Option<LibraryItem>)> {
+    let mut section = "";
+    let mut counts = [0, 0, 0];
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        if line.starts_with('#') { section = line; };
+        let is_entry = !line.is_empty() && !line.starts_with('#');
+        let entry = match section {
+            "# Syntax Definitions" if is_entry => { counts[0] += 1; Some(LibraryItem::Syntax(counts[0] - 1)) },
+            "# Axioms" if is_entry => { counts[1] += 1; Some(LibraryItem::Axiom(counts[1] - 1)) },
+            "# Theorems" if is_entry => { counts[2] += 1; Some(LibraryItem::Theorem(counts[2] - 1)) },
+            _ => None
+        };
+        entries.push((line, entry));
+    };
+    entries
+}
+
+/// The file of an item of the library, found through 'order.txt'
+fn item_file(dir: &str, item: LibraryItem) -> Result<String, String> {
+    let text = match fs::read_to_string(dir.to_owned() + "/order.txt") {
+        Ok(text) => text,
         Err(e) => return Err( format!("{e:?}") )
+    };
+    match order_entries(&text).into_iter().find(|(_, entry)| *entry == Some(item)) {
+        Some((line, _)) => Ok(dir.to_owned() + line),
+        None => Err(format!("{item:?} has no entry in 'order.txt'"))
     }
 }
 
+/// The content of 'order.txt' with entries added at the end of its sections, written with its usual layout
+fn order_file_with(dir: &str, new_entries: [Vec<String>; 3]) -> String {
+    let text = fs::read_to_string(dir.to_owned() + "/order.txt").unwrap_or_default();
+    let mut entries = [Vec::new(), Vec::new(), Vec::new()];
+    for (line, entry) in order_entries(&text) {
+        match entry {
+            Some(LibraryItem::Syntax(_)) => entries[0].push(line.to_owned()),
+            Some(LibraryItem::Axiom(_)) => entries[1].push(line.to_owned()),
+            Some(LibraryItem::Theorem(_)) => entries[2].push(line.to_owned()),
+            _ => ()
+        };
+    };
+    let mut file_data = String::new();
+    let headers = ["# Syntax Definitions", "# Axioms", "# Theorems"];
+    for (section, (mut section_entries, new_section_entries)) in entries.into_iter().zip(new_entries).enumerate() {
+        section_entries.extend(new_section_entries);
+        if section > 0 { file_data.push('\n'); };
+        file_data.push_str(headers[section]);
+        file_data.push('\n');
+        for entry in section_entries {
+            file_data.push_str(&entry);
+            file_data.push('\n');
+        };
+    };
+    file_data
+}
+
+/// The line number, used lines, reference and formula of a proof line
+fn proof_line_fields(line: &str) -> Option<(usize, Vec<usize>, &str, &str)> {
+    let fields = line.splitn(4, ';').map(str::trim).collect::<Vec<_>>();
+    let line_no = fields[0].parse::<usize>().ok()?;
+    let used_lines = fields.get(1)?.split(',')
+        .filter(|used| !used.is_empty())
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<_>, _>>().ok()?;
+    Some((line_no, used_lines, fields.get(2)?, fields.get(3).copied().unwrap_or("")))
+}
+
+/// Makes the proof lines that cite a statement cite it by its new name, and returns None if no line does
+fn rename_citations(content: &str, old_name: &str, new_name: &str) -> Option<String> {
+    let mut in_proof = false;
+    let mut renamed = false;
+    let mut file_data = String::new();
+    for line in content.lines() {
+        if line.starts_with('#') { in_proof = line == "# Proof"; };
+        let fields = proof_line_fields(line).filter(|_| in_proof);
+        match fields.map(|(line_no, used_lines, cited, formula)| {
+            (line_no, used_lines, cited.split_once('.').unwrap_or((cited, "")), formula)
+        }) {
+            // The line is written again with the alignment of the proof lines
+            Some((line_no, used_lines, (cited, assert_id), formula)) if cited == old_name => {
+                let reference = match assert_id {
+                    "" => new_name.to_owned(),
+                    assert_id => format!("{new_name}.{assert_id}")
+                };
+                file_data.push_str(proof_line_to_string(line_no, &used_lines, &reference, formula).trim_end());
+                renamed = true;
+            },
+            _ => file_data.push_str(line)
+        };
+        file_data.push('\n');
+    };
+    renamed.then_some(file_data)
+}
+
+/// Writes the files (or removes those without content) and recompiles the library,
+/// putting back what the files held if one cannot be written or the library does not compile
+fn change_files_and_compile(dir: &str, changes: Vec<(String, Option<String>)>) -> Result<String, String> {
+    let originals = changes.iter()
+        .map(|(path, _)| fs::read_to_string(path).ok())
+        .collect::<Vec<_>>();
+    let written = changes.iter().try_for_each(|(path, content)| match content {
+        Some(content) => fs::write(path, content),
+        None => fs::remove_file(path)
+    });
+    let result = match written {
+        Ok(()) => compile(dir.to_owned()),
+        Err(e) => Err(format!("{e:?}"))
+    };
+    let Err(e) = result else { return result; };
+    for ((path, _), original) in changes.iter().zip(originals) {
+        let _ = match original {
+            Some(original) => fs::write(path, original),
+            None => fs::remove_file(path)
+        };
+    };
+    Err(format!("{e}, the files were left as they were"))
+}
+
+pub fn compile(dir: String) -> Result<String, String> {
+    compile_library(dir, false, 1)
+}
+
+pub fn compile_library(dir: String, sorted: bool, jobs: usize) -> Result<String, String> {
+    let compiled = match sorted {
+        true => compile_sorted_directory(dir.clone(), jobs),
+        false => compile_directory(dir.clone(), jobs)
+    };
+    let (lib, cache) = match compiled {
+        Ok(compiled) => compiled,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    for (path, checked_size) in &cache.unchecked_syntaxes {
+        println!("WARNING: {path} was only checked for ambiguity with the formulas of up to {checked_size} syntaxes");
+    };
+    let theorem_count = lib.theorems.len();
+    if let Err(e) = write_lib(dir.clone() + "/library.math", lib) {
+        return Err( format!("{e:?}") );
+    };
+    // Without its cache, the next compilation simply verifies every theorem again
+    let _ = cache.save(&dir);
+    Ok(format!("Compilation successful! ({} of {theorem_count} theorems verified)", cache.reverified_theorems))
+}
+
+pub fn reorder(dir: String) -> Result<String, String> {
+    let order = dependency_order(dir.clone()).map_err(|e| format!("{e:?}"))?;
+    fs::write(dir + "/order.txt", order)
+        .map_err(|e| format!("{e:?}"))?;
+    Ok("'order.txt' now follows the dependencies of the files".to_string())
+}
+
 pub fn add_syndef(dir: String, path: String) -> Result<String, String> {
     let math_file = get_math_file(&path)?;
     let (mut lib, mut references) = match read_file(dir.clone() + "/library.math") {
@@ -109,7 +301,10 @@ pub fn add_syndef(dir: String, path: String) -> Result<String, String> {
         Err(e) => return Err( format!("{e:?}") )
     };
     match add_syndef_to_lib(math_file, &mut lib, &mut references) {
-        Ok(()) => (),
+        Ok(None) => (),
+        Ok(Some(checked_size)) => {
+            println!("WARNING: {path} was only checked for ambiguity with the formulas of up to {checked_size} syntaxes");
+        },
         Err(e) => return Err( format!("{e:?}") )
     };
     try_move_file_to(&path, dir.clone(), r"\syntax_definitions\")?;
@@ -162,12 +357,539 @@ pub fn verify(dir: String, path: String) -> Result<String, String> {
         Ok((lib, references)) => (lib, references),
         Err(e) => return Err( format!("{e:?}") )
     };
-    match verify_theo(math_file, &mut lib, &mut references) {
-        Ok(()) => Ok("Theorem is valid".to_string()),
+    let (goals, warnings) = match verify_theo(math_file, &mut lib, &mut references) {
+        Ok(res) => res,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    for warning in &warnings {
+        println!("WARNING: {warning}");
+    };
+    if goals.is_empty() {
+        return Ok("Theorem is valid".to_string());
+    };
+    println!("Open goals:");
+    for goal in &goals {
+        println!("{goal}");
+    };
+    Ok(format!("The proof is valid so far, with {} open goals", goals.len()))
+}
+
+pub fn prove_taut(dir: String, formula: String) -> Result<String, String> {
+    let (lib, _) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let lines = match prove_tautology(parse_formula(&formula), &lib) {
+        Ok(lines) => lines,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    println!("# Proof");
+    for line in &lines {
+        println!("{line}");
+    };
+    Ok(format!("The formula is a tautology, proven in {} lines", lines.len()))
+}
+
+pub fn search_proof(dir: String, path: String, depth: Option<usize>, time: Option<u64>) -> Result<String, String> {
+    let math_file = get_math_file(&path)?;
+    let (lib, references) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let depth = depth.unwrap_or(4);
+    let time = Duration::from_secs(time.unwrap_or(10));
+    let completed = match complete_proof(math_file, &lib, &references, depth, time) {
+        Ok(completed) => completed,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    if completed.replaced_lines.is_empty() && completed.added_lines.is_empty() {
+        return Ok("The proof is already complete".to_string());
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    // The lines left to be proven are replaced, and the following lines renumbered
+    let mut in_proof = false;
+    let mut new_lines = Vec::new();
+    for line in content.lines() {
+        if line.starts_with('#') { in_proof = line == "# Proof"; };
+        let Some((line_no, used_lines, reference, formula)) = proof_line_fields(line).filter(|_| in_proof) else {
+            continue;
+        };
+        if let Some((_, lines)) = completed.replaced_lines.iter().find(|(n, _)| *n == line_no) {
+            new_lines.push((line_no, lines.iter().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n")));
+            continue;
+        };
+        let new_number = |n: usize| completed.line_numbers.get(n - 1).copied().unwrap_or(n);
+        if new_number(line_no) != line_no || used_lines.iter().any(|n| new_number(*n) != *n) {
+            let used_lines = used_lines.iter().map(|n| new_number(*n)).collect::<Vec<_>>();
+            let new_line = proof_line_to_string(new_number(line_no), &used_lines, reference, formula);
+            new_lines.push((line_no, new_line.trim_end().to_owned()));
+        };
+    };
+    rewrite_proof_lines(&path, &new_lines)?;
+    if !completed.added_lines.is_empty() {
+        // The proof is the last section of a theorem file
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => return Err( format!("{e:?}") )
+        };
+        let mut content = content.trim_end().to_string();
+        for line in &completed.added_lines {
+            content.push('\n');
+            content.push_str(line);
+        };
+        content.push('\n');
+        fs::write(&path, content).map_err(|e| format!("{e:?}"))?;
+    };
+    let written = completed.replaced_lines.iter().map(|(_, lines)| lines.len()).sum::<usize>() + completed.added_lines.len();
+    Ok(format!("Proof found, {} open goals closed and {written} lines written", completed.replaced_lines.len()))
+}
+
+pub fn minimize_theo(dir: String, name: String) -> Result<String, String> {
+    let (lib, references) = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    // A theorem of the library is given by its name, a pending one by its file
+    let (path, in_library) = match references.get(&name) {
+        Some(Reference::TheoremReference(theo_id, _)) => (item_file(&dir, LibraryItem::Theorem(*theo_id))?, true),
+        _ if Path::new(&name).is_file() => (name, false),
+        _ => return Err(format!("{name} is neither a theorem of the library nor a file"))
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let math_file = match parse_file(content.clone()) {
+        Ok(math_file) => math_file,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let (lines, former_length) = match minimize(math_file, &lib, &references) {
+        Ok(res) => res,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    if lines.len() >= former_length {
+        return Ok("The proof could not be shortened".to_string());
+    };
+    let new_content = with_new_proof(&content, &lines);
+    if in_library {
+        change_files_and_compile(&dir, vec![(path, Some(new_content))])?;
+    } else {
+        fs::write(&path, new_content).map_err(|e| format!("{e:?}"))?;
+    };
+    Ok(format!("Proof shortened from {former_length} to {} lines", lines.len()))
+}
+
+pub fn tidy_theo(dir: String, path: String) -> Result<String, String> {
+    let math_file = get_math_file(&path)?;
+    let (lib, references) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let (lines, former_length) = match tidy(math_file, &lib, &references) {
+        Ok(res) => res,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    if lines.len() == former_length {
+        return Ok("The proof has no unused or duplicate lines".to_string());
+    };
+    replace_proof(&path, &lines)?;
+    Ok(format!("{} proof lines removed", former_length - lines.len()))
+}
+
+pub fn fill(dir: String, path: String) -> Result<String, String> {
+    let math_file = get_math_file(&path)?;
+    let (lib, references) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let lines = match fill_proof(math_file, &lib, &references) {
+        Ok(lines) => lines,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    rewrite_proof_lines(&path, &lines)?;
+    Ok(format!("{} proof lines filled", lines.len()))
+}
+
+pub fn axioms_used_by(dir: String, name: String, show_paths: bool) -> Result<String, String> {
+    let (lib, references) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let Some(Reference::TheoremReference(theo_id, _)) = references.get(&name) else {
+        return Err(format!("{name} is not a theorem of the library"));
+    };
+    let used = axioms_used(&lib, *theo_id);
+    for (item, path) in &used {
+        let name = item.name(&lib, &[]);
+        match show_paths {
+            true => {
+                let path = path.iter()
+                    .map(|theo_id| lib.theorems[*theo_id].name.as_str())
+                    .collect::<Vec<_>>();
+                println!("{name}: {} -> {name}", path.join(" -> "));
+            },
+            false => println!("{name}")
+        };
+    };
+    let axiom_count = used.iter()
+        .filter(|(item, _)| matches!(item, LibraryItem::Axiom(_)))
+        .count();
+    Ok(format!("{name} rests on {axiom_count} axioms and {} definitions", used.len() - axiom_count))
+}
+
+pub fn used_by_theo(dir: String, name: String, max_depth: Option<usize>) -> Result<String, String> {
+    let (lib, references) = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let syntax_names = syntax_names(dir).map_err(|e| format!("{e:?}"))?;
+    let Some(item) = LibraryItem::from_name(&name, &references, &syntax_names) else {
+        return Err(format!("{name} is not in the library"));
+    };
+    let users = used_by(&lib, item, max_depth);
+    let mut depth_counts = Vec::new();
+    for (theo_id, depth) in &users {
+        println!("{depth}: {}", lib.theorems[*theo_id].name);
+        if depth_counts.len() < *depth { depth_counts.push(0); };
+        depth_counts[depth - 1] += 1;
+    };
+    let counts = depth_counts.iter()
+        .enumerate()
+        .map(|(index, count)| format!("{count} at depth {}", index + 1))
+        .collect::<Vec<_>>();
+    match users.is_empty() {
+        true => Ok(format!("No theorem uses {name}")),
+        false => Ok(format!("{} theorems use {name} ({})", users.len(), counts.join(", ")))
+    }
+}
+
+pub fn graph(
+    dir: String, root: Option<String>, out: Option<String>, max_depth: Option<usize>
+) -> Result<String, String> {
+    let (lib, references) = match read_file(dir + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let root = match root {
+        Some(name) => match references.get(&name) {
+            Some(Reference::TheoremReference(theo_id, _)) => Some(*theo_id),
+            _ => return Err(format!("{name} is not a theorem of the library"))
+        },
+        None => None
+    };
+    let (dot, node_count, edge_count) = dependency_graph(&lib, root, max_depth);
+    match out {
+        Some(path) => fs::write(path, dot).map_err(|e| format!("{e:?}"))?,
+        None => print!("{dot}")
+    };
+    Ok(format!("Graph of {node_count} nodes and {edge_count} edges"))
+}
+
+pub fn remove(dir: String, name: String, cascade: bool) -> Result<String, String> {
+    let (lib, references) = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let syntax_names = syntax_names(dir.clone()).map_err(|e| format!("{e:?}"))?;
+    let Some(item) = LibraryItem::from_name(&name, &references, &syntax_names) else {
+        return Err(format!("{name} is not in the library"));
+    };
+    let item = item.file_item(&lib, &syntax_names);
+    let mut removed = dependents(&lib, item, &syntax_names);
+    if !removed.is_empty() && !cascade {
+        let names = removed.iter()
+            .map(|dependent| dependent.name(&lib, &syntax_names))
+            .collect::<Vec<_>>();
+        return Err(format!("{name} is used by {}, add --cascade to remove them too", names.join(", ")));
+    };
+    removed.insert(0, item);
+
+    let text = match fs::read_to_string(dir.clone() + "/order.txt") {
+        Ok(text) => text,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let mut changes = Vec::new();
+    let mut file_data = String::new();
+    for (line, entry) in order_entries(&text) {
+        if entry.is_some_and(|entry| removed.contains(&entry)) {
+            // The file is moved to pending
+            let Some(file_name) = Path::new(line).file_name().and_then(|s| s.to_str()) else {
+                return Err(format!("{line} is not the path of a file"));
+            };
+            let content = fs::read_to_string(dir.clone() + line).map_err(|e| format!("{e:?}"))?;
+            changes.push((dir.clone() + "/pending/" + file_name, Some(content)));
+            changes.push((dir.clone() + line, None));
+            continue;
+        };
+        file_data.push_str(line);
+        file_data.push('\n');
+    };
+    changes.push((dir.clone() + "/order.txt", Some(file_data)));
+
+    // Recompiling numbers the remaining items again, along with the references to them
+    change_files_and_compile(&dir, changes)?;
+    for removed_item in &removed {
+        println!("{}", removed_item.name(&lib, &syntax_names));
+    };
+    Ok(format!("Removed {} items from the library and moved their files to pending", removed.len()))
+}
+
+pub fn rename(dir: String, old_name: String, new_name: String) -> Result<String, String> {
+    let references = match read_file(dir.clone() + "/library.math") {
+        Ok((_, references)) => references,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let item = match references.get(&old_name) {
+        Some(Reference::AxiomReference(ax_id, _)) => LibraryItem::Axiom(*ax_id),
+        Some(Reference::TheoremReference(theo_id, _)) => LibraryItem::Theorem(*theo_id),
+        _ => return Err(format!("{old_name} is not an axiom or a theorem of the library"))
+    };
+    if references.contains_key(&new_name) {
+        return Err(format!("{new_name} is already used in the library"));
+    };
+    if new_name.is_empty() || !new_name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("{new_name} is not a valid name, names are made of letters and digits"));
+    };
+
+    let text = match fs::read_to_string(dir.clone() + "/order.txt") {
+        Ok(text) => text,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let mut renamed_file = None;
+    let mut theorem_files = Vec::new();
+    let mut file_data = String::new();
+    for (line, entry) in order_entries(&text) {
+        match entry {
+            Some(entry) if entry == item => {
+                let new_line = match line.rsplit_once('/') {
+                    Some((folder, _)) => format!("{folder}/{new_name}"),
+                    None => new_name.clone()
+                };
+                file_data.push_str(&new_line);
+                renamed_file = Some((dir.clone() + line, dir.clone() + &new_line));
+            },
+            Some(LibraryItem::Theorem(_)) => {
+                file_data.push_str(line);
+                theorem_files.push(dir.clone() + line);
+            },
+            _ => file_data.push_str(line)
+        };
+        file_data.push('\n');
+    };
+    let Some((old_path, new_path)) = renamed_file else {
+        return Err(format!("{old_name} has no entry in 'order.txt'"));
+    };
+    if old_path != new_path && Path::new(&new_path).exists() {
+        return Err(format!("{new_path} already exists"));
+    };
+
+    let content = match fs::read_to_string(&old_path) {
+        Ok(content) => content,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let (header, body) = content.split_once('\n').unwrap_or((&content, ""));
+    let Some((file_type, _)) = header.rsplit_once(' ') else {
+        return Err(format!("{old_path} has no header"));
+    };
+    let mut changes = vec![(new_path.clone(), Some(format!("{file_type} {new_name}\n{body}")))];
+    if old_path != new_path {
+        changes.push((old_path, None));
+    };
+    changes.push((dir.clone() + "/order.txt", Some(file_data)));
+
+    // The pending theorems may cite the statement too
+    let pending_files = fs::read_dir(dir.clone() + "/pending").into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.path().to_str().map(str::to_owned));
+    let mut updated_files = Vec::new();
+    for file in theorem_files.into_iter().chain(pending_files) {
+        let Ok(content) = fs::read_to_string(&file) else { continue; };
+        let Some(new_content) = rename_citations(&content, &old_name, &new_name) else { continue; };
+        changes.push((file.clone(), Some(new_content)));
+        updated_files.push(file);
+    };
+
+    change_files_and_compile(&dir, changes)?;
+    for file in &updated_files {
+        println!("{file}");
+    };
+    let updated_files = updated_files.len();
+    Ok(format!("Renamed {old_name} to {new_name}, and the citations in {updated_files} files"))
+}
+
+pub fn import_mm(dir: String, path: String) -> Result<String, String> {
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
         Err(e) => return Err( format!("{e:?}") )
+    };
+    let mut taken_names = read_file(dir.clone() + "/library.math")
+        .map(|(_, references)| references.into_keys().collect::<HashSet<_>>())
+        .unwrap_or_default();
+    taken_names.extend(syntax_names(dir.clone()).unwrap_or_default());
+    let import = import_metamath(&source, taken_names).map_err(|e| format!("{e:?}"))?;
+    for (label, reason) in &import.skipped {
+        println!("Skipped {label}: {reason}");
+    };
+
+    let folders = ["/syntax_definitions/", "/axioms/", "/theorems/"];
+    if let Some(file) = import.files.iter().find(|file| Path::new(&(dir.clone() + folders[file.section] + &file.name)).exists()) {
+        return Err(format!("{}{} already exists", folders[file.section], file.name));
+    };
+    let mut entries = [Vec::new(), Vec::new(), Vec::new()];
+    let mut changes = Vec::new();
+    for file in &import.files {
+        let entry = folders[file.section].to_owned() + &file.name;
+        changes.push((dir.clone() + &entry, Some(file.content.clone())));
+        entries[file.section].push(entry);
+    };
+    let counts = entries.iter().map(Vec::len).collect::<Vec<_>>();
+    changes.push((dir.clone() + "/order.txt", Some(order_file_with(&dir, entries))));
+
+    change_files_and_compile(&dir, changes)?;
+    Ok(format!(
+        "Imported {} syntax definitions, {} axioms and {} theorems ({} statements skipped)",
+        counts[0], counts[1], counts[2], import.skipped.len()
+    ))
+}
+
+pub fn export_mm(dir: String, out: String) -> Result<String, String> {
+    let lib = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, _)) => lib,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let syntax_names = syntax_names(dir).map_err(|e| format!("{e:?}"))?;
+    let export = export_metamath(&lib, &syntax_names);
+    for (name, reason) in &export.skipped {
+        println!("Skipped {name}: {reason}");
+    };
+    fs::write(&out, export.database).map_err(|e| format!("{e:?}"))?;
+    Ok(format!("Wrote the library to {out} ({} items skipped)", export.skipped.len()))
+}
+
+pub fn export_json(dir: String, out: String) -> Result<String, String> {
+    let lib = match read_file(dir + "/library.math") {
+        Ok((lib, _)) => lib,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    fs::write(&out, library_to_json(&lib)).map_err(|e| format!("{e:?}"))?;
+    Ok(format!(
+        "Wrote {} syntaxes, {} definitions, {} axioms and {} theorems to {out}",
+        lib.syntaxes.len(), lib.definitions.len(), lib.axioms.len(), lib.theorems.len()
+    ))
+}
+
+pub fn import_json(dir: String, path: String) -> Result<String, String> {
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let lib = library_from_json(&text).map_err(|e| format!("{e:?}"))?;
+    // The library cannot be written back as source files, which would no longer match it
+    let has_sources = Path::new(&(dir.clone() + "/order.txt")).exists()
+        || ["/syntax_definitions", "/axioms", "/theorems"].iter().any(|folder| {
+            fs::read_dir(dir.clone() + folder).is_ok_and(|mut entries| entries.next().is_some())
+        });
+    if has_sources {
+        return Err("The directory has an order.txt or source files, import into a directory without them".to_string());
+    };
+    let message = format!(
+        "Imported {} syntaxes, {} definitions, {} axioms and {} theorems",
+        lib.syntaxes.len(), lib.definitions.len(), lib.axioms.len(), lib.theorems.len()
+    );
+    write_lib(dir.clone() + "/library.math", lib).map_err(|e| format!("{e:?}"))?;
+    match fs::remove_file(dir + "/library.cache") {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err( format!("{e:?}") ),
+        _ => Ok(message)
     }
 }
 
+pub fn export_latex(dir: String, name: Option<String>, all: bool, out: Option<String>) -> Result<String, String> {
+    let (lib, references) = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let syntax_names = syntax_names(dir.clone()).map_err(|e| format!("{e:?}"))?;
+    let exported = match (name, all) {
+        (Some(name), false) => match LibraryItem::from_name(&name, &references, &syntax_names) {
+            Some(item) => Some(item.file_item(&lib, &syntax_names)),
+            None => return Err(format!("{name} is not in the library"))
+        },
+        (None, true) => None,
+        _ => return Err("Give either the name of an item or --all".to_string())
+    };
+
+    let text = match fs::read_to_string(dir.clone() + "/order.txt") {
+        Ok(text) => text,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let mut sections = Vec::new();
+    let mut item_count = 0;
+    let parser = FormulaParser::new(&lib.syntaxes);
+    for (line, entry) in order_entries(&text) {
+        if let Some(title) = line.strip_prefix("# ") {
+            if exported.is_none() { sections.push((title.to_string(), Vec::new())); };
+            continue;
+        };
+        let Some(entry) = entry else { continue; };
+        if exported.is_some_and(|exported| exported != entry) { continue; };
+        // Theorems are written from their files, which name their hypotheses and variables
+        let item = match entry {
+            LibraryItem::Theorem(_) => {
+                let file = get_math_file(&(dir.clone() + line))?;
+                theorem_to_latex(file, &lib, &parser, &references).map_err(|e| format!("{line}: {e:?}"))?
+            },
+            _ => library_item_to_latex(entry, &lib, &syntax_names).map_err(|e| format!("{line}: {e:?}"))?
+        };
+        if sections.is_empty() { sections.push((String::new(), Vec::new())); };
+        let last_index = sections.len() - 1;
+        sections[last_index].1.push(item);
+        item_count += 1;
+    };
+    let document = latex_document(&sections);
+    match out {
+        Some(path) => fs::write(path, document).map_err(|e| format!("{e:?}"))?,
+        None => print!("{document}")
+    };
+    Ok(format!("Wrote {item_count} items as LaTeX"))
+}
+
+pub fn site(dir: String, out_dir: String) -> Result<String, String> {
+    let (lib, references) = match read_file(dir.clone() + "/library.math") {
+        Ok((lib, references)) => (lib, references),
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let syntax_names = syntax_names(dir.clone()).map_err(|e| format!("{e:?}"))?;
+    let text = match fs::read_to_string(dir.clone() + "/order.txt") {
+        Ok(text) => text,
+        Err(e) => return Err( format!("{e:?}") )
+    };
+    let mut sections: Vec<SiteSection> = Vec::new();
+    for (line, entry) in order_entries(&text) {
+        if let Some(title) = line.strip_prefix("# ") {
+            sections.push(SiteSection { title: title.to_string(), items: Vec::new() });
+            continue;
+        };
+        let (Some(entry), Some(section)) = (entry, sections.last_mut()) else { continue; };
+        // The proofs are shown as they are written in the files of the theorems
+        let file = match entry {
+            LibraryItem::Theorem(_) => Some(get_math_file(&(dir.clone() + line))?),
+            _ => None
+        };
+        section.items.push((entry, file));
+    };
+    let pages = library_site(&lib, &syntax_names, &references, sections)
+        .map_err(|(name, e)| format!("{name}: {e:?}"))?;
+    fs::create_dir_all(&out_dir).map_err(|e| format!("{e:?}"))?;
+    for (file_name, content) in &pages {
+        fs::write(Path::new(&out_dir).join(file_name), content).map_err(|e| format!("{e:?}"))?;
+    };
+    Ok(format!("Wrote {} pages to {out_dir}", pages.len() - 1))
+}
+
 pub fn open_editor(dir: String) -> Result<String, String> {
     Command::new("wezterm")
         .arg("start")
@@ -0,0 +1,213 @@
+use super::{
+    Grammar, Token,
+    Syntax, SyntaxType,
+    WellFormedFormula, Object,
+    VariableNames, Parameter, Piece, Parentheses,
+    pieces, parameter_to_string
+};
+
+/// Maximum number of syntaxes in the formulas tried by the search for an ambiguity
+const MAX_WITNESS_SIZE: usize = 3;
+/// Maximum number of formulas built by the search, so that adding a syntax to a large library stays fast
+const MAX_TRIED_FORMULAS: usize = 10000;
+
+/// Candidates indexed by their size, split by whether they use the new syntax
+struct Candidates<T> {
+    old: Vec<Vec<T>>,
+    new: Vec<Vec<T>>
+}
+
+impl<T: Clone> Candidates<T> {
+    /// Every list of 'count' candidates whose sizes add up to 'total',
+    /// and with at least one candidate using the new syntax if 'uses_new' (none otherwise).
+    /// None if there are more than 'limit' lists
+    fn lists(&self, count: usize, total: usize, uses_new: bool, limit: usize) -> Option<Vec<Vec<T>>> {
+        if count == 0 {
+            return Some(match (total, uses_new) {
+                (0, false) => vec![Vec::new()],
+                _ => Vec::new()
+            });
+        };
+        let mut res = Vec::new();
+        for size in 0..=total {
+            let mut heads_and_tails = vec![(&self.old[size], self.lists(count-1, total-size, uses_new, limit)?)];
+            if uses_new {
+                let mut tails = self.lists(count-1, total-size, false, limit)?;
+                tails.extend(self.lists(count-1, total-size, true, limit)?);
+                heads_and_tails.push((&self.new[size], tails));
+            };
+            for (heads, tails) in heads_and_tails {
+                for head in heads {
+                    for tail in &tails {
+                        if res.len() == limit { return None; };
+                        res.push(Some(head.clone()).into_iter().chain(tail.iter().cloned()).collect());
+                    };
+                };
+            };
+        };
+        Some(res)
+    }
+}
+
+/// Whether a formula or an object using the new syntax can be read in several ways,
+/// giving the formula and its readings
+fn try_candidate(
+    candidate: Parameter, grammar: &Grammar, syntaxes: &[Syntax]
+) -> Option<(String, Vec<String>)> {
+    // The variables of the candidates are all numbered 0
+    let (renumbered_wff, renumbered_obj);
+    let (candidate, syntax_type) = match candidate {
+        Parameter::Wff(wff) => {
+            let mut wff = wff.clone();
+            renumber_wff(&mut wff, &mut (0, 0));
+            renumbered_wff = wff;
+            (Parameter::Wff(&renumbered_wff), SyntaxType::Formula)
+        },
+        Parameter::Object(obj) => {
+            let mut obj = obj.clone();
+            renumber_object(&mut obj, &mut (0, 0));
+            renumbered_obj = obj;
+            (Parameter::Object(&renumbered_obj), SyntaxType::Object)
+        }
+    };
+    let mut witness = Vec::new();
+    tokens(candidate, syntaxes, &mut witness);
+    let readings = readings(grammar, &witness, &syntax_type, syntaxes);
+    if readings.is_empty() { return None; };
+    let witness = parameter_to_string(candidate, syntaxes, &VariableNames::default(), Parentheses::Omitted);
+    Some((witness, readings))
+}
+
+/// Gives a different variable to every parameter, so that different readings give different formulas
+fn renumber_wff(wff: &mut WellFormedFormula, next_ids: &mut (usize, usize)) {
+    match wff {
+        WellFormedFormula::Atomic(id) => {
+            *id = next_ids.0;
+            next_ids.0 += 1;
+        },
+        WellFormedFormula::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } => {
+            wff_parameters.iter_mut().for_each(|wff| renumber_wff(wff, next_ids));
+            object_parameters.iter_mut().for_each(|obj| renumber_object(obj, next_ids));
+        },
+        WellFormedFormula::Repetition | WellFormedFormula::WorkVariable(_) => ()
+    };
+}
+
+fn renumber_object(obj: &mut Object, next_ids: &mut (usize, usize)) {
+    match obj {
+        Object::Atomic(id) => {
+            *id = next_ids.1;
+            next_ids.1 += 1;
+        },
+        Object::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } => {
+            wff_parameters.iter_mut().for_each(|wff| renumber_wff(wff, next_ids));
+            object_parameters.iter_mut().for_each(|obj| renumber_object(obj, next_ids));
+        },
+        Object::Repetition | Object::WorkVariable(_) => ()
+    };
+}
+
+/// The tokens of a formula written without any parentheses other than those of its syntaxes
+fn tokens(param: Parameter, syntaxes: &[Syntax], res: &mut Vec<Token>) {
+    match param {
+        Parameter::Wff(WellFormedFormula::Atomic(id)) => res.push(Token::Wff(*id)),
+        Parameter::Object(Object::Atomic(id)) => res.push(Token::Object(*id)),
+        Parameter::Wff(WellFormedFormula::Repetition)
+        | Parameter::Object(Object::Repetition) => res.push(Token::Repetition),
+        _ => for piece in pieces(syntaxes, param).map(|(_, pieces)| pieces).unwrap_or_default() {
+            match piece {
+                Piece::Literal(c) => res.push(Token::Char(c)),
+                Piece::Parameter(param) => tokens(param, syntaxes, res)
+            };
+        }
+    };
+}
+
+/// Every way (at least two) to read the tokens, written with explicit parentheses
+fn readings(grammar: &Grammar, tokens: &[Token], syntax_type: &SyntaxType, syntaxes: &[Syntax]) -> Vec<String> {
+    let Ok(trees) = grammar.parse(tokens, syntax_type) else { return Vec::new(); };
+    let names = VariableNames::default();
+    let mut wffs = Vec::new();
+    let mut objects = Vec::new();
+    for tree in trees {
+        match syntax_type {
+            SyntaxType::Formula => if let Some(wff) = grammar.formula(&tree, tokens) {
+                if !wffs.contains(&wff) { wffs.push(wff); };
+            },
+            SyntaxType::Object => if let Some(obj) = grammar.object(&tree, tokens) {
+                if !objects.contains(&obj) { objects.push(obj); };
+            }
+        };
+    };
+    if wffs.len() + objects.len() < 2 { return Vec::new(); };
+    wffs.iter().map(Parameter::Wff)
+        .chain(objects.iter().map(Parameter::Object))
+        .map(|param| parameter_to_string(param, syntaxes, &names, Parentheses::Explicit))
+        .collect()
+}
+
+/// Looks for a formula (or an object) using a new syntax that can be read in several ways,
+/// by trying those made of a few syntaxes, smallest first, as they are built.
+/// Returns the formula and its readings, or as an error the size up to which the formulas were all tried
+/// when the search had to stop
+pub fn find_ambiguity(syntax: &Syntax, syntaxes: &[Syntax]) -> Result<Option<(String, Vec<String>)>, usize> {
+    let syntaxes = syntaxes.iter()
+        .chain(Some(syntax))
+        .cloned()
+        .collect::<Vec<_>>();
+    let new_ref = syntaxes.len() - 1;
+    let grammar = Grammar::new(&syntaxes);
+    let mut wffs = Candidates { old: vec![vec![WellFormedFormula::Atomic(0)]], new: vec![Vec::new()] };
+    let mut objects = Candidates { old: vec![vec![Object::Atomic(0)]], new: vec![Vec::new()] };
+    let mut built = 0;
+    for size in 1..=MAX_WITNESS_SIZE {
+        for list in [&mut wffs.old, &mut wffs.new] { list.push(Vec::new()); };
+        for list in [&mut objects.old, &mut objects.new] { list.push(Vec::new()); };
+        for (syntax_ref, syntax) in syntaxes.iter().enumerate() {
+            // Formulas without the new syntax are only needed as parameters of smaller formulas
+            for uses_new in [false, true] {
+                if !uses_new && size == MAX_WITNESS_SIZE { continue; };
+                if syntax_ref == new_ref && !uses_new { continue; };
+                // A variadic syntax is tried with its shortest list
+                let splits = (0..size).flat_map(|wff_size| {
+                    [(wff_size, false, false), (wff_size, false, true), (wff_size, true, false), (wff_size, true, true)]
+                });
+                for (wff_size, wffs_use_new, objects_use_new) in splits {
+                    let children_use_new = wffs_use_new || objects_use_new;
+                    if syntax_ref != new_ref && children_use_new != uses_new { continue; };
+                    let limit = MAX_TRIED_FORMULAS - built;
+                    let wff_lists = wffs.lists(syntax.distinct_wff_count, wff_size, wffs_use_new, limit);
+                    let object_lists = objects.lists(syntax.distinct_object_count, size-1-wff_size, objects_use_new, limit);
+                    let (Some(wff_lists), Some(object_lists)) = (wff_lists, object_lists) else {
+                        return Err(size - 1);
+                    };
+                    for wff_parameters in &wff_lists {
+                        for object_parameters in &object_lists {
+                            built += 1;
+                            if built > MAX_TRIED_FORMULAS { return Err(size - 1); };
+                            let (wff_parameters, object_parameters) = (wff_parameters.clone(), object_parameters.clone());
+                            let ambiguity = match syntax.syntax_type {
+                                SyntaxType::Formula => {
+                                    let wff = WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters };
+                                    let ambiguity = uses_new.then(|| try_candidate(Parameter::Wff(&wff), &grammar, &syntaxes));
+                                    let list = match uses_new { false => &mut wffs.old, true => &mut wffs.new };
+                                    list[size].push(wff);
+                                    ambiguity.flatten()
+                                },
+                                SyntaxType::Object => {
+                                    let obj = Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters };
+                                    let ambiguity = uses_new.then(|| try_candidate(Parameter::Object(&obj), &grammar, &syntaxes));
+                                    let list = match uses_new { false => &mut objects.old, true => &mut objects.new };
+                                    list[size].push(obj);
+                                    ambiguity.flatten()
+                                }
+                            };
+                            if ambiguity.is_some() { return Ok(ambiguity); };
+                        };
+                    };
+                };
+            };
+        };
+    };
+    Ok(None)
+}
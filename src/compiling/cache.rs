@@ -0,0 +1,106 @@
+use std::{fs, io, collections::HashMap};
+use crate::serializing::read_file;
+use super::{Theorem, Reference};
+
+const CACHE_FILE: &str = "/library.cache";
+
+/// FNV-1a, which unlike the hashers of the standard library gives the same value from one build to the next
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// A source file as it was when the library was compiled
+#[derive(PartialEq)]
+pub struct CachedFile {
+    pub path: String,
+    pub section: usize,  // 0 for syntax definitions, 1 for axioms and 2 for theorems
+    pub hash: u64,
+    pub dependencies: Vec<String>  // The files defining what the proof cites
+}
+
+/// The source files a library was compiled from
+pub struct BuildCache {
+    pub files: Vec<CachedFile>,
+    pub reverified_theorems: usize,  // Not saved, the theorems the compilation could not take from the cache
+    pub unchecked_syntaxes: Vec<(String, usize)>  // Not saved, the syntaxes whose ambiguity check was cut short
+}
+
+impl BuildCache {
+    /// Saves the cache next to the library it was built with, which must already be written
+    pub fn save(&self, dir: &str) -> io::Result<()> {
+        let library = fs::read(dir.to_owned() + "/library.math")?;
+        let mut data = format!("library\t{:016x}\n", content_hash(&library));
+        for file in &self.files {
+            data.push_str(&format!("{}\t{:016x}\t{}", file.section, file.hash, file.path));
+            for dependency in &file.dependencies {
+                data.push('\t');
+                data.push_str(dependency);
+            };
+            data.push('\n');
+        };
+        fs::write(dir.to_owned() + CACHE_FILE, data)
+    }
+}
+
+/// The last compilation, whose theorems can be taken back when their files did not change
+pub struct PreviousBuild {
+    files: Vec<CachedFile>,
+    theorem_paths: Vec<String>,
+    theorems: Vec<Option<Theorem>>
+}
+
+impl PreviousBuild {
+    /// The cache and the library it was saved with, or None if the library was written by other means since
+    pub fn load(dir: &str) -> Option<PreviousBuild> {
+        let library = fs::read(dir.to_owned() + "/library.math").ok()?;
+        let text = fs::read_to_string(dir.to_owned() + CACHE_FILE).ok()?;
+        let mut lines = text.lines();
+        let library_hash = lines.next()?.strip_prefix("library\t")?;
+        if u64::from_str_radix(library_hash, 16).ok()? != content_hash(&library) { return None; };
+        let mut files = Vec::new();
+        for line in lines {
+            let mut fields = line.split('\t');
+            let section = fields.next()?.parse().ok()?;
+            let hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let path = fields.next()?.to_owned();
+            files.push(CachedFile { path, section, hash, dependencies: fields.map(str::to_owned).collect() });
+        };
+        let (lib, _) = read_file(dir.to_owned() + "/library.math").ok()?;
+        let theorem_paths = files.iter()
+            .filter(|file| file.section == 2)
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        if theorem_paths.len() != lib.theorems.len() { return None; };
+        Some(PreviousBuild { files, theorem_paths, theorems: lib.theorems.into_iter().map(Some).collect() })
+    }
+
+    /// Whether the syntax definitions and axioms are the files they were, in the same order,
+    /// so that they still have the indices the previous theorems refer to them by
+    pub fn same_statements(&self, files: &[CachedFile]) -> bool {
+        let previous_files = self.files.iter().filter(|file| file.section < 2);
+        let files = files.iter().filter(|file| file.section < 2);
+        previous_files.eq(files)
+    }
+
+    /// Takes the theorem of a file that did not change, with the theorems it cites numbered as they are now.
+    /// The theorems must all have been taken from the previous build too, 'theorem_ids' giving their
+    /// indices and whether they were
+    pub fn reuse_theorem(&mut self, file: &CachedFile, theorem_ids: &HashMap<String, (usize, bool)>) -> Option<Theorem> {
+        if self.files.iter().all(|previous| previous != file) { return None; };
+        let old_id = self.theorem_paths.iter().position(|path| *path == file.path)?;
+        let renumbered = self.theorems[old_id].as_ref()?.proof.iter()
+            .map(|step| match step.theorem_ref {
+                Reference::TheoremReference(used_id, assert_id) => {
+                    let (new_id, reused) = theorem_ids.get(&self.theorem_paths[used_id])?;
+                    reused.then_some(Reference::TheoremReference(*new_id, assert_id))
+                },
+                reference => Some(reference)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let mut theorem = self.theorems[old_id].take()?;
+        for (step, reference) in theorem.proof.iter_mut().zip(renumbered) {
+            step.theorem_ref = reference;
+        };
+        Some(theorem)
+    }
+}
@@ -1,10 +1,18 @@
-use std::{fs, io, collections::HashMap};
-use crate::parsing::{parse_file, MathFile};
+use std::{fs, io, cmp::Reverse, collections::{HashMap, BinaryHeap}, time::Duration};
+use crate::parsing::{parse_file, parse_formula, proof_line_to_string, MathFile, Formula, FormulaChar};
 use super::{
-    Reference,
+    Reference, Theorem, FormulaParser,
     compile_syntax, compile_definition, compile_axiom, compile_theorem,
+    compile_partial_theorem, assertions_are_proven, compile_formula,
+    search_proof, SearchedLine,
+    PartialTheorem, MinimizedLine,
+    minimize_proof, tidy_proof, proof_lines, useless_lines,
+    PropositionalSystem, tautology_error,
+    VariableNames, wff_to_string,
     CompileError,
-    Library
+    Library,
+    BuildCache, CachedFile, PreviousBuild, content_hash,
+    TheoremTask, verify_theorems
 };
 
 fn get_file_contents(dir: &mut String, filepath: &str) -> io::Result<String> {
@@ -15,27 +23,28 @@ fn get_file_contents(dir: &mut String, filepath: &str) -> io::Result<String> {
     Ok(contents)
 }
 
+/// Returns the number of syntaxes the ambiguity check of the syntax went up to, if it was cut short
 pub fn add_syndef_to_lib(
     math_file: MathFile, lib: &mut Library, references: &mut HashMap<String, Reference>
-) -> Result<(), CompileError> {
-    let (syntax, maybe_def) = compile_syntax(math_file, &lib.syntaxes)?;
+) -> Result<Option<usize>, CompileError> {
+    let (syntax, maybe_def, checked_size) = compile_syntax(math_file, &lib.syntaxes)?;
     lib.syntaxes.push(syntax);
     match maybe_def {
         Some((name, def)) => {
-            let def = compile_definition(name, def, &lib.syntaxes)?;
+            let def = compile_definition(name, def, &FormulaParser::new(&lib.syntaxes))?;
             let def_ref = Reference::DefinitionReference(lib.definitions.len());
             references.insert(def.name.clone(), def_ref);
             lib.definitions.push(def);
         },
         None => ()
     };
-    Ok(())
+    Ok(checked_size)
 }
 
 pub fn add_axiom_to_lib(
     math_file: MathFile, lib: &mut Library, references: &mut HashMap<String, Reference>
 ) -> Result<(), CompileError> {
-    let axiom = compile_axiom(math_file, &lib.syntaxes)?;
+    let axiom = compile_axiom(math_file, &FormulaParser::new(&lib.syntaxes))?;
     let axiom_ref = Reference::AxiomReference(lib.axioms.len(), 0);
     references.insert(axiom.name.clone(), axiom_ref);
     lib.axioms.push(axiom);
@@ -46,7 +55,7 @@ pub fn add_theo_to_lib(
     math_file: MathFile, lib: &mut Library, references: &mut HashMap<String, Reference>
 ) -> Result<(), CompileError> {
     let theorem = compile_theorem(
-        math_file, &lib.syntaxes, &lib.definitions, &lib.axioms, &lib.theorems, &references
+        math_file, &FormulaParser::new(&lib.syntaxes), &lib.definitions, &lib.axioms, &lib.theorems, &references
     )?;
     let theo_ref = Reference::TheoremReference(lib.theorems.len(), 0);
     references.insert(theorem.name.clone(), theo_ref);
@@ -54,18 +63,373 @@ pub fn add_theo_to_lib(
     Ok(())
 }
 
+/// Verifies a theorem whose proof may be incomplete, and writes the lines left to be proven,
+/// or else the warnings about the lines of the proof
 pub fn verify_theo(
     math_file: MathFile, lib: &mut Library, references: &mut HashMap<String, Reference>
-) -> Result<(), CompileError> {
-    let compilation_result = compile_theorem(
-        math_file, &lib.syntaxes, &lib.definitions, &lib.axioms, &lib.theorems, &references
-    );
-    match compilation_result {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e)
+) -> Result<(Vec<String>, Vec<String>), CompileError> {
+    let partial = compile_partial_theorem(
+        math_file, &FormulaParser::new(&lib.syntaxes), &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    if !partial.open_goals.is_empty() {
+        let goals = partial.open_goals.iter()
+            .map(|index| {
+                let formula = &partial.theorem.proof[partial.line_steps[index - 1]].resulting_formula;
+                format!("{index}: {}", wff_to_string(formula, &lib.syntaxes, &partial.names))
+            })
+            .collect();
+        return Ok((goals, Vec::new()));
+    };
+    assertions_are_proven(&partial.theorem)?;
+    let (unused, duplicates) = useless_lines(&partial);
+    let warnings = unused.iter()
+        .map(|index| format!("line {index} is not used to prove the assertions"))
+        .chain(duplicates.iter().map(|(index, first)| format!("line {index} proves the same formula as line {first}")))
+        .collect();
+    Ok((Vec::new(), warnings))
+}
+
+/// Proves a tautology from the propositional axioms of the library, and writes the proof lines
+pub fn prove_tautology(formula: Formula, lib: &Library) -> Result<Vec<String>, CompileError> {
+    let mut wffs = HashMap::new();
+    let mut objects = HashMap::new();
+    let formula = compile_formula(formula, &FormulaParser::new(&lib.syntaxes), &mut wffs, &mut objects)?;
+    let names = VariableNames::new(&wffs, &objects);
+    let Some(system) = PropositionalSystem::find(&lib.syntaxes, &lib.axioms, &lib.theorems) else {
+        return Err(CompileError::MissingPropositionalAxioms(0));
+    };
+    let steps = system.prove(&[], &formula)
+        .map_err(|e| tautology_error(e, &lib.syntaxes, &names, 0))?;
+    let mut lines = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        let used_hypots = step.used_steps.iter()
+            .map(|id| id + 1)
+            .collect::<Vec<_>>();
+        let Some(reference) = lib.reference_name(&step.reference) else {
+            return Err(CompileError::WeirdReference);
+        };
+        let formula = wff_to_string(&step.formula, &lib.syntaxes, &names);
+        lines.push(proof_line_to_string(index + 1, &used_hypots, &reference, &formula));
+    };
+    Ok(lines)
+}
+
+/// A theorem already in the library can only use the theorems before it
+fn usable_theorems<'a>(
+    file: &MathFile, lib: &'a Library, references: &HashMap<String, Reference>
+) -> Result<&'a [Theorem], CompileError> {
+    match file {
+        MathFile::Theorem { name, .. } => match references.get(name) {
+            Some(Reference::TheoremReference(theo_id, _)) => Ok(&lib.theorems[..*theo_id]),
+            _ => Ok(&lib.theorems[..])
+        },
+        _ => Err(CompileError::IncorrectFileType)
     }
 }
 
+/// The proof lines written by a proof search
+pub struct CompletedProof {
+    pub line_numbers: Vec<usize>,  // The new number of each line of the proof
+    pub replaced_lines: Vec<(usize, Vec<String>)>,  // The lines left to be proven, with the lines replacing them
+    pub added_lines: Vec<String>  // The lines to add at the end of the proof
+}
+
+/// Completes the proof of a theorem with a proof search, and writes the proof lines to change
+pub fn complete_proof(
+    file: MathFile, lib: &Library, references: &HashMap<String, Reference>, max_depth: usize, time_limit: Duration
+) -> Result<CompletedProof, CompileError> {
+    let usable_theorems = usable_theorems(&file, lib, references)?;
+    let partial = compile_partial_theorem(
+        file, &FormulaParser::new(&lib.syntaxes), &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    let searched = search_proof(
+        &partial, &lib.definitions, &lib.axioms, usable_theorems, max_depth, time_limit
+    )?;
+    let write_lines = |lines: &[SearchedLine]| lines.iter().map(|line| {
+        let reference = match line.reference {
+            Reference::HypothesisReference(hypot_id) => partial.hypothesis_names.get(hypot_id).cloned(),
+            reference => lib.reference_name(&reference)
+        };
+        let Some(reference) = reference else {
+            return Err(CompileError::WeirdReference);
+        };
+        let formula = wff_to_string(&line.formula, &lib.syntaxes, &partial.names);
+        Ok(proof_line_to_string(line.line_no, &line.used_lines, &reference, &formula))
+    }).collect::<Result<Vec<_>, _>>();
+    let replaced_lines = searched.goal_lines.iter()
+        .map(|(line_no, lines)| Ok((*line_no, write_lines(lines)?)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CompletedProof {
+        line_numbers: searched.line_numbers,
+        replaced_lines,
+        added_lines: write_lines(&searched.added_lines)?
+    })
+}
+
+/// Rewrites the complete proof of a theorem, and writes the new proof once it is verified,
+/// along with the number of lines of the former one
+fn rewrite_proof(
+    file: MathFile, lib: &Library, references: &HashMap<String, Reference>,
+    rewrite: impl FnOnce(&PartialTheorem, Vec<MinimizedLine>) -> Vec<MinimizedLine>
+) -> Result<(Vec<String>, usize), CompileError> {
+    let MathFile::Theorem { name, hypotheses, assertions, distinct, proof } = file else {
+        return Err(CompileError::IncorrectFileType);
+    };
+    let written_references = proof.iter()
+        .map(|(_, _, reference, _)| reference.clone())
+        .collect::<Vec<_>>();
+    let theorem_file = MathFile::Theorem {
+        name: name.clone(),
+        hypotheses: hypotheses.clone(),
+        assertions: assertions.clone(),
+        distinct: distinct.clone(),
+        proof
+    };
+    let parser = FormulaParser::new(&lib.syntaxes);
+    let partial = compile_partial_theorem(
+        theorem_file, &parser, &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    if !partial.open_goals.is_empty() {
+        return Err(CompileError::IncompleteProof(partial.open_goals));
+    };
+    assertions_are_proven(&partial.theorem)?;
+    let rewritten = rewrite(&partial, proof_lines(&partial));
+    let mut lines = Vec::new();
+    let mut proof = Vec::new();
+    for (index, line) in rewritten.iter().enumerate() {
+        let reference = match line.reference {
+            Some(reference) => lib.reference_name(&reference),
+            None => written_references.get(line.original_line - 1).cloned()
+        };
+        let Some(reference) = reference else {
+            return Err(CompileError::WeirdReference);
+        };
+        let used_lines = line.used_lines.iter().map(|used| used + 1).collect::<Vec<_>>();
+        let formula = wff_to_string(&line.formula, &lib.syntaxes, &partial.names);
+        lines.push(proof_line_to_string(index + 1, &used_lines, &reference, &formula));
+        proof.push((index + 1, used_lines, reference, Some(parse_formula(&formula))));
+    };
+    // The new proof is verified as it will be written
+    compile_theorem(
+        MathFile::Theorem { name, hypotheses, assertions, distinct, proof },
+        &parser, &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    Ok((lines, partial.line_steps.len()))
+}
+
+/// Shortens the proof of a theorem with the theorems of the library
+pub fn minimize(
+    file: MathFile, lib: &Library, references: &HashMap<String, Reference>
+) -> Result<(Vec<String>, usize), CompileError> {
+    let usable_theorems = usable_theorems(&file, lib, references)?;
+    rewrite_proof(file, lib, references, |partial, lines| {
+        minimize_proof(lines, &partial.theorem, &lib.definitions, &lib.axioms, usable_theorems)
+    })
+}
+
+/// Removes the unused and duplicate lines of the proof of a theorem
+pub fn tidy(
+    file: MathFile, lib: &Library, references: &HashMap<String, Reference>
+) -> Result<(Vec<String>, usize), CompileError> {
+    rewrite_proof(file, lib, references, |partial, lines| tidy_proof(lines, &partial.theorem.assertions))
+}
+
+/// Writes the proof lines whose formula or used hypotheses were left to be inferred, as they were compiled
+pub fn fill_proof(
+    file: MathFile, lib: &Library, references: &HashMap<String, Reference>
+) -> Result<Vec<(usize, String)>, CompileError> {
+    let MathFile::Theorem { proof, .. } = &file else {
+        return Err(CompileError::IncorrectFileType);
+    };
+    let references_names = proof.iter()
+        .map(|(line_no, _, reference, _)| (*line_no, reference.clone()))
+        .collect::<HashMap<_, _>>();
+    let partial = compile_partial_theorem(
+        file, &FormulaParser::new(&lib.syntaxes), &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    let mut lines = Vec::new();
+    for line_no in &partial.inferred_lines {
+        let (Some(reference), Some(step_id)) = (references_names.get(line_no), partial.line_steps.get(line_no - 1)) else {
+            continue;
+        };
+        let step = &partial.theorem.proof[*step_id];
+        // The hypotheses used by a line are the last steps of earlier lines
+        let used_lines = step.used_hypotheses.iter()
+            .filter_map(|id| partial.line_steps.iter().position(|step_id| step_id == id))
+            .map(|index| index + 1)
+            .collect::<Vec<_>>();
+        let formula = wff_to_string(&step.resulting_formula, &lib.syntaxes, &partial.names);
+        lines.push((*line_no, proof_line_to_string(*line_no, &used_lines, reference, &formula)));
+    };
+    Ok(lines)
+}
+
+/// The names of the syntax definitions of a library, in the order they are compiled in
+pub fn syntax_names(mut dir: String) -> Result<Vec<String>, CompileError> {
+    let Ok(order) = get_file_contents(&mut dir, "/order.txt") else {
+        return Err(CompileError::OrderFileNotFound);
+    };
+    let mut names = Vec::new();
+    let mut in_syntaxes = false;
+    for (line_no, line) in order.lines().enumerate() {
+        if line.starts_with('#') {
+            in_syntaxes = line == "# Syntax Definitions";
+            continue;
+        };
+        if !in_syntaxes || line.is_empty() { continue; };
+        let file_content = get_file_contents(&mut dir, line)
+            .map_err(|e| CompileError::IOError(e, line.to_string(), line_no+1))?;
+        let Ok(MathFile::SyntaxDefinition { name, .. }) = parse_file(file_content) else {
+            return Err(CompileError::UnparsableFile(line.to_owned(), line_no+1));
+        };
+        names.push(name);
+    };
+    Ok(names)
+}
+
+/// A file of the library folders, with the files it needs to be compiled after
+struct ScannedFile {
+    path: String,
+    name: String,
+    section: usize,  // 0 for syntax definitions, 1 for axioms and 2 for theorems
+    dependencies: Vec<usize>
+}
+
+const SECTIONS: [(&str, &str); 3] = [
+    ("# Syntax Definitions", "/syntax_definitions"),
+    ("# Axioms", "/axioms"),
+    ("# Theorems", "/theorems")
+];
+
+/// The symbols of a formula, spaces and variables left out
+fn formula_symbols(formula: &Formula) -> Vec<char> {
+    formula.iter()
+        .filter_map(|c| match c {
+            FormulaChar::Char(c) if !c.is_whitespace() => Some(*c),
+            _ => None
+        })
+        .collect()
+}
+
+/// Lists the files of the syntax definition, axiom and theorem folders, and the order they can be compiled in,
+/// in the format of 'order.txt'. A theorem comes after the definitions, axioms and theorems its proof cites,
+/// and a syntax after the syntaxes its definition uses, a formula being taken to use a syntax when it contains
+/// all of its symbols. Among the files that can come next, the one listed first in 'order.txt' is taken
+pub fn dependency_order(mut dir: String) -> Result<String, CompileError> {
+    let current_order = get_file_contents(&mut dir, "/order.txt").unwrap_or_default();
+    let mut files = Vec::new();
+    let mut contents = Vec::new();
+    for (section, (_, folder)) in SECTIONS.iter().enumerate() {
+        let entries = fs::read_dir(dir.clone() + folder)
+            .map_err(|e| CompileError::IOError(e, folder.to_string(), 0))?;
+        let mut paths = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(|file_name| format!("{folder}/{file_name}")))
+            .collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            let file_content = get_file_contents(&mut dir, &path)
+                .map_err(|e| CompileError::IOError(e, path.clone(), 0))?;
+            let Ok(math_file) = parse_file(file_content) else {
+                return Err(CompileError::UnparsableLibraryFile(path));
+            };
+            let name = match &math_file {
+                MathFile::SyntaxDefinition { name, .. }
+                | MathFile::Axiom { name, .. }
+                | MathFile::Theorem { name, .. } => name.clone()
+            };
+            files.push(ScannedFile { path, name, section, dependencies: Vec::new() });
+            contents.push(math_file);
+        };
+    };
+
+    let indices = files.iter()
+        .enumerate()
+        .map(|(index, file)| (file.name.clone(), index))
+        .collect::<HashMap<_, _>>();
+    let symbols = contents.iter()
+        .map(|math_file| match math_file {
+            MathFile::SyntaxDefinition { syntax, .. } => formula_symbols(syntax),
+            _ => Vec::new()
+        })
+        .collect::<Vec<_>>();
+    for (index, math_file) in contents.iter().enumerate() {
+        let dependencies = match math_file {
+            MathFile::SyntaxDefinition { definition: Some(definition), .. } => {
+                let used_symbols = formula_symbols(definition);
+                (0..files.len())
+                    .filter(|other| *other != index && files[*other].section == 0 && !symbols[*other].is_empty())
+                    .filter(|other| symbols[*other].iter().all(|c| used_symbols.contains(c)))
+                    .collect()
+            },
+            // The names the compiler does not know of are reported when the proof is compiled
+            MathFile::Theorem { proof, .. } => proof.iter()
+                .filter_map(|(_, _, reference, _)| {
+                    let name = reference.split_once('.').map_or(reference.as_str(), |(name, _)| name);
+                    indices.get(name).copied()
+                })
+                .filter(|used| *used != index)
+                .collect(),
+            _ => Vec::new()
+        };
+        files[index].dependencies = dependencies;
+    };
+
+    // Kahn's algorithm, taking the files in the order of the sections, and then of 'order.txt'
+    let priority = |file: &ScannedFile| {
+        let listed_at = current_order.lines().position(|line| line == file.path);
+        (file.section, listed_at.unwrap_or(usize::MAX), file.path.clone())
+    };
+    let mut remaining_dependencies = files.iter().map(|file| file.dependencies.len()).collect::<Vec<_>>();
+    let mut users = vec![Vec::new(); files.len()];
+    for (index, file) in files.iter().enumerate() {
+        file.dependencies.iter().for_each(|used| users[*used].push(index));
+    };
+    let mut available = (0..files.len())
+        .filter(|index| remaining_dependencies[*index] == 0)
+        .map(|index| Reverse((priority(&files[index]), index)))
+        .collect::<BinaryHeap<_>>();
+    let mut sorted = Vec::new();
+    while let Some(Reverse((_, index))) = available.pop() {
+        sorted.push(index);
+        for user in &users[index] {
+            remaining_dependencies[*user] -= 1;
+            if remaining_dependencies[*user] == 0 {
+                available.push(Reverse((priority(&files[*user]), *user)));
+            };
+        };
+    };
+    if sorted.len() < files.len() {
+        // Every file left depends on another file left, so following the dependencies ends up going in circles
+        let mut path = vec![(0..files.len()).find(|index| remaining_dependencies[*index] > 0).unwrap_or(0)];
+        let cycle_start = loop {
+            let current = path[path.len() - 1];
+            let Some(next) = files[current].dependencies.iter().find(|used| remaining_dependencies[**used] > 0) else {
+                break 0;
+            };
+            if let Some(start) = path.iter().position(|index| index == next) { break start; };
+            path.push(*next);
+        };
+        let cycle = path[cycle_start..].iter()
+            .map(|index| files[*index].path.clone())
+            .collect();
+        return Err(CompileError::DependencyCycle(cycle));
+    };
+
+    let mut order = String::new();
+    for (section, (header, _)) in SECTIONS.iter().enumerate() {
+        if section > 0 { order.push('\n'); };
+        order.push_str(header);
+        order.push('\n');
+        for index in sorted.iter().filter(|index| files[**index].section == section) {
+            order.push_str(&files[*index].path);
+            order.push('\n');
+        };
+    };
+    Ok(order)
+}
+
 enum CompilerState {
     CompilingSyntaxes,
     CompilingAxioms,
@@ -73,7 +437,42 @@ enum CompilerState {
     Waiting
 }
 
-pub fn compile_directory(mut dir: String) -> Result<Library, CompileError> {
+/// The files defining the definitions, axioms and theorems a proof cites, each listed once.
+/// 'item_paths' gives the files of the definitions, of the axioms and of the theorems compiled so far
+fn cited_files(math_file: &MathFile, references: &HashMap<String, Reference>, item_paths: &[Vec<String>; 3]) -> Vec<String> {
+    let MathFile::Theorem { proof, .. } = math_file else { return Vec::new(); };
+    let mut files = Vec::new();
+    for (_, _, reference, _) in proof {
+        let name = reference.split_once('.').map_or(reference.as_str(), |(name, _)| name);
+        let file = match references.get(name) {
+            Some(Reference::DefinitionReference(def_id)) => item_paths[0].get(*def_id),
+            Some(Reference::AxiomReference(ax_id, _)) => item_paths[1].get(*ax_id),
+            Some(Reference::TheoremReference(theo_id, _)) => item_paths[2].get(*theo_id),
+            _ => None
+        };
+        let Some(file) = file else { continue; };
+        if !files.contains(file) { files.push(file.clone()); };
+    };
+    files
+}
+
+/// Compiles the library in the order of 'order.txt'. The theorems whose files did not change since
+/// the last compilation, and whose proofs only cite such theorems, are taken from the previous library
+pub fn compile_directory(mut dir: String, jobs: usize) -> Result<(Library, BuildCache), CompileError> {
+    let Ok(order) = get_file_contents(&mut dir, "/order.txt") else {
+        return Err(CompileError::OrderFileNotFound);
+    };
+    compile_in_order(dir, &order, jobs)
+}
+
+/// Compiles the library in the order worked out from the files themselves, whatever 'order.txt' says
+pub fn compile_sorted_directory(dir: String, jobs: usize) -> Result<(Library, BuildCache), CompileError> {
+    let order = dependency_order(dir.clone())?;
+    compile_in_order(dir, &order, jobs)
+}
+
+/// The syntaxes and axioms are compiled one by one, and the theorems then verified on 'jobs' threads
+fn compile_in_order(mut dir: String, order: &str, jobs: usize) -> Result<(Library, BuildCache), CompileError> {
     let mut lib = Library {
         syntaxes: Vec::new(),
         definitions: Vec::new(),
@@ -82,9 +481,18 @@ pub fn compile_directory(mut dir: String) -> Result<Library, CompileError> {
     };
     let mut references = HashMap::new();
     let mut state = CompilerState::Waiting;
-    let Ok(order) = get_file_contents(&mut dir, "/order.txt") else {
-        return Err(CompileError::OrderFileNotFound);
-    };
+    let mut previous = PreviousBuild::load(&dir);
+    let mut statements_unchanged = None;
+    let mut files = Vec::new();
+    let mut item_paths = [Vec::new(), Vec::new(), Vec::new()];
+    let mut theorem_ids = HashMap::new();
+    let mut theorems = Vec::new();
+    let mut tasks = Vec::new();
+    let mut unchecked_syntaxes = Vec::new();
+    // The grammar of the syntaxes is built once they are all compiled
+    let mut parser = None;
+    // The theorems before a line the compiler cannot go past may fail first, so the error waits for them
+    let mut order_error = None;
     for (line_no, line) in order.lines().enumerate() {
         if line == "" { continue; };
         if line.starts_with('#') {
@@ -101,26 +509,91 @@ pub fn compile_directory(mut dir: String) -> Result<Library, CompileError> {
                     state = CompilerState::CompilingTheorems;
                     continue;
                 },
-                _ => return Err(CompileError::InvalidOrderLine(line.to_owned(), line_no+1))
+                _ => {
+                    order_error = Some(CompileError::InvalidOrderLine(line.to_owned(), line_no+1));
+                    break;
+                }
             }
         };
-        let file_content = get_file_contents(&mut dir, line)
-            .map_err(|e| CompileError::IOError(e, line.to_string(), line_no+1))?;
+        let file_content = match get_file_contents(&mut dir, line) {
+            Ok(file_content) => file_content,
+            Err(e) => {
+                order_error = Some(CompileError::IOError(e, line.to_string(), line_no+1));
+                break;
+            }
+        };
+        let hash = content_hash(file_content.as_bytes());
         let Ok(math_file) = parse_file(file_content) else {
-            return Err(CompileError::UnparsableFile(line.to_owned(), line_no+1));
+            order_error = Some(CompileError::UnparsableFile(line.to_owned(), line_no+1));
+            break;
         };
+        let path = line.to_owned();
         match state {
             CompilerState::Waiting => (),
             CompilerState::CompilingSyntaxes => {
-                add_syndef_to_lib(math_file, &mut lib, &mut references)?;
+                let def_count = lib.definitions.len();
+                parser = None;
+                if let Some(checked_size) = add_syndef_to_lib(math_file, &mut lib, &mut references)? {
+                    unchecked_syntaxes.push((path.clone(), checked_size));
+                };
+                if lib.definitions.len() > def_count { item_paths[0].push(path.clone()); };
+                files.push(CachedFile { path, section: 0, hash, dependencies: Vec::new() });
             },
             CompilerState::CompilingAxioms => {
-                add_axiom_to_lib(math_file, &mut lib, &mut references)?;
+                let parser = parser.get_or_insert_with(|| FormulaParser::new(&lib.syntaxes));
+                let axiom = compile_axiom(math_file, parser)?;
+                references.insert(axiom.name.clone(), Reference::AxiomReference(lib.axioms.len(), 0));
+                lib.axioms.push(axiom);
+                item_paths[1].push(path.clone());
+                files.push(CachedFile { path, section: 1, hash, dependencies: Vec::new() });
             },
             CompilerState::CompilingTheorems => {
-                add_theo_to_lib(math_file, &mut lib, &mut references)?;
+                let dependencies = cited_files(&math_file, &references, &item_paths);
+                let file = CachedFile { path: path.clone(), section: 2, hash, dependencies };
+                // The previous theorems refer to the syntaxes, definitions and axioms by their indices
+                let statements_unchanged = *statements_unchanged.get_or_insert_with(|| {
+                    previous.as_ref().is_some_and(|previous| previous.same_statements(&files))
+                });
+                let reused = previous.as_mut()
+                    .filter(|_| statements_unchanged)
+                    .and_then(|previous| previous.reuse_theorem(&file, &theorem_ids));
+                let theo_id = theorems.len();
+                theorem_ids.insert(path.clone(), (theo_id, reused.is_some()));
+                let name = match (&reused, &math_file) {
+                    (Some(theorem), _) => Some(theorem.name.clone()),
+                    (None, MathFile::Theorem { name, .. }) => Some(name.clone()),
+                    (None, _) => None
+                };
+                match reused {
+                    Some(theorem) => theorems.push(Some(theorem)),
+                    None => {
+                        // Tautologies are proven with whatever propositional axioms are found among the earlier theorems
+                        let proves_tautologies = match &math_file {
+                            MathFile::Theorem { proof, .. } => proof.iter().any(|(_, _, reference, _)| reference == "taut"),
+                            _ => false
+                        };
+                        let dependencies = match proves_tautologies {
+                            true => (0..theo_id).collect(),
+                            false => file.dependencies.iter()
+                                .filter_map(|used| theorem_ids.get(used).map(|(used_id, _)| *used_id))
+                                .collect()
+                        };
+                        tasks.push(TheoremTask { id: theo_id, math_file, dependencies });
+                        theorems.push(None);
+                    }
+                };
+                if let Some(name) = name {
+                    references.insert(name, Reference::TheoremReference(theo_id, 0));
+                };
+                item_paths[2].push(path);
+                files.push(file);
             }
         };
     };
-    Ok(lib)
+    let reverified_theorems = tasks.len();
+    let parser = parser.unwrap_or_else(|| FormulaParser::new(&lib.syntaxes));
+    let (theorems, error) = verify_theorems(&lib, &parser, &references, tasks, theorems, jobs);
+    if let Some(e) = error.or(order_error) { return Err(e); };
+    lib.theorems = theorems.into_iter().flatten().collect();
+    Ok((lib, BuildCache { files, reverified_theorems, unchecked_syntaxes }))
 }
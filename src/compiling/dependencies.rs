@@ -0,0 +1,250 @@
+use std::collections::{HashMap, VecDeque};
+use super::{Reference, Library, WellFormedFormula, Object};
+
+/// A syntax, definition, axiom or theorem of the library, by its index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LibraryItem {
+    Syntax(usize),
+    Definition(usize),
+    Axiom(usize),
+    Theorem(usize)
+}
+
+impl LibraryItem {
+    /// The definition, axiom or theorem a proof step refers to, whatever the assertion it uses
+    fn referenced(reference: Reference) -> Option<LibraryItem> {
+        match reference {
+            Reference::DefinitionReference(def_id) => Some(LibraryItem::Definition(def_id)),
+            Reference::AxiomReference(ax_id, _) => Some(LibraryItem::Axiom(ax_id)),
+            Reference::TheoremReference(theo_id, _) => Some(LibraryItem::Theorem(theo_id)),
+            Reference::HypothesisReference(_) | Reference::UnprovenReference => None
+        }
+    }
+
+    /// The item a name refers to, the syntaxes being named after their files
+    pub fn from_name(name: &str, references: &HashMap<String, Reference>, syntax_names: &[String]) -> Option<LibraryItem> {
+        match references.get(name) {
+            Some(reference) => LibraryItem::referenced(*reference),
+            None => syntax_names.iter().position(|syntax_name| syntax_name == name).map(LibraryItem::Syntax)
+        }
+    }
+
+    /// The name files use to refer to the item
+    pub fn name(&self, lib: &Library, syntax_names: &[String]) -> String {
+        let name = match self {
+            LibraryItem::Syntax(syntax_id) => syntax_names.get(*syntax_id),
+            LibraryItem::Definition(def_id) => lib.definitions.get(*def_id).map(|def| &def.name),
+            LibraryItem::Axiom(ax_id) => lib.axioms.get(*ax_id).map(|axiom| &axiom.name),
+            LibraryItem::Theorem(theo_id) => lib.theorems.get(*theo_id).map(|theorem| &theorem.name)
+        };
+        name.cloned().unwrap_or_else(|| "?".to_string())
+    }
+
+    /// The item whose file defines this one, a definition being written in the file of its syntax
+    pub fn file_item(self, lib: &Library, syntax_names: &[String]) -> LibraryItem {
+        let LibraryItem::Definition(def_id) = self else { return self; };
+        syntax_names.iter()
+            .position(|syntax_name| Some(syntax_name) == lib.definitions.get(def_id).map(|def| &def.name))
+            .map_or(self, LibraryItem::Syntax)
+    }
+}
+
+fn wff_syntaxes(wff: &WellFormedFormula, syntaxes: &mut Vec<usize>) {
+    if let WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } = wff {
+        if !syntaxes.contains(syntax_ref) { syntaxes.push(*syntax_ref); };
+        wff_parameters.iter().for_each(|wff| wff_syntaxes(wff, syntaxes));
+        object_parameters.iter().for_each(|obj| object_syntaxes(obj, syntaxes));
+    };
+}
+
+fn object_syntaxes(obj: &Object, syntaxes: &mut Vec<usize>) {
+    if let Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } = obj {
+        if !syntaxes.contains(syntax_ref) { syntaxes.push(*syntax_ref); };
+        wff_parameters.iter().for_each(|wff| wff_syntaxes(wff, syntaxes));
+        object_parameters.iter().for_each(|obj| object_syntaxes(obj, syntaxes));
+    };
+}
+
+/// The syntaxes written in the statement and the proof of a theorem, and the definitions, axioms and theorems
+/// its proof refers to, each listed once
+pub fn direct_dependencies(lib: &Library, theo_id: usize) -> Vec<LibraryItem> {
+    let theorem = &lib.theorems[theo_id];
+    let mut syntaxes = Vec::new();
+    theorem.hypotheses.iter()
+        .chain(&theorem.assertions)
+        .chain(theorem.proof.iter().map(|step| &step.resulting_formula))
+        .for_each(|wff| wff_syntaxes(wff, &mut syntaxes));
+    syntaxes.sort();
+    let mut res = syntaxes.into_iter().map(LibraryItem::Syntax).collect::<Vec<_>>();
+    for step in &theorem.proof {
+        let Some(item) = LibraryItem::referenced(step.theorem_ref) else { continue; };
+        if !res.contains(&item) { res.push(item); };
+    };
+    res
+}
+
+/// The axioms and definitions a theorem rests on, the axioms first, each with the shortest chain
+/// of theorems leading to it from the theorem (the theorem included)
+pub fn axioms_used(lib: &Library, theo_id: usize) -> Vec<(LibraryItem, Vec<usize>)> {
+    let mut parents = vec![None; lib.theorems.len()];
+    let mut visited = vec![false; lib.theorems.len()];
+    let mut queue = VecDeque::from([theo_id]);
+    visited[theo_id] = true;
+    let mut found = Vec::new();
+    while let Some(current) = queue.pop_front() {
+        for item in direct_dependencies(lib, current) {
+            match item {
+                LibraryItem::Syntax(_) => continue,
+                LibraryItem::Theorem(used_id) => {
+                    if visited[used_id] { continue; };
+                    visited[used_id] = true;
+                    parents[used_id] = Some(current);
+                    queue.push_back(used_id);
+                },
+                LibraryItem::Definition(_) | LibraryItem::Axiom(_) => {
+                    if found.iter().any(|(found_item, _)| *found_item == item) { continue; };
+                    let mut path = vec![current];
+                    while let Some(parent) = parents[path[path.len() - 1]] {
+                        path.push(parent);
+                    };
+                    path.reverse();
+                    found.push((item, path));
+                }
+            };
+        };
+    };
+    found.sort_by_key(|(item, _)| match item {
+        LibraryItem::Axiom(ax_id) => (0, *ax_id),
+        LibraryItem::Definition(def_id) => (1, *def_id),
+        _ => (2, 0)
+    });
+    found
+}
+
+/// For every item, the theorems that depend on it directly
+pub fn reverse_index(lib: &Library) -> HashMap<LibraryItem, Vec<usize>> {
+    let mut users = HashMap::<LibraryItem, Vec<usize>>::new();
+    for theo_id in 0..lib.theorems.len() {
+        for item in direct_dependencies(lib, theo_id) {
+            users.entry(item).or_default().push(theo_id);
+        };
+    };
+    users
+}
+
+/// The theorems that depend on an item, directly (at depth 1) or through at most 'max_depth' theorems,
+/// with the depth they are first found at
+pub fn used_by(lib: &Library, item: LibraryItem, max_depth: Option<usize>) -> Vec<(usize, usize)> {
+    let users = reverse_index(lib);
+    let mut found = Vec::new();
+    let mut visited = vec![false; lib.theorems.len()];
+    let mut queue = VecDeque::from([(item, 0)]);
+    while let Some((current, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) { continue; };
+        for theo_id in users.get(&current).into_iter().flatten() {
+            if visited[*theo_id] { continue; };
+            visited[*theo_id] = true;
+            found.push((*theo_id, depth + 1));
+            queue.push_back((LibraryItem::Theorem(*theo_id), depth + 1));
+        };
+    };
+    found
+}
+
+/// The items that could not be compiled without an item, directly or through other items.
+/// Definitions are left out, as they go along with their syntax
+pub fn dependents(lib: &Library, item: LibraryItem, syntax_names: &[String]) -> Vec<LibraryItem> {
+    let item = item.file_item(lib, syntax_names);
+    let users = reverse_index(lib);
+    let mut found = Vec::new();
+    let mut queue = VecDeque::from([item]);
+    while let Some(current) = queue.pop_front() {
+        let mut direct = users.get(&current).into_iter()
+            .flatten()
+            .map(|theo_id| LibraryItem::Theorem(*theo_id))
+            .collect::<Vec<_>>();
+        if let LibraryItem::Syntax(syntax_id) = current {
+            for (def_id, definition) in lib.definitions.iter().enumerate() {
+                let def_item = LibraryItem::Definition(def_id).file_item(lib, syntax_names);
+                // The theorems using the definition of the syntax depend on the syntax
+                if def_item == current {
+                    let def_users = users.get(&LibraryItem::Definition(def_id)).into_iter().flatten();
+                    direct.extend(def_users.map(|theo_id| LibraryItem::Theorem(*theo_id)));
+                    continue;
+                };
+                let mut syntaxes = Vec::new();
+                wff_syntaxes(&definition.definition, &mut syntaxes);
+                if syntaxes.contains(&syntax_id) { direct.push(def_item); };
+            };
+            for (ax_id, axiom) in lib.axioms.iter().enumerate() {
+                let mut syntaxes = Vec::new();
+                axiom.hypotheses.iter()
+                    .chain(&axiom.assertions)
+                    .for_each(|wff| wff_syntaxes(wff, &mut syntaxes));
+                if syntaxes.contains(&syntax_id) { direct.push(LibraryItem::Axiom(ax_id)); };
+            };
+        };
+        for dependent in direct {
+            if dependent == item || found.contains(&dependent) { continue; };
+            found.push(dependent);
+            queue.push_back(dependent);
+        };
+    };
+    found.sort();
+    found
+}
+
+fn dot_node(item: LibraryItem, lib: &Library) -> String {
+    let name = item.name(lib, &[]).replace('"', "\\\"");
+    let color = match item {
+        LibraryItem::Syntax(_) => "white",  // Syntaxes are not part of the graph
+        LibraryItem::Definition(_) => "lightyellow",
+        LibraryItem::Axiom(_) => "lightblue",
+        LibraryItem::Theorem(_) => "palegreen"
+    };
+    format!("    \"{name}\" [fillcolor={color}];\n")
+}
+
+/// The dependency graph of the definitions, axioms and theorems in the DOT format of Graphviz, with an edge
+/// from each theorem to what its proof refers to. Only the items at most 'max_depth' edges away from the root
+/// are kept, the root being the given theorem, or else every item no theorem depends on.
+/// Returns the graph, with its number of nodes and of edges
+pub fn dependency_graph(lib: &Library, root: Option<usize>, max_depth: Option<usize>) -> (String, usize, usize) {
+    let roots = match root {
+        Some(theo_id) => vec![LibraryItem::Theorem(theo_id)],
+        None => {
+            let users = reverse_index(lib);
+            (0..lib.definitions.len()).map(LibraryItem::Definition)
+                .chain((0..lib.axioms.len()).map(LibraryItem::Axiom))
+                .chain((0..lib.theorems.len()).map(LibraryItem::Theorem))
+                .filter(|item| !users.contains_key(item))
+                .collect()
+        }
+    };
+    let mut nodes = roots.clone();
+    let mut edges = Vec::new();
+    let mut queue = roots.into_iter().map(|item| (item, 0)).collect::<VecDeque<_>>();
+    while let Some((item, depth)) = queue.pop_front() {
+        let LibraryItem::Theorem(theo_id) = item else { continue; };
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) { continue; };
+        for used in direct_dependencies(lib, theo_id) {
+            if let LibraryItem::Syntax(_) = used { continue; };
+            edges.push((item, used));
+            if nodes.contains(&used) { continue; };
+            nodes.push(used);
+            queue.push_back((used, depth + 1));
+        };
+    };
+    nodes.sort();
+    let mut res = String::from("digraph library {\n    rankdir=BT;\n    node [style=filled];\n");
+    for node in &nodes {
+        res.push_str(&dot_node(*node, lib));
+    };
+    for (user, used) in &edges {
+        let user = user.name(lib, &[]).replace('"', "\\\"");
+        let used = used.name(lib, &[]).replace('"', "\\\"");
+        res.push_str(&format!("    \"{user}\" -> \"{used}\";\n"));
+    };
+    res.push_str("}\n");
+    (res, nodes.len(), edges.len())
+}
@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use super::{
+    Syntax, Placeholder,
+    WellFormedFormula, Object,
+    precedence_allows, repetition_indices
+};
+
+// Order in which the variables are named when their original names are unknown
+pub(super) const WFF_NAMES: [char; 25] = [
+    '𝜑', '𝜓', '𝜒', '𝜃', '𝜏', '𝜂', '𝜁', '𝜎', '𝜌', '𝜇', '𝜆', '𝜅', '𝜄',
+    '𝛼', '𝛽', '𝛾', '𝛿', '𝜀', '𝜈', '𝜉', '𝜊', '𝜋', '𝜍', '𝜐', '𝜔'
+];
+pub(super) const OBJECT_NAMES: [char; 25] = [
+    '𝑥', '𝑦', '𝑧', '𝑤', '𝑣', '𝑢', '𝑡', '𝑠', '𝑟', '𝑞', '𝑝', '𝑎', '𝑏',
+    '𝑐', '𝑑', '𝑒', '𝑓', '𝑔', '𝑖', '𝑗', '𝑘', '𝑙', '𝑚', '𝑛', '𝑜'
+];
+
+/// The characters used to write the variables of a formula
+pub struct VariableNames {
+    wffs: Vec<char>,
+    objects: Vec<char>
+}
+
+fn complete_names(mut names: Vec<Option<char>>, default_names: &[char]) -> Vec<char> {
+    let unused = default_names.iter()
+        .filter(|c| !names.contains(&Some(**c)))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut unused = unused.into_iter();
+    for name in names.iter_mut() {
+        if name.is_none() {
+            *name = unused.next();
+        };
+    };
+    names.into_iter()
+        .map(|name| name.unwrap_or('?'))
+        .collect()
+}
+
+impl VariableNames {
+    /// Names the variables after the characters they were compiled from (see compile_formula),
+    /// the other variables get the first names that are not already taken
+    pub fn new(wffs: &HashMap<usize, WellFormedFormula>, objects: &HashMap<usize, Object>) -> VariableNames {
+        let mut wff_names = vec![None; WFF_NAMES.len()];
+        for (id, wff) in wffs {
+            if let WellFormedFormula::Atomic(n) = wff {
+                if let Some(name) = wff_names.get_mut(*n) {
+                    *name = char::from_u32('𝛼' as u32 + *id as u32);
+                };
+            };
+        };
+        let mut object_names = vec![None; OBJECT_NAMES.len()];
+        for (id, obj) in objects {
+            if let Object::Atomic(n) = obj {
+                if let Some(name) = object_names.get_mut(*n) {
+                    *name = char::from_u32('𝑎' as u32 + *id as u32);
+                };
+            };
+        };
+        VariableNames {
+            wffs: complete_names(wff_names, &WFF_NAMES),
+            objects: complete_names(object_names, &OBJECT_NAMES)
+        }
+    }
+}
+
+impl Default for VariableNames {
+    fn default() -> Self {
+        VariableNames { wffs: WFF_NAMES.to_vec(), objects: OBJECT_NAMES.to_vec() }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Parameter<'a> {
+    Wff(&'a WellFormedFormula),
+    Object(&'a Object)
+}
+
+pub(super) enum Piece<'a> {
+    Literal(char),
+    Parameter(Parameter<'a>)
+}
+
+impl Piece<'_> {
+    fn is_parameter(&self) -> bool {
+        matches!(self, Piece::Parameter(_))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum Parentheses {
+    Minimal,
+    Explicit,
+    Omitted  // Only the parentheses of the syntaxes themselves are written
+}
+
+struct Printer<'a> {
+    syntaxes: &'a [Syntax],
+    names: &'a VariableNames,
+    parentheses: Parentheses,
+    latex: bool
+}
+
+// The LaTeX commands of the greek letters, from '𝛼' to '𝜔'
+const GREEK_COMMANDS: [&str; 25] = [
+    "\\alpha", "\\beta", "\\gamma", "\\delta", "\\varepsilon", "\\zeta", "\\eta", "\\theta", "\\iota",
+    "\\kappa", "\\lambda", "\\mu", "\\nu", "\\xi", "o", "\\pi", "\\rho", "\\varsigma", "\\sigma", "\\tau",
+    "\\upsilon", "\\varphi", "\\chi", "\\psi", "\\omega"
+];
+const LATEX_SYMBOLS: [(char, &str); 27] = [
+    ('⇒', "\\Rightarrow"), ('⇔', "\\Leftrightarrow"), ('→', "\\to"), ('↔', "\\leftrightarrow"), ('¬', "\\neg"),
+    ('∧', "\\land"), ('∨', "\\lor"), ('∀', "\\forall"), ('∃', "\\exists"), ('∈', "\\in"), ('∉', "\\notin"),
+    ('≠', "\\neq"), ('⊆', "\\subseteq"), ('⊂', "\\subset"), ('∪', "\\cup"), ('∩', "\\cap"), ('∅', "\\emptyset"),
+    ('⊤', "\\top"), ('⊥', "\\bot"), ('≤', "\\leq"), ('≥', "\\geq"), ('×', "\\times"), ('∖', "\\setminus"),
+    ('⊢', "\\vdash"), ('∣', "\\mid"), ('…', "\\ldots"), ('·', "\\cdot")
+];
+
+/// A character of a formula in LaTeX math mode
+pub(super) fn latex_symbol(c: char) -> String {
+    if ('𝛼'..='𝜔').contains(&c) {
+        return GREEK_COMMANDS[c as usize - '𝛼' as usize].to_string();
+    };
+    if ('𝑎'..='𝑧').contains(&c) || c == 'ℎ' {
+        return char::from_u32(c as u32 - '𝑎' as u32 + 'a' as u32).map_or("h".to_string(), |c| c.to_string());
+    };
+    if let Some((_, command)) = LATEX_SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+        return command.to_string();
+    };
+    match c {
+        '{' | '}' | '#' | '%' | '&' | '_' | '$' => format!("\\{c}"),
+        '\\' => "\\backslash".to_string(),
+        '^' => "\\hat{}".to_string(),
+        '~' => "\\sim".to_string(),
+        c if c.is_ascii() => c.to_string(),
+        c => format!("\\text{{{c}}}")
+    }
+}
+
+/// Writes a formula with as few parentheses as the precedences of its syntaxes allow
+pub fn wff_to_string(wff: &WellFormedFormula, syntaxes: &[Syntax], names: &VariableNames) -> String {
+    parameter_to_string(Parameter::Wff(wff), syntaxes, names, Parentheses::Minimal)
+}
+
+/// Writes a formula with parentheses around every parameter that is not enclosed by its own characters,
+/// which shows how the formula is read even when the syntaxes are ambiguous
+pub fn wff_to_explicit_string(wff: &WellFormedFormula, syntaxes: &[Syntax], names: &VariableNames) -> String {
+    parameter_to_string(Parameter::Wff(wff), syntaxes, names, Parentheses::Explicit)
+}
+
+/// Writes a formula in LaTeX math mode, its syntaxes with their templates when they have one
+pub(super) fn parameter_to_latex(param: Parameter, syntaxes: &[Syntax], names: &VariableNames) -> String {
+    let printer = Printer { syntaxes, names, parentheses: Parentheses::Minimal, latex: true };
+    let mut res = String::new();
+    printer.write(param, &mut res);
+    res
+}
+
+/// Writes the formula of a syntax, its variables named in the usual order and spaced as in the formulas
+pub(super) fn syntax_to_string(syntax: &Syntax) -> String {
+    let is_parameter = |pl: &Placeholder| !matches!(pl, Placeholder::LiteralChar(_));
+    let formula = &syntax.formula;
+    let mut res = String::new();
+    for (index, pl) in formula.iter().enumerate() {
+        let parameter_before = formula[..index].iter().any(is_parameter);
+        let parameter_after = formula[index+1..].iter().any(is_parameter);
+        let after_parameter = index > 0 && is_parameter(&formula[index-1]);
+        match pl {
+            Placeholder::LiteralChar(c) => {
+                if after_parameter && parameter_after { res.push(' '); };
+                res.push(*c);
+                if formula.get(index+1).is_some_and(is_parameter) && parameter_before { res.push(' '); };
+                continue;
+            },
+            _ if after_parameter => res.push(' '),
+            _ => ()
+        };
+        res.push(match pl {
+            Placeholder::WellFormedFormula(id) => WFF_NAMES.get(*id).copied().unwrap_or('?'),
+            Placeholder::Object(id) => OBJECT_NAMES.get(*id).copied().unwrap_or('?'),
+            _ => '…'
+        });
+    };
+    res
+}
+
+/// Writes the formula of a syntax in LaTeX math mode, its variables named in the usual order
+pub(super) fn syntax_to_latex(syntax: &Syntax) -> String {
+    let mut res = String::new();
+    for pl in syntax.latex.as_ref().unwrap_or(&syntax.formula) {
+        match pl {
+            Placeholder::LiteralChar(c) if syntax.latex.is_some() => res.push(*c),
+            Placeholder::LiteralChar(c) => Printer::push_latex(&mut res, &latex_symbol(*c)),
+            Placeholder::WellFormedFormula(id) => {
+                let name = WFF_NAMES.get(*id).copied().unwrap_or('?');
+                Printer::push_latex(&mut res, &latex_symbol(name));
+            },
+            Placeholder::Object(id) => {
+                let name = OBJECT_NAMES.get(*id).copied().unwrap_or('?');
+                Printer::push_latex(&mut res, &latex_symbol(name));
+            },
+            Placeholder::Repetition => Printer::push_latex(&mut res, &latex_symbol('…'))
+        };
+    };
+    res
+}
+
+pub(super) fn parameter_to_string(
+    param: Parameter, syntaxes: &[Syntax], names: &VariableNames, parentheses: Parentheses
+) -> String {
+    let printer = Printer { syntaxes, names, parentheses, latex: false };
+    let mut res = String::new();
+    printer.write(param, &mut res);
+    res
+}
+
+/// The syntax and the characters and parameters of a composite, in the order they are written
+pub(super) fn pieces<'a>(syntaxes: &'a [Syntax], param: Parameter<'a>) -> Option<(&'a Syntax, Vec<Piece<'a>>)> {
+    written_pieces(syntaxes, param, false)
+}
+
+/// The pieces of a composite, taken from the LaTeX template of its syntax if asked and if there is one
+fn written_pieces<'a>(
+    syntaxes: &'a [Syntax], param: Parameter<'a>, latex: bool
+) -> Option<(&'a Syntax, Vec<Piece<'a>>)> {
+    let (syntax_ref, wff_parameters, object_parameters) = match param {
+        Parameter::Wff(WellFormedFormula::SyntaxComposite {
+            syntax_ref, wff_parameters, object_parameters
+        }) => (*syntax_ref, wff_parameters, object_parameters),
+        Parameter::Object(Object::SyntaxComposite {
+            syntax_ref, wff_parameters, object_parameters
+        }) => (*syntax_ref, wff_parameters, object_parameters),
+        _ => return None
+    };
+    let syntax = syntaxes.get(syntax_ref)?;
+    let repeated_count = match syntax.repetition().map(|(repeated_index, _)| syntax.formula[repeated_index]) {
+        Some(Placeholder::WellFormedFormula(_)) => wff_parameters.len().checked_sub(syntax.distinct_wff_count)?,
+        Some(Placeholder::Object(_)) => object_parameters.len().checked_sub(syntax.distinct_object_count)?,
+        _ => 0
+    };
+    // The template places the parameters as the formula does, the repeated one included
+    let formula = match (latex, &syntax.latex) {
+        (true, Some(template)) => template,
+        _ => &syntax.formula
+    };
+    let repetition = repetition_indices(formula);
+    let piece = |pl: &Placeholder| match pl {
+        Placeholder::LiteralChar(c) => Some(Piece::Literal(*c)),
+        Placeholder::WellFormedFormula(id) => {
+            // The repeated parameters come right after the placeholder preceding '…'
+            let index = match repetition.map(|(repeated_index, _)| formula[repeated_index]) {
+                Some(Placeholder::WellFormedFormula(repeated)) if *id > repeated => id + repeated_count,
+                _ => *id
+            };
+            Some(Piece::Parameter(Parameter::Wff(wff_parameters.get(index)?)))
+        },
+        Placeholder::Object(id) => {
+            let index = match repetition.map(|(repeated_index, _)| formula[repeated_index]) {
+                Some(Placeholder::Object(repeated)) if *id > repeated => id + repeated_count,
+                _ => *id
+            };
+            Some(Piece::Parameter(Parameter::Object(object_parameters.get(index)?)))
+        },
+        Placeholder::Repetition => None
+    };
+    let Some((repeated_index, rep_index)) = repetition else {
+        let pieces = formula.iter()
+            .map(piece)
+            .collect::<Option<Vec<_>>>()?;
+        return Some((syntax, pieces));
+    };
+    // 'prefix 𝜑 sep … sep 𝜓 suffix' is written with a 'sep element' for every repeated element
+    let mut pieces = formula[..=repeated_index].iter()
+        .map(piece)
+        .collect::<Option<Vec<_>>>()?;
+    let separator = &formula[repeated_index+1..rep_index];
+    for index in 0..repeated_count {
+        for pl in separator {
+            pieces.push(piece(pl)?);
+        };
+        let element = match formula[repeated_index] {
+            Placeholder::WellFormedFormula(id) => Parameter::Wff(wff_parameters.get(id + 1 + index)?),
+            Placeholder::Object(id) => Parameter::Object(object_parameters.get(id + 1 + index)?),
+            _ => return None
+        };
+        pieces.push(Piece::Parameter(element));
+    };
+    for pl in &formula[rep_index+1..] {
+        pieces.push(piece(pl)?);
+    };
+    Some((syntax, pieces))
+}
+
+impl<'a> Printer<'a> {
+    /// Whether a parameter at the left (or right) edge of a syntax must be put between parentheses
+    fn needs_parentheses(&self, parent: &Syntax, param: Parameter<'a>, left_edge: bool) -> bool {
+        let Some((syntax, pieces)) = written_pieces(self.syntaxes, param, self.latex) else { return false; };
+        if self.parentheses == Parentheses::Explicit {
+            return pieces.first().is_some_and(Piece::is_parameter) || pieces.last().is_some_and(Piece::is_parameter);
+        };
+        let facing_piece = match left_edge {
+            true => pieces.last(),
+            false => pieces.first()
+        };
+        let Some(Piece::Parameter(next)) = facing_piece else { return false; };
+        if self.parentheses == Parentheses::Omitted { return false; };
+        if parent.precedence.is_none() || syntax.precedence.is_none() { return true; };
+        if !precedence_allows(parent, syntax, left_edge) { return true; };
+        // The parameter on the same side of the child faces the parent too, unless it has its own parentheses
+        if self.needs_parentheses(syntax, *next, !left_edge) { return false; };
+        self.needs_parentheses(parent, *next, left_edge)
+    }
+
+    /// Writes a variable or a character in LaTeX, apart from the command written before it
+    fn push_latex(res: &mut String, symbol: &str) {
+        let letter_before = res.ends_with(|c: char| c.is_ascii_alphabetic());
+        if letter_before && symbol.starts_with(|c: char| c.is_ascii_alphabetic()) { res.push(' '); };
+        res.push_str(symbol);
+    }
+
+    fn write(&self, param: Parameter<'a>, res: &mut String) {
+        if self.latex {
+            self.write_latex(param, res);
+            return;
+        };
+        match param {
+            Parameter::Wff(WellFormedFormula::Atomic(id)) => {
+                res.push(self.names.wffs.get(*id).copied().unwrap_or('?'));
+                return;
+            },
+            Parameter::Object(Object::Atomic(id)) => {
+                res.push(self.names.objects.get(*id).copied().unwrap_or('?'));
+                return;
+            },
+            Parameter::Wff(WellFormedFormula::Repetition) | Parameter::Object(Object::Repetition) => {
+                res.push('…');
+                return;
+            },
+            Parameter::Wff(WellFormedFormula::WorkVariable(id)) => {
+                res.push_str(&format!("&W{id}"));
+                return;
+            },
+            Parameter::Object(Object::WorkVariable(id)) => {
+                res.push_str(&format!("&C{id}"));
+                return;
+            },
+            _ => ()
+        };
+        let Some((syntax, pieces)) = pieces(self.syntaxes, param) else {
+            res.push('?');
+            return;
+        };
+        self.write_pieces(syntax, &pieces, res);
+    }
+
+    fn write_latex(&self, param: Parameter<'a>, res: &mut String) {
+        let symbol = match param {
+            Parameter::Wff(WellFormedFormula::Atomic(id)) => latex_symbol(self.names.wffs.get(*id).copied().unwrap_or('?')),
+            Parameter::Object(Object::Atomic(id)) => latex_symbol(self.names.objects.get(*id).copied().unwrap_or('?')),
+            Parameter::Wff(WellFormedFormula::Repetition) | Parameter::Object(Object::Repetition) => latex_symbol('…'),
+            Parameter::Wff(WellFormedFormula::WorkVariable(id)) => format!("\\&W{id}"),
+            Parameter::Object(Object::WorkVariable(id)) => format!("\\&C{id}"),
+            _ => {
+                let Some((syntax, pieces)) = written_pieces(self.syntaxes, param, true) else {
+                    res.push('?');
+                    return;
+                };
+                // A template is written as it is, and the composite is kept in a group for its commands
+                res.push('{');
+                match syntax.latex {
+                    Some(_) => self.write_template(syntax, &pieces, res),
+                    None => self.write_pieces(syntax, &pieces, res)
+                };
+                res.push('}');
+                return;
+            }
+        };
+        Self::push_latex(res, &symbol);
+    }
+
+    fn write_template(&self, syntax: &Syntax, pieces: &[Piece<'a>], res: &mut String) {
+        let last_index = pieces.len() - 1;
+        for (index, piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Literal(c) => res.push(*c),
+                Piece::Parameter(param) => {
+                    let parentheses = match index {
+                        _ if pieces.len() < 2 => false,
+                        0 => self.needs_parentheses(syntax, *param, true),
+                        i if i == last_index => self.needs_parentheses(syntax, *param, false),
+                        _ => false
+                    };
+                    if parentheses { res.push('('); };
+                    self.write(*param, res);
+                    if parentheses { res.push(')'); };
+                }
+            };
+        };
+    }
+
+    fn write_pieces(&self, syntax: &Syntax, pieces: &[Piece<'a>], res: &mut String) {
+        let last_index = pieces.len() - 1;
+        for (index, piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Literal(c) => {
+                    // Characters between two parameters are surrounded with spaces, like in '(𝜑 ⇒ 𝜓)'
+                    let parameter_before = pieces[..index].iter().any(Piece::is_parameter);
+                    let parameter_after = pieces[index+1..].iter().any(Piece::is_parameter);
+                    if index > 0 && pieces[index-1].is_parameter() && parameter_after { res.push(' '); };
+                    match self.latex {
+                        true => Self::push_latex(res, &latex_symbol(*c)),
+                        false => res.push(*c)
+                    };
+                    if pieces.get(index+1).is_some_and(Piece::is_parameter) && parameter_before { res.push(' '); };
+                },
+                Piece::Parameter(param) => {
+                    if index > 0 && pieces[index-1].is_parameter() { res.push(' '); };
+                    let parentheses = match index {
+                        _ if pieces.len() < 2 => false,
+                        0 => self.needs_parentheses(syntax, *param, true),
+                        i if i == last_index => self.needs_parentheses(syntax, *param, false),
+                        _ => false
+                    };
+                    if parentheses { res.push('('); };
+                    self.write(*param, res);
+                    if parentheses { res.push(')'); };
+                }
+            };
+        };
+    }
+}
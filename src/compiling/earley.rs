@@ -0,0 +1,613 @@
+// This file is also compiled as part of the editor (see src_editor/library_data/mod.rs),
+// so it must only depend on the standard library and on the types it gets from 'super'
+use std::collections::{HashMap, HashSet};
+use super::{
+    Syntax, SyntaxType, Placeholder,
+    Precedence, Associativity,
+    WellFormedFormula, Object
+};
+
+/// Maximum number of parse trees built for a single ambiguous formula
+const MAX_PARSES: usize = 16;
+
+// Non-terminals of the grammar, those of the variadic syntaxes are numbered after these two
+const WFF: usize = 0;
+const OBJECT: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Char(char),
+    Wff(usize),
+    Object(usize),
+    Repetition,
+    WorkWff(usize),
+    WorkObject(usize)
+}
+
+/// Positions are indices in the list of tokens, and 'end' is excluded
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseTree {
+    Variable(usize),
+    Repetition(usize),
+    Group(Box<ParseTree>),  // Parentheses around a formula or an object
+    Composite {
+        syntax_ref: usize,
+        start: usize,
+        end: usize,
+        children: Vec<ParseTree>  // The placeholders (and repeated elements) in order of appearance
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Symbol {
+    Literal(char),
+    WffVariable,
+    ObjectVariable,
+    Repetition,
+    NonTerminal(usize)
+}
+
+enum RuleKind {
+    Syntax(usize),
+    Group,
+    Inline  // Its children are given directly to the parent (variables and lists of variadic syntaxes)
+}
+
+struct Rule {
+    lhs: usize,
+    rhs: Vec<Symbol>,
+    kind: RuleKind
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    dot: usize,
+    origin: usize
+}
+
+struct Chart {
+    sets: Vec<Vec<Item>>,
+    members: Vec<HashSet<Item>>,
+    completed: HashMap<(usize, usize), Vec<usize>>  // (non-terminal, end) => starts
+}
+
+impl Chart {
+    fn add(&mut self, position: usize, item: Item) {
+        if self.members[position].insert(item) {
+            self.sets[position].push(item);
+        };
+    }
+}
+
+enum Parameter {
+    Wff(WellFormedFormula),
+    Object(Object)
+}
+
+fn symbol(pl: &Placeholder) -> Symbol {
+    match pl {
+        Placeholder::LiteralChar(c) => Symbol::Literal(*c),
+        Placeholder::WellFormedFormula(_) => Symbol::NonTerminal(WFF),
+        Placeholder::Object(_) => Symbol::NonTerminal(OBJECT),
+        Placeholder::Repetition => Symbol::Repetition
+    }
+}
+
+/// Whether a terminal symbol reads a token
+fn scans(terminal: &Symbol, token: Option<&Token>) -> bool {
+    match (terminal, token) {
+        (Symbol::Literal(c1), Some(Token::Char(c2))) => c1 == c2,
+        (Symbol::WffVariable, Some(Token::Wff(_) | Token::WorkWff(_))) => true,
+        (Symbol::ObjectVariable, Some(Token::Object(_) | Token::WorkObject(_))) => true,
+        (Symbol::Repetition, Some(Token::Repetition)) => true,
+        _ => false
+    }
+}
+
+fn is_variable(pl: &Placeholder) -> bool {
+    matches!(pl, Placeholder::WellFormedFormula(_) | Placeholder::Object(_))
+}
+
+/// Whether a syntax can be a parameter at the left (or right) edge of another one without parentheses.
+/// It can always if its other side faces the parent, otherwise it must bind tighter than the parent,
+/// or as tight if the parent associates in its direction.
+/// The precedences of syntaxes that declare none are not restricted
+pub fn precedence_allows(parent: &Syntax, child: &Syntax, left_edge: bool) -> bool {
+    let facing_side = match left_edge {
+        true => child.formula.last(),
+        false => child.formula.first()
+    };
+    if !facing_side.is_some_and(is_variable) { return true; };
+    let (
+        Some(Precedence { level: parent_level, associativity }),
+        Some(Precedence { level: child_level, associativity: _ })
+    ) = (parent.precedence, child.precedence) else {
+        return true;
+    };
+    matches!(
+        (child_level.cmp(&parent_level), associativity, left_edge),
+        (std::cmp::Ordering::Greater, _, _)
+        | (std::cmp::Ordering::Equal, Associativity::Left, true)
+        | (std::cmp::Ordering::Equal, Associativity::Right, false)
+    )
+}
+
+/// The context-free grammar formed by a list of syntaxes, which parses formulas with the Earley algorithm
+pub struct Grammar<'a> {
+    syntaxes: Vec<&'a Syntax>,
+    rules: Vec<Rule>,
+    rules_by_lhs: Vec<Vec<usize>>
+}
+
+impl<'a> Grammar<'a> {
+    pub fn new(syntaxes: impl IntoIterator<Item = &'a Syntax>) -> Grammar<'a> {
+        let syntaxes = syntaxes.into_iter().collect::<Vec<_>>();
+        let mut rules = vec![
+            Rule { lhs: WFF, rhs: vec![Symbol::WffVariable], kind: RuleKind::Inline },
+            Rule { lhs: OBJECT, rhs: vec![Symbol::ObjectVariable], kind: RuleKind::Inline }
+        ];
+        // Any formula or object can be put between parentheses
+        for nonterminal in [WFF, OBJECT] {
+            let rhs = vec![Symbol::Literal('('), Symbol::NonTerminal(nonterminal), Symbol::Literal(')')];
+            rules.push(Rule { lhs: nonterminal, rhs, kind: RuleKind::Group });
+        };
+        let mut nonterminal_count = 2;
+        for (syntax_ref, syntax) in syntaxes.iter().enumerate() {
+            if syntax.formula.is_empty() { continue; };
+            let lhs = match syntax.syntax_type {
+                SyntaxType::Formula => WFF,
+                SyntaxType::Object => OBJECT
+            };
+            let Some((repeated_index, rep_index)) = syntax.repetition() else {
+                let rhs = syntax.formula.iter().map(symbol).collect();
+                rules.push(Rule { lhs, rhs, kind: RuleKind::Syntax(syntax_ref) });
+                continue;
+            };
+            // 'prefix 𝜑 sep … sep 𝜓 suffix' is read as 'prefix 𝜑 list suffix', where the list is either
+            //   - without_rep: 'sep 𝜓' repeated
+            //   - with_rep: the same, but one of the elements other than the last is a '…'
+            let separator = syntax.formula[repeated_index+1..rep_index].iter()
+                .map(symbol)
+                .collect::<Vec<_>>();
+            let element = symbol(&syntax.formula[repeated_index]);
+            let without_rep = nonterminal_count;
+            let ending_with_rep = nonterminal_count + 1;
+            let with_rep = nonterminal_count + 2;
+            nonterminal_count += 3;
+            let list_rules = [
+                (without_rep, None, element),
+                (without_rep, Some(without_rep), element),
+                (ending_with_rep, None, Symbol::Repetition),
+                (ending_with_rep, Some(without_rep), Symbol::Repetition),
+                (with_rep, Some(ending_with_rep), element),
+                (with_rep, Some(with_rep), element)
+            ];
+            for (list, previous, last) in list_rules {
+                let rhs = previous.map(Symbol::NonTerminal).into_iter()
+                    .chain(separator.iter().copied())
+                    .chain(Some(last))
+                    .collect();
+                rules.push(Rule { lhs: list, rhs, kind: RuleKind::Inline });
+            };
+            let suffix_index = rep_index + separator.len() + 2;
+            for list in [without_rep, with_rep] {
+                let rhs = syntax.formula[..=repeated_index].iter()
+                    .map(symbol)
+                    .chain(Some(Symbol::NonTerminal(list)))
+                    .chain(syntax.formula[suffix_index..].iter().map(symbol))
+                    .collect();
+                rules.push(Rule { lhs, rhs, kind: RuleKind::Syntax(syntax_ref) });
+            };
+        };
+        let mut rules_by_lhs = vec![Vec::new(); nonterminal_count];
+        for (id, rule) in rules.iter().enumerate() {
+            rules_by_lhs[rule.lhs].push(id);
+        };
+        Grammar { syntaxes, rules, rules_by_lhs }
+    }
+
+    /// Returns every way (up to a limit) to read the tokens as a formula or as an object,
+    /// or the position of the first token that cannot be read
+    pub fn parse(&self, tokens: &[Token], syntax_type: &SyntaxType) -> Result<Vec<ParseTree>, usize> {
+        let start_symbol = match syntax_type {
+            SyntaxType::Formula => WFF,
+            SyntaxType::Object => OBJECT
+        };
+        let chart = self.recognize(tokens, start_symbol);
+        let reached = chart.sets.iter()
+            .rposition(|set| !set.is_empty())
+            .unwrap_or(0);
+        let mut memo = HashMap::new();
+        let trees = self.trees(&chart, start_symbol, 0, tokens.len(), &mut memo)
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        match trees.is_empty() {
+            true => Err(reached.min(tokens.len())),
+            false => Ok(trees)
+        }
+    }
+
+    fn recognize(&self, tokens: &[Token], start_symbol: usize) -> Chart {
+        let mut chart = Chart {
+            sets: vec![Vec::new(); tokens.len()+1],
+            members: vec![HashSet::new(); tokens.len()+1],
+            completed: HashMap::new()
+        };
+        // Items waiting for a non-terminal at some position
+        let mut waiting = vec![HashMap::<usize, Vec<Item>>::new(); tokens.len()+1];
+        for &rule in &self.rules_by_lhs[start_symbol] {
+            chart.add(0, Item { rule, dot: 0, origin: 0 });
+        };
+        for position in 0..=tokens.len() {
+            let mut index = 0;
+            while let Some(&item) = chart.sets[position].get(index) {
+                index += 1;
+                let rule = &self.rules[item.rule];
+                match rule.rhs.get(item.dot) {
+                    // Completion (no rule is empty, so the origin is always a previous position)
+                    None => {
+                        let starts = chart.completed.entry((rule.lhs, position)).or_default();
+                        if !starts.contains(&item.origin) {
+                            starts.push(item.origin);
+                        };
+                        let Some(parents) = waiting[item.origin].get(&rule.lhs) else { continue; };
+                        for parent in parents {
+                            if !self.rule_allows(parent.rule, parent.dot, item.rule) { continue; };
+                            chart.add(position, Item { dot: parent.dot + 1, ..*parent });
+                        };
+                    },
+                    // Prediction
+                    Some(Symbol::NonTerminal(nonterminal)) => {
+                        let waiting_items = waiting[position].entry(*nonterminal).or_default();
+                        waiting_items.push(item);
+                        if waiting_items.len() > 1 { continue; };  // Already predicted
+                        for &rule in &self.rules_by_lhs[*nonterminal] {
+                            // Rules starting with a terminal are only predicted if it is the next token
+                            let first = self.rules[rule].rhs[0];
+                            if !matches!(first, Symbol::NonTerminal(_)) && !scans(&first, tokens.get(position)) {
+                                continue;
+                            };
+                            chart.add(position, Item { rule, dot: 0, origin: position });
+                        };
+                    },
+                    // Scan
+                    Some(terminal) => {
+                        if scans(terminal, tokens.get(position)) {
+                            chart.add(position+1, Item { dot: item.dot + 1, ..item });
+                        };
+                    }
+                };
+            };
+        };
+        chart
+    }
+
+    /// The syntax a rule stands for, and whether the symbol at some position is on its left or right edge
+    fn edge_of_rule(&self, rule_id: usize, position: usize) -> Option<(&Syntax, bool)> {
+        let rule = &self.rules[rule_id];
+        let RuleKind::Syntax(syntax_ref) = rule.kind else { return None; };
+        if rule.rhs.len() < 2 { return None; };
+        match position {
+            0 => Some((self.syntaxes[syntax_ref], true)),
+            p if p == rule.rhs.len() - 1 => Some((self.syntaxes[syntax_ref], false)),
+            _ => None
+        }
+    }
+
+    /// Whether a completed rule can be used as a parameter of another rule, at some position.
+    /// This only looks at the syntax of the parameter, trees are checked more thoroughly later
+    fn rule_allows(&self, parent_rule: usize, position: usize, child_rule: usize) -> bool {
+        let Some((parent, left_edge)) = self.edge_of_rule(parent_rule, position) else { return true; };
+        let RuleKind::Syntax(child_ref) = self.rules[child_rule].kind else { return true; };
+        precedence_allows(parent, self.syntaxes[child_ref], left_edge)
+    }
+
+    /// Whether a tree can be a parameter at the edge of a syntax:
+    /// every syntax along its edge that faces the parent must be allowed there
+    fn tree_allows(&self, parent: &Syntax, tree: &ParseTree, left_edge: bool) -> bool {
+        let mut node = tree;
+        while let ParseTree::Composite { syntax_ref, start: _, end: _, children } = node {
+            let syntax = self.syntaxes[*syntax_ref];
+            if !precedence_allows(parent, syntax, left_edge) { return false; };
+            let facing_side = match left_edge {
+                true => syntax.formula.last(),
+                false => syntax.formula.first()
+            };
+            let next = match (facing_side.is_some_and(is_variable), left_edge) {
+                (false, _) => None,
+                (true, true) => children.last(),
+                (true, false) => children.first()
+            };
+            let Some(next) = next else { return true; };
+            node = next;
+        };
+        true
+    }
+
+    /// Every list of children a non-terminal can give to its parent when it spans from start to end
+    fn trees(
+        &self, chart: &Chart, nonterminal: usize, start: usize, end: usize,
+        memo: &mut HashMap<(usize, usize, usize), Vec<Vec<ParseTree>>>
+    ) -> Vec<Vec<ParseTree>> {
+        if let Some(trees) = memo.get(&(nonterminal, start, end)) {
+            return trees.clone();
+        };
+        // Guards against cycles, in case a syntax consists of a single placeholder
+        memo.insert((nonterminal, start, end), Vec::new());
+        let mut res = Vec::new();
+        'try_rule: for &rule_id in &self.rules_by_lhs[nonterminal] {
+            let rule = &self.rules[rule_id];
+            let item = Item { rule: rule_id, dot: rule.rhs.len(), origin: start };
+            if !chart.members[end].contains(&item) { continue 'try_rule; };
+            for children in self.decompose(chart, rule_id, rule.rhs.len(), start, end, memo) {
+                match rule.kind {
+                    RuleKind::Syntax(syntax_ref) => res.push(vec![
+                        ParseTree::Composite { syntax_ref, start, end, children }
+                    ]),
+                    RuleKind::Group => res.extend(
+                        children.into_iter().map(|tree| vec![ParseTree::Group(Box::new(tree))])
+                    ),
+                    RuleKind::Inline => res.push(children)
+                };
+                if res.len() >= MAX_PARSES { break 'try_rule; };
+            };
+        };
+        memo.insert((nonterminal, start, end), res.clone());
+        res
+    }
+
+    /// Every list of children the first 'dot' symbols of a rule can give when they span from start to end
+    fn decompose(
+        &self, chart: &Chart, rule_id: usize, dot: usize, start: usize, end: usize,
+        memo: &mut HashMap<(usize, usize, usize), Vec<Vec<ParseTree>>>
+    ) -> Vec<Vec<ParseTree>> {
+        if dot == 0 {
+            return match start == end {
+                true => vec![Vec::new()],
+                false => Vec::new()
+            };
+        };
+        let previous = Item { rule: rule_id, dot: dot-1, origin: start };
+        let mut res = Vec::new();
+        match self.rules[rule_id].rhs[dot-1] {
+            Symbol::NonTerminal(nonterminal) => {
+                let Some(starts) = chart.completed.get(&(nonterminal, end)) else {
+                    return res;
+                };
+                for &middle in starts {
+                    if middle < start || !chart.members[middle].contains(&previous) { continue; };
+                    let mut lasts = self.trees(chart, nonterminal, middle, end, memo);
+                    if let Some((parent, left_edge)) = self.edge_of_rule(rule_id, dot-1) {
+                        lasts.retain(|last| last.iter().all(|tree| self.tree_allows(parent, tree, left_edge)));
+                    };
+                    if lasts.is_empty() { continue; };
+                    for prefix in self.decompose(chart, rule_id, dot-1, start, middle, memo) {
+                        for last in &lasts {
+                            res.push(prefix.iter().chain(last).cloned().collect());
+                            if res.len() >= MAX_PARSES { return res; };
+                        };
+                    };
+                };
+            },
+            terminal => {
+                if end == 0 || !chart.members[end-1].contains(&previous) { return res; };
+                let leaf = match terminal {
+                    Symbol::WffVariable | Symbol::ObjectVariable => Some(ParseTree::Variable(end-1)),
+                    Symbol::Repetition => Some(ParseTree::Repetition(end-1)),
+                    _ => None
+                };
+                for mut prefix in self.decompose(chart, rule_id, dot-1, start, end-1, memo) {
+                    prefix.extend(leaf.clone());
+                    res.push(prefix);
+                };
+            }
+        };
+        res
+    }
+
+    /// Builds the formula a parse tree stands for, if its repeated placeholders are consistent
+    pub fn formula(&self, tree: &ParseTree, tokens: &[Token]) -> Option<WellFormedFormula> {
+        match self.parameter(tree, tokens, &Placeholder::WellFormedFormula(0))? {
+            Parameter::Wff(wff) => Some(wff),
+            Parameter::Object(_) => None
+        }
+    }
+
+    /// Builds the object a parse tree stands for, if its repeated placeholders are consistent
+    pub fn object(&self, tree: &ParseTree, tokens: &[Token]) -> Option<Object> {
+        match self.parameter(tree, tokens, &Placeholder::Object(0))? {
+            Parameter::Object(obj) => Some(obj),
+            Parameter::Wff(_) => None
+        }
+    }
+
+    /// The placeholder tells what a '…' stands for
+    fn parameter(&self, tree: &ParseTree, tokens: &[Token], pl: &Placeholder) -> Option<Parameter> {
+        let (syntax_ref, children) = match tree {
+            ParseTree::Variable(position) => return match tokens.get(*position)? {
+                Token::Wff(id) => Some(Parameter::Wff(WellFormedFormula::Atomic(*id))),
+                Token::Object(id) => Some(Parameter::Object(Object::Atomic(*id))),
+                Token::WorkWff(id) => Some(Parameter::Wff(WellFormedFormula::WorkVariable(*id))),
+                Token::WorkObject(id) => Some(Parameter::Object(Object::WorkVariable(*id))),
+                _ => None
+            },
+            ParseTree::Group(tree) => return self.parameter(tree, tokens, pl),
+            ParseTree::Repetition(_) => return match pl {
+                Placeholder::WellFormedFormula(_) => Some(Parameter::Wff(WellFormedFormula::Repetition)),
+                Placeholder::Object(_) => Some(Parameter::Object(Object::Repetition)),
+                _ => None
+            },
+            ParseTree::Composite { syntax_ref, start: _, end: _, children } => (*syntax_ref, children)
+        };
+        let syntax = self.syntaxes.get(syntax_ref)?;
+        let placeholders = syntax.formula.iter()
+            .filter(|pl| is_variable(pl))
+            .collect::<Vec<_>>();
+        // The elements repeated by the '…' come right after the placeholder preceding it
+        let (repeated, middle) = match syntax.repetition() {
+            None => (None, 0..0),
+            Some((repeated_index, _)) => {
+                let repeated_position = syntax.formula[..repeated_index].iter()
+                    .filter(|pl| is_variable(pl))
+                    .count();
+                let middle_len = children.len().checked_sub(placeholders.len())?;
+                (Some(&syntax.formula[repeated_index]), repeated_position+1..repeated_position+1+middle_len)
+            }
+        };
+        if children.len() != placeholders.len() + middle.len() { return None; };
+        let mut wffs = vec![None; syntax.distinct_wff_count];
+        let mut objects = vec![None; syntax.distinct_object_count];
+        let fixed_children = children[..middle.start].iter().chain(&children[middle.end..]);
+        for (child, pl) in fixed_children.zip(placeholders) {
+            match (pl, self.parameter(child, tokens, pl)?) {
+                (Placeholder::WellFormedFormula(id), Parameter::Wff(wff)) => match &wffs[*id] {
+                    Some(f) => if *f != wff { return None; },
+                    None => wffs[*id] = Some(wff)
+                },
+                (Placeholder::Object(id), Parameter::Object(obj)) => match &objects[*id] {
+                    Some(o) => if *o != obj { return None; },
+                    None => objects[*id] = Some(obj)
+                },
+                _ => return None
+            };
+        };
+        let mut wff_parameters = wffs.into_iter().collect::<Option<Vec<_>>>()?;
+        let mut object_parameters = objects.into_iter().collect::<Option<Vec<_>>>()?;
+        match repeated {
+            Some(repeated @ Placeholder::WellFormedFormula(id)) => {
+                let tail = wff_parameters.split_off(id+1);
+                for child in &children[middle] {
+                    let Parameter::Wff(wff) = self.parameter(child, tokens, repeated)? else { return None; };
+                    wff_parameters.push(wff);
+                };
+                wff_parameters.extend(tail);
+            },
+            Some(repeated @ Placeholder::Object(id)) => {
+                let tail = object_parameters.split_off(id+1);
+                for child in &children[middle] {
+                    let Parameter::Object(obj) = self.parameter(child, tokens, repeated)? else { return None; };
+                    object_parameters.push(obj);
+                };
+                object_parameters.extend(tail);
+            },
+            _ => ()
+        };
+        match syntax.syntax_type {
+            SyntaxType::Formula => Some(Parameter::Wff(
+                WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters }
+            )),
+            SyntaxType::Object => Some(Parameter::Object(
+                Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters }
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary_syntax(operator: char, precedence: Option<Precedence>) -> Syntax {
+        let formula = vec![
+            Placeholder::WellFormedFormula(0),
+            Placeholder::LiteralChar(operator),
+            Placeholder::WellFormedFormula(1)
+        ];
+        Syntax::new(SyntaxType::Formula, formula, precedence, 2, 0)
+    }
+
+    // '∧' binds tighter than '⇒', which associates to the right, and '∨' has no precedence
+    fn syntaxes() -> Vec<Syntax> {
+        vec![
+            binary_syntax('∧', Some(Precedence { level: 2, associativity: Associativity::Left })),
+            binary_syntax('⇒', Some(Precedence { level: 1, associativity: Associativity::Right })),
+            binary_syntax('∨', None)
+        ]
+    }
+
+    /// Reads 'a' to 'z' as formula variables
+    fn tokens(text: &str) -> Vec<Token> {
+        text.chars()
+            .filter(|c| *c != ' ')
+            .map(|c| match c {
+                'a'..='z' => Token::Wff(c as usize - 'a' as usize),
+                _ => Token::Char(c)
+            })
+            .collect()
+    }
+
+    fn atomic(id: usize) -> WellFormedFormula {
+        WellFormedFormula::Atomic(id)
+    }
+
+    fn composite(syntax_ref: usize, left: WellFormedFormula, right: WellFormedFormula) -> WellFormedFormula {
+        WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters: vec![left, right], object_parameters: Vec::new() }
+    }
+
+    fn parse_formulas(grammar: &Grammar, text: &str) -> Result<Vec<WellFormedFormula>, usize> {
+        let tokens = tokens(text);
+        let trees = grammar.parse(&tokens, &SyntaxType::Formula)?;
+        Ok(trees.iter().map(|tree| grammar.formula(tree, &tokens).unwrap()).collect())
+    }
+
+    #[test]
+    fn parentheses_group_a_formula() {
+        let syntaxes = syntaxes();
+        let grammar = Grammar::new(&syntaxes);
+        let tokens = tokens("(a ⇒ b) ⇒ c");
+        let trees = grammar.parse(&tokens, &SyntaxType::Formula).unwrap();
+        assert_eq!(trees.len(), 1);
+        let ParseTree::Composite { children, .. } = &trees[0] else { panic!("{:?}", trees[0]); };
+        assert!(matches!(children[0], ParseTree::Group(_)));
+        assert_eq!(
+            grammar.formula(&trees[0], &tokens),
+            Some(composite(1, composite(1, atomic(0), atomic(1)), atomic(2)))
+        );
+        assert_eq!(parse_formulas(&grammar, "((a))"), Ok(vec![atomic(0)]));
+    }
+
+    #[test]
+    fn precedence_and_associativity_choose_the_reading() {
+        let syntaxes = syntaxes();
+        let grammar = Grammar::new(&syntaxes);
+        assert_eq!(
+            parse_formulas(&grammar, "a ∧ b ⇒ c"),
+            Ok(vec![composite(1, composite(0, atomic(0), atomic(1)), atomic(2))])
+        );
+        assert_eq!(
+            parse_formulas(&grammar, "a ⇒ b ∧ c"),
+            Ok(vec![composite(1, atomic(0), composite(0, atomic(1), atomic(2)))])
+        );
+        assert_eq!(
+            parse_formulas(&grammar, "a ⇒ b ⇒ c"),
+            Ok(vec![composite(1, atomic(0), composite(1, atomic(1), atomic(2)))])
+        );
+        assert_eq!(
+            parse_formulas(&grammar, "a ∧ b ∧ c"),
+            Ok(vec![composite(0, composite(0, atomic(0), atomic(1)), atomic(2))])
+        );
+    }
+
+    #[test]
+    fn ambiguous_formulas_have_every_reading() {
+        let syntaxes = syntaxes();
+        let grammar = Grammar::new(&syntaxes);
+        let mut formulas = parse_formulas(&grammar, "a ∨ b ∨ c").unwrap();
+        formulas.sort_by_key(|wff| matches!(wff, WellFormedFormula::SyntaxComposite { wff_parameters, .. }
+            if matches!(wff_parameters[0], WellFormedFormula::Atomic(_))));
+        assert_eq!(formulas, vec![
+            composite(2, composite(2, atomic(0), atomic(1)), atomic(2)),
+            composite(2, atomic(0), composite(2, atomic(1), atomic(2)))
+        ]);
+    }
+
+    #[test]
+    fn unreadable_formulas_give_the_position_of_the_error() {
+        let syntaxes = syntaxes();
+        let grammar = Grammar::new(&syntaxes);
+        assert_eq!(parse_formulas(&grammar, "a ∧ ⇒ b"), Err(2));
+        assert_eq!(parse_formulas(&grammar, "(a ∧ b"), Err(4));
+    }
+}
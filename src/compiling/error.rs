@@ -1,5 +1,5 @@
 use std::io;
-use super::PartiallyCompiled;
+use super::LibraryItem;
 
 #[derive(Debug)]
 pub enum CompileError {
@@ -8,10 +8,14 @@ pub enum CompileError {
     InvalidOrderLine(String, usize),
     IOError(io::Error, String, usize),
     UnparsableFile(String, usize),
+    UnparsableLibraryFile(String),  // A file found in the library folders
+    DependencyCycle(Vec<String>),  // Files that each depend on the next one, the last depending on the first
 
     // In math_file.rs
     IncorrectFileType,
-    AmbiguousSyntax(String),
+    InvalidRepetition(String),
+    InvalidLatexTemplate(String),  // The syntax whose template uses a letter it does not have, or repeats differently
+    AmbiguousSyntax(String, String, Vec<String>),  // Name, a formula that can be read in several ways, its readings
     MissingProofLine(usize),
     IncorrectNumberOfHypothesis(usize, usize, usize),
     IncorrectResultingFormula(usize),
@@ -19,11 +23,26 @@ pub enum CompileError {
     WeirdReference,
     InaccessibleHypothesis(usize, usize),
     AssertionNotProven(usize),
+    UndeterminedFormula(usize),  // The line whose formula cannot be inferred from its used hypotheses
+    AmbiguousUsedHypotheses(usize),  // The line whose used hypotheses can be chosen in several ways
+    MissingPropositionalAxioms(usize),
+    TooManyAtoms(usize, usize),
+    NotATautology(Vec<String>, usize),  // Values of the atoms for which the formula is false
+    IncompleteProof(Vec<usize>),  // The lines left to be proven
+
+    // In search.rs
+    GoalNotFound(usize),  // The first line left to be proven that could not be proven
+    GoalSearchTimedOut(usize),
+    ProofNotFound(usize),  // Index of the first assertion that could not be proven
+    ProofSearchTimedOut(usize),
+
+    // In latex.rs and site.rs
+    ItemNotInLibrary(LibraryItem),  // An item of 'order.txt' that the compiled library does not have
 
     // In formula.rs
     ShouldNotBeReached,
-    UncompilableFormula(Vec<PartiallyCompiled>),
-
-    // TODO error
-    RepetitionCharacterNotCompilable,
+    UncompilableFormula(usize),  // Position of the first character (spaces excluded) that cannot be read
+    AmbiguousFormula(Vec<String>),  // Every way to read the formula
+    InvalidDistinctVariables(usize),
+    UnexpectedWorkVariable(usize),  // Work variables can only appear in the formulas of a proof
 }
@@ -1,106 +1,130 @@
 use std::collections::HashMap;
 use crate::parsing::FormulaChar;
 use super::{
-    PartiallyCompiled,
-    Placeholder,
+    Grammar, Token,
+    VariableNames, wff_to_explicit_string,
     Syntax, SyntaxType,
-    WellFormedFormula, Object,
+    WellFormedFormula, Object, Variable,
     CompileError
 };
 
+/// The syntaxes of a library with the grammar they form, built once for all the formulas read with them
+pub struct FormulaParser<'a> {
+    pub syntaxes: &'a [Syntax],
+    grammar: Grammar<'a>
+}
+
+impl<'a> FormulaParser<'a> {
+    pub fn new(syntaxes: &'a [Syntax]) -> FormulaParser<'a> {
+        FormulaParser { syntaxes, grammar: Grammar::new(syntaxes) }
+    }
+}
+
 pub fn compile_formula(
-    formula: Vec<FormulaChar>, syntaxes: &Vec<Syntax>,
+    formula: Vec<FormulaChar>, parser: &FormulaParser,
+    wffs: &mut HashMap<usize, WellFormedFormula>, objects: &mut HashMap<usize, Object>
+) -> Result<WellFormedFormula, CompileError> {
+    if let Some(position) = formula.iter().position(|c| matches!(c, FormulaChar::WorkWff(_) | FormulaChar::WorkObject(_))) {
+        return Err(CompileError::UnexpectedWorkVariable(position));
+    };
+    compile_proof_formula(formula, parser, wffs, objects)
+}
+
+/// The formulas of a proof may contain work variables
+pub fn compile_proof_formula(
+    formula: Vec<FormulaChar>, parser: &FormulaParser,
     wffs: &mut HashMap<usize, WellFormedFormula>, objects: &mut HashMap<usize, Object>
 ) -> Result<WellFormedFormula, CompileError> {
     let mut next_wff_index = wffs.len();
     let mut next_object_index = objects.len();
-    let mut partial_compilation = formula.into_iter()
+    let tokens = formula.into_iter()
         .map(|c| match c {
-            FormulaChar::Char(_) => Ok(PartiallyCompiled::NotCompiled(c)),
-            FormulaChar::RepetitionChar => Err(CompileError::RepetitionCharacterNotCompilable),
+            FormulaChar::Char(c) => Token::Char(c),
+            FormulaChar::RepetitionChar => Token::Repetition,
             FormulaChar::Wff(id) => match wffs.get(&id) {
-                Some(wff) => Ok(PartiallyCompiled::CompiledFormula(wff.to_owned())),
-                None => {
-                    let wff = WellFormedFormula::Atomic(next_wff_index);
-                    wffs.insert(id, wff.clone());
+                Some(WellFormedFormula::Atomic(n)) => Token::Wff(*n),
+                _ => {
+                    wffs.insert(id, WellFormedFormula::Atomic(next_wff_index));
                     next_wff_index += 1;
-                    Ok(PartiallyCompiled::CompiledFormula(wff))
+                    Token::Wff(next_wff_index - 1)
                 }
             },
             FormulaChar::Object(id) => match objects.get(&id) {
-                Some(object) => Ok(PartiallyCompiled::CompiledObject(object.to_owned())),
-                None => {
-                    let object = Object::Atomic(next_object_index);
-                    objects.insert(id, object.clone());
+                Some(Object::Atomic(n)) => Token::Object(*n),
+                _ => {
+                    objects.insert(id, Object::Atomic(next_object_index));
                     next_object_index += 1;
-                    Ok(PartiallyCompiled::CompiledObject(object))
+                    Token::Object(next_object_index - 1)
                 }
-            }
+            },
+            FormulaChar::WorkWff(id) => Token::WorkWff(id),
+            FormulaChar::WorkObject(id) => Token::WorkObject(id)
         })
-        .collect::<Result<Vec<_>, _>>()?;
-    'try_find_patterns: while partial_compilation.len() > 1 {
-        'try_syntax: for (syntax_id, syntax) in syntaxes.iter().enumerate() {
-            'try_position: for index in 0..partial_compilation.len() {
-                if partial_compilation.len() - index < syntax.formula.len() {
-                    continue 'try_syntax;  // If the syntax does not fit in the rest of the text, go to the next syntax
-                };
-                let mut wffs = vec![None; syntax.distinct_wff_count];
-                let mut objects = vec![None; syntax.distinct_object_count];
-                for (c, pl) in partial_compilation[index..].iter().zip(&syntax.formula) {
-                    let valid = match (c, pl) {
-                        (
-                            PartiallyCompiled::NotCompiled(FormulaChar::Char(c1)),
-                            Placeholder::LiteralChar(c2)
-                        ) => c1 == c2,
-                        (
-                            PartiallyCompiled::CompiledFormula(wff),
-                            Placeholder::WellFormedFormula(id)
-                        ) => match &wffs[*id] {
-                            Some(f) => wff == f,
-                            None => { wffs[*id] = Some(wff.to_owned()); true }
-                        },
-                        (
-                            PartiallyCompiled::CompiledObject(obj),
-                            Placeholder::Object(id)
-                        ) => match &objects[*id] {
-                            Some(o) => obj == o,
-                            None => { objects[*id] = Some(obj.to_owned()); true }
-                        },
-                        _ => false
-                    };
-                    match valid {
-                        true => (),
-                        false => continue 'try_position  // Syntax did not match here, try next position
-                    };
-                };
-                let wff_parameters = wffs.into_iter()
-                    .map(|wff| wff.ok_or(CompileError::ShouldNotBeReached))
-                    .collect::<Result<Vec<_>, _>>()?;
-                let object_parameters = objects.into_iter()
-                    .map(|obj| obj.ok_or(CompileError::ShouldNotBeReached))
-                    .collect::<Result<Vec<_>, _>>()?;
-                let element_to_insert = match syntax.syntax_type {
-                    SyntaxType::Formula => PartiallyCompiled::CompiledFormula(
-                        WellFormedFormula::SyntaxComposite { syntax_ref: syntax_id, wff_parameters, object_parameters }
-                    ),
-                    SyntaxType::Object => PartiallyCompiled::CompiledObject(
-                        Object::SyntaxComposite { syntax_ref: syntax_id, wff_parameters, object_parameters }
-                    )
-                };
-                for _ in 0..syntax.formula.len() {
-                    partial_compilation.remove(index);
+        .collect::<Vec<_>>();
+    let grammar = &parser.grammar;
+    let trees = match grammar.parse(&tokens, &SyntaxType::Formula) {
+        Ok(trees) => trees,
+        Err(position) => return Err(CompileError::UncompilableFormula(position))
+    };
+    let mut parses = Vec::new();
+    for tree in trees {
+        let Some(wff) = grammar.formula(&tree, &tokens) else { continue; };
+        if !parses.contains(&wff) {
+            parses.push(wff);
+        };
+    };
+    match parses.len() {
+        0 => Err(CompileError::UncompilableFormula(tokens.len())),
+        1 => parses.pop().ok_or(CompileError::ShouldNotBeReached),
+        _ => {
+            let names = VariableNames::new(wffs, objects);
+            let readings = parses.iter()
+                .map(|wff| wff_to_explicit_string(wff, parser.syntaxes, &names))
+                .collect();
+            Err(CompileError::AmbiguousFormula(readings))
+        }
+    }
+}
+
+pub fn compile_distinct_variables(
+    lines: Vec<Vec<FormulaChar>>,
+    wffs: &mut HashMap<usize, WellFormedFormula>, objects: &mut HashMap<usize, Object>
+) -> Result<Vec<(Variable, Variable)>, CompileError> {
+    let mut distinct_variables = Vec::new();
+    for (line_no, line) in lines.into_iter().enumerate() {
+        let mut variables = Vec::new();
+        for c in line {
+            let variable = match c {
+                FormulaChar::Wff(id) => {
+                    let next_wff_index = wffs.len();
+                    match wffs.entry(id).or_insert(WellFormedFormula::Atomic(next_wff_index)) {
+                        WellFormedFormula::Atomic(n) => Variable::Wff(*n),
+                        _ => return Err(CompileError::ShouldNotBeReached)
+                    }
+                },
+                FormulaChar::Object(id) => {
+                    let next_object_index = objects.len();
+                    match objects.entry(id).or_insert(Object::Atomic(next_object_index)) {
+                        Object::Atomic(n) => Variable::Object(*n),
+                        _ => return Err(CompileError::ShouldNotBeReached)
+                    }
+                },
+                _ => return Err(CompileError::InvalidDistinctVariables(line_no+1))
+            };
+            if variables.contains(&variable) {
+                return Err(CompileError::InvalidDistinctVariables(line_no+1));
+            };
+            variables.push(variable);
+        };
+        // Every line lists variables that are pairwise distinct
+        for (i, v1) in variables.iter().enumerate() {
+            for v2 in &variables[i+1..] {
+                let pair = (*v1.min(v2), *v1.max(v2));
+                if !distinct_variables.contains(&pair) {
+                    distinct_variables.push(pair);
                 };
-                partial_compilation.insert(index, element_to_insert);
-                // Once a syntax has been compiled, we iterate through the list again from the beginning
-                continue 'try_find_patterns;
             };
         };
-        // We only can get here if no syntax has matched
-        return Err(CompileError::UncompilableFormula(partial_compilation));
     };
-    match partial_compilation.pop() {
-        Some(PartiallyCompiled::CompiledFormula(wff)) => Ok(wff),
-        Some(other) => Err(CompileError::UncompilableFormula(vec![other])),
-        None => Err(CompileError::ShouldNotBeReached)
-    }
+    Ok(distinct_variables)
 }
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use crate::parsing::MathFile;
+use super::{
+    WellFormedFormula, Object, Variable,
+    Reference, Library, LibraryItem, CompileError, FormulaParser,
+    VariableNames, Parameter,
+    parameter_to_latex, syntax_to_latex,
+    compile_partial_theorem, proof_lines
+};
+
+const PREAMBLE: &str = "\\documentclass{article}\n\
+    \\usepackage{amsmath, amssymb}\n\
+    \\usepackage{longtable}\n\n\
+    \\begin{document}\n";
+
+/// Writes names and other text outside of math mode
+fn escape_text(text: &str) -> String {
+    let mut res = String::new();
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '#' | '%' | '&' | '_' | '$' => { res.push('\\'); res.push(c); },
+            '\\' => res.push_str("\\textbackslash{}"),
+            '^' => res.push_str("\\textasciicircum{}"),
+            '~' => res.push_str("\\textasciitilde{}"),
+            c => res.push(c)
+        };
+    };
+    res
+}
+
+fn name_to_latex(name: &str) -> String {
+    format!("\\textsf{{{}}}", escape_text(name))
+}
+
+fn wff_to_math(wff: &WellFormedFormula, lib: &Library, names: &VariableNames) -> String {
+    format!("${}$", parameter_to_latex(Parameter::Wff(wff), &lib.syntaxes, names))
+}
+
+fn variable_to_latex(var: Variable, lib: &Library, names: &VariableNames) -> String {
+    match var {
+        Variable::Wff(id) => parameter_to_latex(Parameter::Wff(&WellFormedFormula::Atomic(id)), &lib.syntaxes, names),
+        Variable::Object(id) => parameter_to_latex(Parameter::Object(&Object::Atomic(id)), &lib.syntaxes, names)
+    }
+}
+
+/// The hypotheses, assertions and distinct variables of a statement, as a list
+fn statement_to_latex(
+    hypotheses: &[(String, &WellFormedFormula)],
+    assertions: &[&WellFormedFormula],
+    distinct_variables: &[(Variable, Variable)],
+    lib: &Library,
+    names: &VariableNames
+) -> String {
+    let mut res = String::from("\\begin{description}\n");
+    for (name, hypothesis) in hypotheses {
+        res.push_str(&format!("\\item[Hypothesis {name}] {}\n", wff_to_math(hypothesis, lib, names)));
+    };
+    for (index, assertion) in assertions.iter().enumerate() {
+        // Assertions after the first one are referred to as 'name.1', 'name.2'...
+        let number = match index {
+            0 => String::new(),
+            _ => format!(" {index}")
+        };
+        res.push_str(&format!("\\item[Assertion{number}] {}\n", wff_to_math(assertion, lib, names)));
+    };
+    if !distinct_variables.is_empty() {
+        let pairs = distinct_variables.iter()
+            .map(|(var1, var2)| {
+                format!("{}, {}", variable_to_latex(*var1, lib, names), variable_to_latex(*var2, lib, names))
+            })
+            .collect::<Vec<_>>();
+        res.push_str(&format!("\\item[Distinct variables] ${}$\n", pairs.join(";\\ ")));
+    };
+    res.push_str("\\end{description}\n");
+    res
+}
+
+/// Writes a syntax with its definition, a definition, an axiom or the statement of a theorem, as compiled in the library
+pub fn library_item_to_latex(
+    item: LibraryItem, lib: &Library, syntax_names: &[String]
+) -> Result<String, CompileError> {
+    let names = VariableNames::default();
+    let item = item.file_item(lib, syntax_names);
+    let name = name_to_latex(&item.name(lib, syntax_names));
+    let missing = CompileError::ItemNotInLibrary(item);
+    match item {
+        LibraryItem::Syntax(syntax_id) => {
+            let (Some(syntax), Some(syntax_name)) = (lib.syntaxes.get(syntax_id), syntax_names.get(syntax_id)) else {
+                return Err(missing);
+            };
+            let mut res = format!("\\subsection*{{Syntax {name}}}\n");
+            res.push_str(&format!("\\[ {} \\]\n", syntax_to_latex(syntax)));
+            if let Some(definition) = lib.definitions.iter().find(|def| def.name == *syntax_name) {
+                res.push_str(&format!("Definition: {}\n", wff_to_math(&definition.definition, lib, &names)));
+            };
+            Ok(res)
+        },
+        LibraryItem::Definition(def_id) => {
+            let definition = &lib.definitions.get(def_id).ok_or(missing)?.definition;
+            Ok(format!("\\subsection*{{Definition {name}}}\n{}\n", wff_to_math(definition, lib, &names)))
+        },
+        LibraryItem::Axiom(_) | LibraryItem::Theorem(_) => {
+            let (kind, hypotheses, assertions, distinct_variables) = match item {
+                LibraryItem::Axiom(ax_id) => {
+                    let axiom = lib.axioms.get(ax_id).ok_or(missing)?;
+                    ("Axiom", &axiom.hypotheses, &axiom.assertions, &axiom.distinct_variables)
+                },
+                LibraryItem::Theorem(theo_id) => {
+                    let theorem = lib.theorems.get(theo_id).ok_or(missing)?;
+                    ("Theorem", &theorem.hypotheses, &theorem.assertions, &theorem.distinct_variables)
+                },
+                _ => unreachable!()
+            };
+            // The library does not keep the names of the hypotheses
+            let hypotheses = hypotheses.iter()
+                .enumerate()
+                .map(|(index, hypothesis)| ((index + 1).to_string(), hypothesis))
+                .collect::<Vec<_>>();
+            let assertions = assertions.iter().collect::<Vec<_>>();
+            let statement = statement_to_latex(&hypotheses, &assertions, distinct_variables, lib, &names);
+            Ok(format!("\\subsection*{{{kind} {name}}}\n{statement}"))
+        }
+    }
+}
+
+/// Writes the statement of a theorem and the table of its proof, with the names and references of its file
+pub fn theorem_to_latex(
+    file: MathFile, lib: &Library, parser: &FormulaParser, references: &HashMap<String, Reference>
+) -> Result<String, CompileError> {
+    let MathFile::Theorem { name, proof, .. } = &file else {
+        return Err(CompileError::IncorrectFileType);
+    };
+    let mut res = format!("\\subsection*{{Theorem {}}}\n", name_to_latex(name));
+    let written_references = proof.iter()
+        .map(|(_, _, reference, _)| reference.clone())
+        .collect::<Vec<_>>();
+    let partial = compile_partial_theorem(
+        file, parser, &lib.definitions, &lib.axioms, &lib.theorems, references
+    )?;
+    let theorem = &partial.theorem;
+    let hypotheses = partial.hypothesis_names.iter()
+        .map(|name| name_to_latex(name))
+        .zip(&theorem.hypotheses)
+        .collect::<Vec<_>>();
+    let assertions = theorem.assertions.iter().collect::<Vec<_>>();
+    res.push_str(&statement_to_latex(&hypotheses, &assertions, &theorem.distinct_variables, lib, &partial.names));
+
+    res.push_str("\\begin{longtable}{rlll}\n");
+    res.push_str("Line & Uses & Reference & Formula \\\\\n\\hline\n\\endhead\n");
+    for line in proof_lines(&partial) {
+        let used_lines = line.used_lines.iter()
+            .map(|used| (used + 1).to_string())
+            .collect::<Vec<_>>();
+        let reference = written_references.get(line.original_line - 1)
+            .map_or(String::new(), |name| name_to_latex(name));
+        res.push_str(&format!(
+            "{} & {} & {reference} & {} \\\\\n",
+            line.original_line, used_lines.join(", "), wff_to_math(&line.formula, lib, &partial.names)
+        ));
+    };
+    res.push_str("\\end{longtable}\n");
+    Ok(res)
+}
+
+/// A LaTeX document made of the given sections, each a title and the items written in it
+pub fn latex_document(sections: &[(String, Vec<String>)]) -> String {
+    let mut res = PREAMBLE.to_string();
+    for (title, items) in sections {
+        if !title.is_empty() {
+            res.push_str(&format!("\n\\section*{{{}}}\n", escape_text(title)));
+        };
+        for item in items {
+            res.push('\n');
+            res.push_str(item);
+        };
+    };
+    res.push_str("\n\\end{document}\n");
+    res
+}
@@ -1,25 +1,82 @@
 use std::collections::HashMap;
-use crate::parsing::{MathFile, FormulaChar, DefinitionType};
+use crate::parsing::{self, MathFile, FormulaChar, DefinitionType};
 use super::{
     Syntax, Axiom, Theorem, Definition,
     SyntaxType, Placeholder, Reference, LogicStep,
-    WellFormedFormula, Object,
-    compile_formula,
-    formula_is_contained,
+    Precedence, Associativity,
+    WellFormedFormula, Object, Variable,
+    FormulaParser, compile_formula,
+    compile_proof_formula,
+    compile_distinct_variables,
+    find_ambiguity,
     formula_is_substitution,
+    substituted_assertion,
+    find_used_hypotheses,
+    PropositionalSystem, tautology_error,
+    WorkVariables, wff_has_work_variables,
+    VariableNames,
     CompileError
 };
 
+/// A syntax may contain at most one '…', which must look like '𝜑 sep … sep 𝜓',
+/// where 'sep' is a non-empty list of characters, and 𝜑 and 𝜓 are placeholders of the same type.
+/// For simplicity, every placeholder of a variadic syntax must appear only once
+fn repetition_is_valid(syntax: &Syntax) -> bool {
+    let formula = &syntax.formula;
+    let repetition_count = formula.iter()
+        .filter(|pl| **pl == Placeholder::Repetition)
+        .count();
+    if repetition_count == 0 { return true; };
+    if repetition_count > 1 { return false; };
+    let Some((repeated_index, rep_index)) = syntax.repetition() else {
+        return false;
+    };
+    let separator = &formula[repeated_index+1..rep_index];
+    if separator.is_empty() { return false; };
+    let Some(after) = formula.get(rep_index+1..rep_index+separator.len()+2) else {
+        return false;
+    };
+    if &after[..separator.len()] != separator { return false; };
+    let same_type = matches!(
+        (&formula[repeated_index], &after[separator.len()]),
+        (Placeholder::WellFormedFormula(_), Placeholder::WellFormedFormula(_))
+        | (Placeholder::Object(_), Placeholder::Object(_))
+    );
+    let placeholder_count = formula.iter()
+        .filter(|pl| matches!(pl, Placeholder::WellFormedFormula(_) | Placeholder::Object(_)))
+        .count();
+    same_type && placeholder_count == syntax.distinct_wff_count + syntax.distinct_object_count
+}
+
+/// The template of a variadic syntax repeats the same placeholder, in the same way as the syntax,
+/// and the template of any other syntax cannot contain '…'
+fn template_is_valid(syntax: &Syntax) -> bool {
+    let Some(template) = &syntax.latex else { return true; };
+    let Some((repeated_index, _)) = syntax.repetition() else {
+        return !template.contains(&Placeholder::Repetition);
+    };
+    let template_syntax = Syntax { formula: template.clone(), latex: None, ..syntax.clone() };
+    repetition_is_valid(&template_syntax) && template_syntax.repetition()
+        .is_some_and(|(template_index, _)| template[template_index] == syntax.formula[repeated_index])
+}
+
+/// The name of a syntax and the formula it is defined with
+type NamedDefinition = (String, Vec<FormulaChar>);
+
+/// Also returns the number of syntaxes the formulas of the ambiguity check were built with,
+/// when the check was cut short before finding nothing
 pub fn compile_syntax(file: MathFile, syntaxes: &Vec<Syntax>)
--> Result<(Syntax, Option<(String, Vec<FormulaChar>)>), CompileError>
+-> Result<(Syntax, Option<NamedDefinition>, Option<usize>), CompileError>
 {
-    let (name, def_type, syntax, definition) = match file {
+    let (name, def_type, syntax, precedence, definition, latex) = match file {
         MathFile::SyntaxDefinition {
             name,
             definition_type,
             syntax,
-            definition
-        } => (name, definition_type, syntax, definition),
+            precedence,
+            definition,
+            latex
+        } => (name, definition_type, syntax, precedence, definition, latex),
         _ => return Err(CompileError::IncorrectFileType)
     };
     let syntax_type = match def_type {
@@ -46,43 +103,76 @@ pub fn compile_syntax(file: MathFile, syntaxes: &Vec<Syntax>)
             FormulaChar::Object(id) => match obj_mapping[id] {
                 Some(n) => formula.push(Placeholder::Object(n)),
                 None => {
-                    formula.push(Placeholder::WellFormedFormula(next_obj_id));
+                    formula.push(Placeholder::Object(next_obj_id));
                     obj_mapping[id] = Some(next_obj_id);
                     next_obj_id += 1;
                 }
+            },
+            FormulaChar::WorkWff(_) | FormulaChar::WorkObject(_) => {
+                return Err(CompileError::UnexpectedWorkVariable(formula.len()));
             }
         };
     };
-    // verify syntax doesn't make the compiling ambiguous
-    for other_syntax in syntaxes {
-        let other_formula = &other_syntax.formula;
-        if formula_is_contained(&formula, other_formula) {
-            return Err(CompileError::AmbiguousSyntax(name));
-        };
-        if formula_is_contained(other_formula, &formula) {
-            return Err(CompileError::AmbiguousSyntax(name));
+    let precedence = precedence.map(|(level, associativity)| Precedence {
+        level,
+        associativity: match associativity {
+            parsing::Associativity::Left => Associativity::Left,
+            parsing::Associativity::Right => Associativity::Right,
+            parsing::Associativity::NonAssociative => Associativity::NonAssociative
         }
+    });
+    // The template refers to the placeholders with the letters of the syntax
+    let latex = match latex {
+        Some(template) => {
+            let template = template.into_iter()
+                .map(|ch| match ch {
+                    FormulaChar::Char(c) => Some(Placeholder::LiteralChar(c)),
+                    FormulaChar::RepetitionChar => Some(Placeholder::Repetition),
+                    FormulaChar::Wff(id) => wff_mapping[id].map(Placeholder::WellFormedFormula),
+                    FormulaChar::Object(id) => obj_mapping[id].map(Placeholder::Object),
+                    FormulaChar::WorkWff(_) | FormulaChar::WorkObject(_) => None
+                })
+                .collect::<Option<Vec<_>>>();
+            match template {
+                Some(template) => Some(template),
+                None => return Err(CompileError::InvalidLatexTemplate(name))
+            }
+        },
+        None => None
+    };
+    let syntax = Syntax {
+        syntax_type,
+        formula,
+        precedence,
+        distinct_wff_count: next_wff_id,
+        distinct_object_count: next_obj_id,
+        latex
+    };
+    if !repetition_is_valid(&syntax) {
+        return Err(CompileError::InvalidRepetition(name));
+    };
+    if !template_is_valid(&syntax) {
+        return Err(CompileError::InvalidLatexTemplate(name));
+    };
+    // verify syntax doesn't make the compiling ambiguous
+    let checked_size = match find_ambiguity(&syntax, syntaxes) {
+        Ok(None) => None,
+        Ok(Some((witness, readings))) => return Err(CompileError::AmbiguousSyntax(name, witness, readings)),
+        // Refusing the syntax would keep large libraries from compiling, so the check is only reported as incomplete
+        Err(checked_size) => Some(checked_size)
     };
 
     let name_def = match definition {
         Some(def) => Some((name, def)),
         None => None
     };
-    Ok((
-        Syntax {
-            syntax_type,
-            formula,
-            distinct_wff_count: next_wff_id,
-            distinct_object_count: next_obj_id
-        },
-        name_def
-    ))
+    Ok((syntax, name_def, checked_size))
 }
 
-pub fn compile_definition(name: String, def: Vec<FormulaChar>, syntaxes: &Vec<Syntax>) -> Result<Definition, CompileError> {
+pub fn compile_definition(name: String, def: Vec<FormulaChar>, parser: &FormulaParser) -> Result<Definition, CompileError> {
     let mut wffs = HashMap::<usize, WellFormedFormula>::new();
     let mut objects = HashMap::<usize, Object>::new();
-    let definition = compile_formula(def, syntaxes, &mut wffs, &mut objects)?;
+    let definition = compile_formula(def, parser, &mut wffs, &mut objects)?;
     Ok(
         Definition {
             name,
@@ -93,62 +183,167 @@ pub fn compile_definition(name: String, def: Vec<FormulaChar>, syntaxes: &Vec<Sy
     )
 }
 
-pub fn compile_axiom(file: MathFile, syntaxes: &Vec<Syntax>) -> Result<Axiom, CompileError> {
+pub fn compile_axiom(file: MathFile, parser: &FormulaParser) -> Result<Axiom, CompileError> {
     let mut wffs = HashMap::<usize, WellFormedFormula>::new();
     let mut objects = HashMap::<usize, Object>::new();
-    let (name, hypotheses, assertions) = match file {
+    let (name, hypotheses, assertions, distinct) = match file {
         MathFile::Axiom {
             name,
             hypotheses,
-            assertions
-        } => (name, hypotheses, assertions),
+            assertions,
+            distinct
+        } => (name, hypotheses, assertions, distinct),
         _ => return Err(CompileError::IncorrectFileType)
     };
     let compiled_hypotheses = hypotheses.into_iter()
-        .map(|hyp| compile_formula(hyp, syntaxes, &mut wffs, &mut objects))
+        .map(|hyp| compile_formula(hyp, parser, &mut wffs, &mut objects))
         .collect::<Result<Vec<_>, _>>()?;
     let compiled_assertions = assertions.into_iter()
-        .map(|ass| compile_formula(ass, syntaxes, &mut wffs, &mut objects))
+        .map(|ass| compile_formula(ass, parser, &mut wffs, &mut objects))
         .collect::<Result<Vec<_>, _>>()?;
+    let distinct_variables = compile_distinct_variables(distinct, &mut wffs, &mut objects)?;
     Ok(Axiom {
         name,
         hypotheses: compiled_hypotheses,
         assertions: compiled_assertions,
+        distinct_variables,
         distinct_wff_count: wffs.len(),
         distinct_object_count: objects.len()
     })
 }
 
+/// What a proof step can use from a definition, an axiom or a theorem
+pub(super) struct Statement<'a> {
+    pub hypotheses: &'a Vec<WellFormedFormula>,
+    pub assertion: WellFormedFormula,
+    pub distinct: &'a [(Variable, Variable)],
+    pub wff_count: usize,
+    pub object_count: usize
+}
+
+static NO_HYPOTHESES: Vec<WellFormedFormula> = Vec::new();
+
+pub(super) fn referenced_statement<'a>(
+    reference: Reference,
+    definitions: &'a [Definition],
+    axioms: &'a [Axiom],
+    theorems: &'a [Theorem]
+) -> Option<Statement<'a>> {
+    match reference {
+        Reference::DefinitionReference(def_id) => {
+            let definition = definitions.get(def_id)?;
+            Some(Statement {
+                hypotheses: &NO_HYPOTHESES,
+                assertion: definition.definition.clone(),
+                distinct: &[],
+                wff_count: definition.distinct_wff_count,
+                object_count: definition.distinct_object_count
+            })
+        },
+        Reference::AxiomReference(ax_id, assert_id) => {
+            let axiom = axioms.get(ax_id)?;
+            Some(Statement {
+                hypotheses: &axiom.hypotheses,
+                assertion: axiom.assertions.get(assert_id)?.clone(),
+                distinct: &axiom.distinct_variables,
+                wff_count: axiom.distinct_wff_count,
+                object_count: axiom.distinct_object_count
+            })
+        },
+        Reference::TheoremReference(theo_id, assert_id) => {
+            let theorem = theorems.get(theo_id)?;
+            Some(Statement {
+                hypotheses: &theorem.hypotheses,
+                assertion: theorem.assertions.get(assert_id)?.clone(),
+                distinct: &theorem.distinct_variables,
+                wff_count: theorem.distinct_wff_count,
+                object_count: theorem.distinct_object_count
+            })
+        },
+        Reference::HypothesisReference(_) | Reference::UnprovenReference => None
+    }
+}
+
+/// A theorem whose proof is verified but may not prove its assertions yet
+pub(super) struct PartialTheorem {
+    pub theorem: Theorem,
+    pub hypothesis_names: Vec<String>,
+    pub line_steps: Vec<usize>,  // The step of the compiled proof each line ends with
+    pub inferred_lines: Vec<usize>,  // The lines whose formula or used hypotheses were left to be inferred
+    pub open_goals: Vec<usize>,  // The lines of '?' and those whose formula still contains work variables
+    pub names: VariableNames
+}
+
 pub fn compile_theorem(
     file: MathFile,
-    syntaxes: &Vec<Syntax>,
+    parser: &FormulaParser,
     definitions: &Vec<Definition>,
     axioms: &Vec<Axiom>,
-    theorems: &Vec<Theorem>,
+    theorems: &[Theorem],
     references: &HashMap<String, Reference> 
 ) -> Result<Theorem, CompileError> {
+    let partial = compile_partial_theorem(file, parser, definitions, axioms, theorems, references)?;
+    if !partial.open_goals.is_empty() {
+        return Err(CompileError::IncompleteProof(partial.open_goals));
+    };
+    let theorem = partial.theorem;
+    assertions_are_proven(&theorem)?;
+    Ok(theorem)
+}
+
+pub(super) fn assertions_are_proven(theorem: &Theorem) -> Result<(), CompileError> {
+    let steps = theorem.proof.iter()
+        .map(|l| &l.resulting_formula)
+        .collect::<Vec<_>>();
+    for (index, assertion) in theorem.assertions.iter().enumerate() {
+        if !steps.contains(&assertion) {
+            return Err(CompileError::AssertionNotProven(index));
+        };
+    };
+    Ok(())
+}
+
+pub(super) fn compile_partial_theorem(
+    file: MathFile,
+    parser: &FormulaParser,
+    definitions: &Vec<Definition>,
+    axioms: &Vec<Axiom>,
+    theorems: &[Theorem],
+    references: &HashMap<String, Reference> 
+) -> Result<PartialTheorem, CompileError> {
     let mut wffs = HashMap::<usize, WellFormedFormula>::new();
     let mut objects = HashMap::<usize, Object>::new();
-    let (name, hypotheses, assertions, proof) = match file {
+    let (name, hypotheses, assertions, distinct, proof) = match file {
         MathFile::Theorem {
             name,
             hypotheses,
             assertions,
+            distinct,
             proof
-        } => (name, hypotheses, assertions, proof),
+        } => (name, hypotheses, assertions, distinct, proof),
         _ => return Err(CompileError::IncorrectFileType)
     };
     let mut hypot_names = HashMap::new();
+    let mut hypothesis_names = Vec::new();
     let mut hypot_list = Vec::new();
     for (index, (hypot_name, hypot)) in hypotheses.into_iter().enumerate() {
-        hypot_names.insert(hypot_name, index);
-        hypot_list.push(compile_formula(hypot, syntaxes, &mut wffs, &mut objects)?);
+        hypot_names.insert(hypot_name.clone(), index);
+        hypothesis_names.push(hypot_name);
+        hypot_list.push(compile_formula(hypot, parser, &mut wffs, &mut objects)?);
     }
     let assertions = assertions.into_iter()
-        .map(|ass| compile_formula(ass, syntaxes, &mut wffs, &mut objects))
+        .map(|ass| compile_formula(ass, parser, &mut wffs, &mut objects))
         .collect::<Result<Vec<_>, _>>()?;
+    let distinct_variables = compile_distinct_variables(distinct, &mut wffs, &mut objects)?;
     // Proof compilation and verification
     let mut compiled_proof = Vec::new();
+    let mut line_steps = Vec::new();
+    let mut inferred_lines = Vec::new();
+    let mut open_goals = Vec::new();
+    let mut system = None;
+    let mut work = WorkVariables::new(&proof.iter().map(|line| line.3.as_ref()).collect::<Vec<_>>());
+    let mut work_lines = Vec::new();  // The lines whose formula is written with work variables, or left to be found
+    let mut unified_lines = Vec::new();
     for (
         i,
         (index, used_hypots, theorem_name, formula)
@@ -159,15 +354,103 @@ pub fn compile_theorem(
             if used_hypots.len() != 0 {
                 return Err(CompileError::IncorrectNumberOfHypothesis(used_hypots.len(), 0, index));
             };
-            let resulting_formula = compile_formula(formula, syntaxes, &mut wffs, &mut objects)?;
-            if resulting_formula != hypot_list[hypot_id] {
+            let given_formula = match formula {
+                Some(formula) => compile_proof_formula(formula, parser, &mut wffs, &mut objects)?,
+                None => {
+                    inferred_lines.push(index);
+                    hypot_list[hypot_id].clone()
+                }
+            };
+            if wff_has_work_variables(&given_formula) { work_lines.push(index); };
+            if !work.unify(&[(given_formula, hypot_list[hypot_id].clone())]) {
                 return Err(CompileError::IncorrectResultingFormula(index));
             };
             compiled_proof.push(LogicStep {
                 used_hypotheses: used_hypots,  // Empty
                 theorem_ref: Reference::HypothesisReference(hypot_id),
+                resulting_formula: hypot_list[hypot_id].clone()
+            });
+            line_steps.push(compiled_proof.len() - 1);
+            continue;
+        };
+        let used_hypots = used_hypots.into_iter()
+            .map(|n| n.checked_sub(1)
+                .and_then(|idx| line_steps.get(idx).copied())
+                .ok_or(CompileError::InaccessibleHypothesis(n, index))
+            ).collect::<Result<Vec<_>, _>>()?;
+        let used_hypotheses = used_hypots.iter()
+            .map(|idx| work.resolve_wff(&compiled_proof[*idx].resulting_formula))
+            .collect::<Vec<_>>();
+        // Step left to be proven, whose formula may be left to be found
+        if theorem_name == "?" {
+            let resulting_formula = match formula {
+                Some(formula) => compile_proof_formula(formula, parser, &mut wffs, &mut objects)?,
+                None => work.new_wff()
+            };
+            if wff_has_work_variables(&resulting_formula) { work_lines.push(index); };
+            compiled_proof.push(LogicStep {
+                used_hypotheses: used_hypots,
+                theorem_ref: Reference::UnprovenReference,
                 resulting_formula
             });
+            line_steps.push(compiled_proof.len() - 1);
+            open_goals.push(index);
+            continue;
+        };
+        // Tautology, proven from the used lines with the propositional axioms
+        if theorem_name == "taut" {
+            let Some(formula) = formula else {
+                return Err(CompileError::UndeterminedFormula(index));
+            };
+            let resulting_formula = compile_proof_formula(formula, parser, &mut wffs, &mut objects)?;
+            let resulting_formula = work.resolve_wff(&resulting_formula);
+            if wff_has_work_variables(&resulting_formula) || used_hypotheses.iter().any(wff_has_work_variables) {
+                return Err(CompileError::UndeterminedFormula(index));
+            };
+            if system.is_none() {
+                system = PropositionalSystem::find(parser.syntaxes, axioms, theorems);
+            };
+            let Some(system) = &system else {
+                return Err(CompileError::MissingPropositionalAxioms(index));
+            };
+            let steps = system.prove(&used_hypotheses, &resulting_formula).map_err(|e| {
+                let names = VariableNames::new(&wffs, &objects);
+                tautology_error(e, parser.syntaxes, &names, index)
+            })?;
+            let mut step_ids = Vec::new();
+            for step in steps {
+                let theorem_ref = match step.reference {
+                    Reference::HypothesisReference(premise) => {
+                        step_ids.push(used_hypots[premise]);
+                        continue;
+                    },
+                    theorem_ref => theorem_ref
+                };
+                let used_steps = step.used_steps.iter()
+                    .map(|id| step_ids[*id])
+                    .collect::<Vec<_>>();
+                let used_formulas = used_steps.iter()
+                    .map(|id| compiled_proof[*id].resulting_formula.clone())
+                    .collect::<Vec<_>>();
+                let Some(statement) = referenced_statement(theorem_ref, definitions, axioms, theorems) else {
+                    return Err(CompileError::WeirdReference);
+                };
+                if !formula_is_substitution(
+                    &step.formula, &used_formulas, &statement, &distinct_variables
+                ) {
+                    return Err(CompileError::IncorrectResultingFormula(index));
+                };
+                compiled_proof.push(LogicStep {
+                    used_hypotheses: used_steps,
+                    theorem_ref,
+                    resulting_formula: step.formula
+                });
+                step_ids.push(compiled_proof.len() - 1);
+            };
+            match step_ids.last() {
+                Some(&id) if compiled_proof[id].resulting_formula == resulting_formula => line_steps.push(id),
+                _ => return Err(CompileError::IncorrectResultingFormula(index))
+            };
             continue;
         };
         // Definition/Axiom/Theorem usage
@@ -183,72 +466,103 @@ pub fn compile_theorem(
         let Some(reference) = references.get(&theo_name) else {
             return Err(CompileError::UnknownTheorem(theo_name, index));
         };
-        let empty_vec = vec![];
-        let (theo_hypotheses, theo_assertion,
-            wff_count, object_count, theo_ref) = match reference {
-            Reference::DefinitionReference(def_id) => {
-                match &definitions[*def_id] {
-                    Definition {
-                        name: _,
-                        definition,
-                        distinct_wff_count,
-                        distinct_object_count
-                    } => {
-                        let def_ref = Reference::DefinitionReference(*def_id);
-                        (&empty_vec, definition.clone(), *distinct_wff_count, *distinct_object_count, def_ref)
-                    }
-                }
-            },
-            Reference::AxiomReference(ax_id, 0) => {
-                match &axioms[*ax_id] {
-                    Axiom {
-                        name: _,
-                        hypotheses,
-                        assertions,
-                        distinct_wff_count,
-                        distinct_object_count
-                    } => {
-                        let Some(assertion) = assertions.get(assert_id) else {
-                            return Err(CompileError::UnknownTheorem(theo_name + "." + &assert_id.to_string(), index));
-                        };
-                        let ax_ref = Reference::AxiomReference(*ax_id, assert_id);
-                        (hypotheses, assertion.clone(), *distinct_wff_count, *distinct_object_count, ax_ref)
-                    }
-                }
-            },
-            Reference::TheoremReference(theo_id, 0) => {
-                match &theorems[*theo_id] {
-                    Theorem {
-                        name: _,
-                        hypotheses,
-                        assertions,
-                        proof: _,
-                        distinct_wff_count,
-                        distinct_object_count
-                    } => {
-                        let Some(assertion) = assertions.get(assert_id) else {
-                            return Err(CompileError::UnknownTheorem(theo_name + "." + &assert_id.to_string(), index));
-                        };
-                        let theo_ref = Reference::TheoremReference(*theo_id, assert_id);
-                        (hypotheses, assertion.clone(), *distinct_wff_count, *distinct_object_count, theo_ref)
-                    }
-                }
+        let theo_ref = match reference {
+            Reference::DefinitionReference(def_id) => Reference::DefinitionReference(*def_id),
+            Reference::AxiomReference(ax_id, 0) => Reference::AxiomReference(*ax_id, assert_id),
+            Reference::TheoremReference(theo_id, 0) if *theo_id < theorems.len() => {
+                Reference::TheoremReference(*theo_id, assert_id)
             },
+            // The names of the theorems coming after this one are not known to it
+            Reference::TheoremReference(_, 0) => return Err(CompileError::UnknownTheorem(theo_name, index)),
             _ => return Err(CompileError::WeirdReference)
         };
-        if used_hypots.len() != theo_hypotheses.len() {
-            return Err(CompileError::IncorrectNumberOfHypothesis(used_hypots.len(), theo_hypotheses.len(), index));
+        let Some(statement) = referenced_statement(theo_ref, definitions, axioms, theorems) else {
+            return Err(CompileError::UnknownTheorem(theo_name + "." + &assert_id.to_string(), index));
+        };
+        let given_formula = formula
+            .map(|formula| compile_proof_formula(formula, parser, &mut wffs, &mut objects))
+            .transpose()?;
+        if given_formula.as_ref().is_some_and(wff_has_work_variables) { work_lines.push(index); };
+        let given_formula = given_formula.map(|formula| work.resolve_wff(&formula));
+        // Lines with work variables are unified with the statement, whose variables become work variables too
+        if given_formula.as_ref().is_some_and(wff_has_work_variables) || used_hypotheses.iter().any(wff_has_work_variables) {
+            if used_hypots.len() != statement.hypotheses.len() {
+                return Err(CompileError::IncorrectNumberOfHypothesis(used_hypots.len(), statement.hypotheses.len(), index));
+            };
+            let Some((statement_hypotheses, statement_assertion)) = work.instantiate(&statement) else {
+                return Err(CompileError::IncorrectResultingFormula(index));
+            };
+            let mut pairs = statement_hypotheses.into_iter()
+                .zip(used_hypotheses)
+                .collect::<Vec<_>>();
+            match given_formula {
+                Some(formula) => pairs.push((formula, statement_assertion.clone())),
+                None => work_lines.push(index)
+            };
+            if !work.unify(&pairs) {
+                return Err(CompileError::IncorrectResultingFormula(index));
+            };
+            compiled_proof.push(LogicStep {
+                used_hypotheses: used_hypots,
+                theorem_ref: theo_ref,
+                resulting_formula: work.resolve_wff(&statement_assertion)
+            });
+            line_steps.push(compiled_proof.len() - 1);
+            unified_lines.push(index);
+            continue;
+        };
+        // The used hypotheses can be left blank, or be given in any order
+        let given_order_fits = used_hypots.len() == statement.hypotheses.len() && match &given_formula {
+            Some(formula) => formula_is_substitution(
+                formula, &used_hypotheses, &statement, &distinct_variables
+            ),
+            None => substituted_assertion(
+                &used_hypotheses, statement.hypotheses, &statement.assertion,
+                statement.wff_count, statement.object_count
+            ).is_some()
+        };
+        let mut used_hypots = used_hypots;
+        if !given_order_fits {
+            if !used_hypots.is_empty() && used_hypots.len() != statement.hypotheses.len() {
+                return Err(CompileError::IncorrectNumberOfHypothesis(used_hypots.len(), statement.hypotheses.len(), index));
+            };
+            let candidates = match used_hypots.is_empty() {
+                true => line_steps.clone(),
+                false => used_hypots.clone()
+            };
+            let candidate_formulas = candidates.iter()
+                .map(|id| work.resolve_wff(&compiled_proof[*id].resulting_formula))
+                .collect::<Vec<_>>();
+            let candidate_formulas = candidate_formulas.iter().collect::<Vec<_>>();
+            let found = find_used_hypotheses(
+                &candidate_formulas, used_hypots.is_empty(), given_formula.as_ref(), &statement, &distinct_variables
+            );
+            used_hypots = match &found[..] {
+                [] => return Err(CompileError::IncorrectResultingFormula(index)),
+                [picked] => picked.iter().map(|id| candidates[*id]).collect(),
+                _ => return Err(CompileError::AmbiguousUsedHypotheses(index))
+            };
+            inferred_lines.push(index);
         };
-        let used_hypots = used_hypots.into_iter()
-            .map(|n| n.checked_sub(1).ok_or(CompileError::InaccessibleHypothesis(0, index)))
-            .collect::<Result<Vec<_>, _>>()?;
         let used_hypotheses = used_hypots.iter()
-            .map(|idx| compiled_proof.get(*idx)
-                .ok_or(CompileError::InaccessibleHypothesis(idx+1, index))
-                .map(|step| step.resulting_formula.clone())
-            ).collect::<Result<Vec<_>, _>>()?;
-        let resulting_formula = compile_formula(formula, syntaxes, &mut wffs, &mut objects)?;
-        if !formula_is_substitution(&resulting_formula, &used_hypotheses, &theo_hypotheses, &theo_assertion, wff_count, object_count) {
+            .map(|idx| work.resolve_wff(&compiled_proof[*idx].resulting_formula))
+            .collect::<Vec<_>>();
+        let resulting_formula = match given_formula {
+            Some(formula) => formula,
+            None => {
+                let Some(formula) = substituted_assertion(
+                    &used_hypotheses, statement.hypotheses, &statement.assertion,
+                    statement.wff_count, statement.object_count
+                ) else {
+                    return Err(CompileError::UndeterminedFormula(index));
+                };
+                if !inferred_lines.contains(&index) { inferred_lines.push(index); };
+                formula
+            }
+        };
+        if !formula_is_substitution(
+            &resulting_formula, &used_hypotheses, &statement, &distinct_variables
+        ) {
             return Err(CompileError::IncorrectResultingFormula(index));
         };
         compiled_proof.push(LogicStep {
@@ -256,23 +570,51 @@ pub fn compile_theorem(
             theorem_ref: theo_ref,
             resulting_formula
         });
+        line_steps.push(compiled_proof.len() - 1);
     };
-    // Verify that assertions have been proven
-    let steps = compiled_proof.iter()
-        .map(|l| &l.resulting_formula)
-        .collect::<Vec<_>>();
-    for (index, assertion) in assertions.iter().enumerate() {
-        if !steps.contains(&assertion) {
-            return Err(CompileError::AssertionNotProven(index));
+    // The work variables found by the later lines complete the formulas of the earlier ones
+    for step in compiled_proof.iter_mut() {
+        step.resulting_formula = work.resolve_wff(&step.resulting_formula);
+    };
+    for index in unified_lines {
+        let step = &compiled_proof[line_steps[index - 1]];
+        let used_formulas = step.used_hypotheses.iter()
+            .map(|id| compiled_proof[*id].resulting_formula.clone())
+            .collect::<Vec<_>>();
+        if wff_has_work_variables(&step.resulting_formula) || used_formulas.iter().any(wff_has_work_variables) {
+            open_goals.push(index);
+            continue;
+        };
+        // Distinct variables can only be checked once the formulas are complete
+        let Some(statement) = referenced_statement(step.theorem_ref, definitions, axioms, theorems) else { continue; };
+        if !formula_is_substitution(
+            &step.resulting_formula, &used_formulas, &statement, &distinct_variables
+        ) {
+            return Err(CompileError::IncorrectResultingFormula(index));
         };
     };
-
-    Ok(Theorem {
+    for index in work_lines {
+        let step = &compiled_proof[line_steps[index - 1]];
+        if !wff_has_work_variables(&step.resulting_formula) && !inferred_lines.contains(&index) {
+            inferred_lines.push(index);
+        };
+    };
+    open_goals.sort();
+    let theorem = Theorem {
         name,
         hypotheses: hypot_list,
         assertions,
+        distinct_variables,
         proof: compiled_proof,
         distinct_wff_count: wffs.len(),
         distinct_object_count: objects.len()
+    };
+    Ok(PartialTheorem {
+        theorem,
+        hypothesis_names,
+        line_steps,
+        inferred_lines,
+        open_goals,
+        names: VariableNames::new(&wffs, &objects)
     })
 }
@@ -0,0 +1,652 @@
+use std::collections::{HashMap, HashSet};
+use crate::parsing::USUAL_WFFS;
+use super::{
+    SyntaxType, Placeholder,
+    WellFormedFormula, Object, Variable,
+    LogicStep, Reference, Library,
+    Parameter, Piece, pieces,
+    WFF_NAMES, OBJECT_NAMES,
+    Substitution, find_wff_substitutions, wff_variables,
+    referenced_statement
+};
+
+/// A Metamath database written from a library, and the items it could not contain, with the reason why
+pub struct MetamathExport {
+    pub database: String,
+    pub skipped: Vec<(String, String)>
+}
+
+const TYPECODES: [&str; 3] = ["wff", "object", "|-"];
+
+/// Metamath symbols are printable ASCII: the usual symbols get their set.mm spelling,
+/// the other characters are written with their code point
+const USUAL_CONSTANTS: [(char, &str); 19] = [
+    ('⇒', "->"), ('⇔', "<->"), ('¬', "-."), ('∧', "/\\"), ('∨', "\\/"),
+    ('∀', "A."), ('∃', "E."), ('∈', "e."), ('∉', "e/"), ('≠', "=/="),
+    ('⊆', "C_"), ('⊂', "C."), ('∪', "u."), ('∩', "i^i"), ('∅', "(/)"),
+    ('⊤', "T."), ('⊥', "F."), ('≤', "<_"), ('×', "X.")
+];
+
+fn constant_token(c: char) -> String {
+    if let Some((_, token)) = USUAL_CONSTANTS.iter().find(|(usual, _)| *usual == c) {
+        return token.to_string();
+    };
+    match c.is_ascii_graphic() && c != '$' {
+        true => c.to_string(),
+        false => format!("u{:04X}", c as u32)
+    }
+}
+
+/// Labels can only contain letters, digits, '-', '_' and '.'
+fn label_base(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '_'
+        })
+        .collect()
+}
+
+/// Labels and math symbols share a single namespace
+fn unique(mut name: String, taken: &mut HashSet<String>) -> String {
+    while taken.contains(&name) {
+        name.push('_');
+    };
+    taken.insert(name.clone());
+    name
+}
+
+/// Writes the tokens of a statement, going to the next line before the lines get longer than 79 characters
+fn wrapped(indent: &str, tokens: &[String]) -> String {
+    let mut res = indent.to_owned();
+    let mut line_length = indent.len();
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && line_length + 1 + token.len() > 79 {
+            res.push('\n');
+            res.push_str(indent);
+            res.push_str("    ");
+            line_length = indent.len() + 4;
+        } else if index > 0 {
+            res.push(' ');
+            line_length += 1;
+        };
+        res.push_str(token);
+        line_length += token.len();
+    };
+    res.push('\n');
+    res
+}
+
+/// The number of wff and object variables to declare, enough for every variable written in the library
+fn variable_counts(lib: &Library) -> (usize, usize) {
+    let mut variables = Vec::new();
+    for syntax in &lib.syntaxes {
+        for placeholder in &syntax.formula {
+            match placeholder {
+                Placeholder::WellFormedFormula(id) => variables.push(Variable::Wff(*id)),
+                Placeholder::Object(id) => variables.push(Variable::Object(*id)),
+                Placeholder::LiteralChar(_) | Placeholder::Repetition => ()
+            };
+        };
+    };
+    let formulas = lib.definitions.iter()
+        .map(|definition| &definition.definition)
+        .chain(lib.axioms.iter().flat_map(|axiom| axiom.hypotheses.iter().chain(&axiom.assertions)))
+        .chain(lib.theorems.iter().flat_map(|theorem| {
+            theorem.hypotheses.iter()
+                .chain(&theorem.assertions)
+                .chain(theorem.proof.iter().map(|step| &step.resulting_formula))
+        }));
+    for wff in formulas {
+        wff_variables(wff, &mut variables);
+    };
+    let distinct = lib.axioms.iter()
+        .flat_map(|axiom| &axiom.distinct_variables)
+        .chain(lib.theorems.iter().flat_map(|theorem| &theorem.distinct_variables));
+    for (var1, var2) in distinct {
+        variables.extend([*var1, *var2]);
+    };
+    variables.iter().fold((0, 0), |(wffs, objects), variable| match variable {
+        Variable::Wff(id) => (wffs.max(id + 1), objects),
+        Variable::Object(id) => (wffs, objects.max(id + 1))
+    })
+}
+
+/// The syntax of a composite formula or object, and its parameters
+fn composite(param: Parameter<'_>) -> Option<(usize, &Vec<WellFormedFormula>, &Vec<Object>)> {
+    match param {
+        Parameter::Wff(WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters })
+        | Parameter::Object(Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters }) => {
+            Some((*syntax_ref, wff_parameters, object_parameters))
+        },
+        _ => None
+    }
+}
+
+/// A part of a proof, which is only given in full the first time it is needed
+#[derive(PartialEq, Eq, Hash)]
+enum Subproof {
+    Wff(WellFormedFormula),
+    Object(Object),
+    Step(usize)
+}
+
+enum ProofItem {
+    Label(String),
+    Repeated(usize)  // The index of the last item of a subproof given before
+}
+
+/// A proof in the compressed format of Metamath, which refers back to the subproofs it needs several times
+#[derive(Default)]
+struct CompressedProof {
+    items: Vec<ProofItem>,
+    saved: Vec<bool>,  // Whether the subproof ending at an item is referred to later
+    subproofs: HashMap<Subproof, usize>
+}
+
+/// A number of the compressed format: a letter from 'A' to 'T' preceded by letters from 'U' to 'Y'
+fn proof_number(number: usize) -> String {
+    let mut letters = vec![(b'A' + ((number - 1) % 20) as u8) as char];
+    let mut rest = (number - 1) / 20;
+    while rest > 0 {
+        letters.push((b'U' + ((rest - 1) % 5) as u8) as char);
+        rest = (rest - 1) / 5;
+    };
+    letters.iter().rev().collect()
+}
+
+impl CompressedProof {
+    fn push(&mut self, label: String) {
+        self.items.push(ProofItem::Label(label));
+        self.saved.push(false);
+    }
+
+    /// Refers back to a subproof if it was already given, otherwise it is to be given now
+    fn repeat(&mut self, subproof: &Subproof) -> bool {
+        let Some(end) = self.subproofs.get(subproof) else { return false; };
+        self.saved[*end] = true;
+        self.items.push(ProofItem::Repeated(*end));
+        self.saved.push(false);
+        true
+    }
+
+    fn end(&mut self, subproof: Subproof) {
+        self.subproofs.insert(subproof, self.items.len() - 1);
+    }
+
+    /// The tokens of the proof, for a statement whose mandatory hypotheses are numbered first
+    fn tokens(&self, mandatory: &[String]) -> Vec<String> {
+        let mut numbers = mandatory.iter()
+            .enumerate()
+            .map(|(index, label)| (label.as_str(), index + 1))
+            .collect::<HashMap<_, _>>();
+        let mut tokens = vec!["(".to_owned()];
+        for item in &self.items {
+            let ProofItem::Label(label) = item else { continue; };
+            if numbers.contains_key(label.as_str()) { continue; };
+            numbers.insert(label, numbers.len() + 1);
+            tokens.push(label.clone());
+        };
+        tokens.push(")".to_owned());
+        let mut letters = String::new();
+        let mut saved_numbers = HashMap::new();
+        for (index, item) in self.items.iter().enumerate() {
+            let number = match item {
+                ProofItem::Label(label) => numbers[label.as_str()],
+                ProofItem::Repeated(end) => numbers.len() + saved_numbers[end]
+            };
+            letters.push_str(&proof_number(number));
+            if self.saved[index] {
+                saved_numbers.insert(index, saved_numbers.len() + 1);
+                letters.push('Z');
+            };
+        };
+        // The letters can be cut anywhere, each piece fills a line of the statement
+        let letters = letters.chars().collect::<Vec<_>>();
+        tokens.extend(letters.chunks(71).map(|chunk| chunk.iter().collect::<String>()));
+        tokens
+    }
+}
+
+/// The variables of a statement, in the order of their floating hypotheses
+fn statement_variables(hypotheses: &[WellFormedFormula], assertion: &WellFormedFormula) -> Vec<Variable> {
+    let mut variables = Vec::new();
+    hypotheses.iter()
+        .chain([assertion])
+        .for_each(|wff| wff_variables(wff, &mut variables));
+    variables.sort();
+    variables
+}
+
+struct Exporter<'a> {
+    lib: &'a Library,
+    syntax_names: &'a [String],
+    taken: HashSet<String>,
+    constants: Vec<(char, String)>,
+    wff_variables: Vec<(String, String)>,  // The symbol of each variable, and the label of its floating hypothesis
+    object_variables: Vec<(String, String)>,
+    syntax_variables: Vec<Vec<Variable>>,  // The variables of each syntax, in the order its axiom expects them
+    syntax_labels: Vec<Option<String>>,  // None for the items that were skipped
+    definition_labels: Vec<Option<String>>,
+    axiom_labels: Vec<Option<Vec<String>>>,  // A label per assertion
+    theorem_labels: Vec<Option<Vec<String>>>,
+    skipped: Vec<(String, String)>
+}
+
+impl<'a> Exporter<'a> {
+    fn new(lib: &'a Library, syntax_names: &'a [String]) -> Exporter<'a> {
+        let mut taken = TYPECODES.iter().map(|typecode| typecode.to_string()).collect::<HashSet<_>>();
+        let mut constants = Vec::new();
+        for syntax in &lib.syntaxes {
+            for placeholder in &syntax.formula {
+                let Placeholder::LiteralChar(c) = placeholder else { continue; };
+                if constants.iter().any(|(known, _)| known == c) { continue; };
+                let token = constant_token(*c);
+                taken.insert(token.clone());
+                constants.push((*c, token));
+            };
+        };
+        // The variables are named like the letters the library writes them with
+        let (wff_count, object_count) = variable_counts(lib);
+        let wff_names = (0..wff_count)
+            .map(|id| {
+                let usual = WFF_NAMES.get(id)
+                    .and_then(|letter| USUAL_WFFS.iter().find(|(_, usual)| usual == letter));
+                let name = usual.map_or_else(|| format!("wff{id}"), |(name, _)| name.to_string());
+                unique(name, &mut taken)
+            })
+            .collect::<Vec<_>>();
+        let object_names = (0..object_count)
+            .map(|id| {
+                let usual = OBJECT_NAMES.get(id)
+                    .and_then(|letter| char::from_u32(*letter as u32 - '𝑎' as u32 + 'a' as u32));
+                let name = usual.map_or_else(|| format!("obj{id}"), |letter| letter.to_string());
+                unique(name, &mut taken)
+            })
+            .collect::<Vec<_>>();
+        let wff_variables = wff_names.into_iter()
+            .map(|name| (name.clone(), unique(format!("w{name}"), &mut taken)))
+            .collect();
+        let object_variables = object_names.into_iter()
+            .map(|name| (name.clone(), unique(format!("v{name}"), &mut taken)))
+            .collect();
+        Exporter {
+            lib,
+            syntax_names,
+            taken,
+            constants,
+            wff_variables,
+            object_variables,
+            syntax_variables: Vec::new(),
+            syntax_labels: Vec::new(),
+            definition_labels: Vec::new(),
+            axiom_labels: Vec::new(),
+            theorem_labels: Vec::new(),
+            skipped: Vec::new()
+        }
+    }
+
+    fn constant(&self, c: char) -> String {
+        self.constants.iter()
+            .find(|(known, _)| *known == c)
+            .map_or_else(|| constant_token(c), |(_, token)| token.clone())
+    }
+
+    fn variable_symbol(&self, variable: Variable) -> String {
+        match variable {
+            Variable::Wff(id) => self.wff_variables[id].0.clone(),
+            Variable::Object(id) => self.object_variables[id].0.clone()
+        }
+    }
+
+    fn syntax_name(&self, syntax_id: usize) -> String {
+        self.syntax_names.get(syntax_id)
+            .cloned()
+            .unwrap_or_else(|| format!("syn{syntax_id}"))
+    }
+
+    /// Why a formula or an object cannot be written in Metamath, if it cannot
+    fn parameter_problem(&self, param: Parameter) -> Option<String> {
+        match param {
+            Parameter::Wff(WellFormedFormula::Atomic(_)) | Parameter::Object(Object::Atomic(_)) => return None,
+            Parameter::Wff(WellFormedFormula::Repetition) | Parameter::Object(Object::Repetition) => {
+                return Some("its formulas contain \"…\"".to_string());
+            },
+            Parameter::Wff(WellFormedFormula::WorkVariable(_)) | Parameter::Object(Object::WorkVariable(_)) => {
+                return Some("its formulas contain work variables".to_string());
+            },
+            _ => ()
+        };
+        let (syntax_ref, wff_parameters, object_parameters) = composite(param)?;
+        if self.syntax_labels[syntax_ref].is_none() {
+            return Some(format!("it uses the syntax {}, which was skipped", self.syntax_name(syntax_ref)));
+        };
+        wff_parameters.iter().find_map(|wff| self.parameter_problem(Parameter::Wff(wff)))
+            .or_else(|| object_parameters.iter().find_map(|obj| self.parameter_problem(Parameter::Object(obj))))
+    }
+
+    fn formulas_problem<'f>(&self, mut formulas: impl Iterator<Item = &'f WellFormedFormula>) -> Option<String> {
+        formulas.find_map(|wff| self.parameter_problem(Parameter::Wff(wff)))
+    }
+
+    fn push_tokens(&self, param: Parameter, tokens: &mut Vec<String>) {
+        match param {
+            Parameter::Wff(WellFormedFormula::Atomic(id)) => tokens.push(self.wff_variables[*id].0.clone()),
+            Parameter::Object(Object::Atomic(id)) => tokens.push(self.object_variables[*id].0.clone()),
+            _ => for piece in pieces(&self.lib.syntaxes, param).map(|(_, pieces)| pieces).unwrap_or_default() {
+                match piece {
+                    Piece::Literal(c) => tokens.push(self.constant(c)),
+                    Piece::Parameter(param) => self.push_tokens(param, tokens)
+                };
+            }
+        };
+    }
+
+    /// The tokens of a statement asserting a formula
+    fn assertion_tokens(&self, label: &str, keyword: &str, wff: &WellFormedFormula) -> Vec<String> {
+        let mut tokens = vec![label.to_owned(), keyword.to_owned(), "|-".to_owned()];
+        self.push_tokens(Parameter::Wff(wff), &mut tokens);
+        tokens
+    }
+
+    /// Pushes the proof that an expression is a formula or an object, which is the parse tree it is built from
+    fn push_syntax_proof(&self, param: Parameter, proof: &mut CompressedProof) {
+        let subproof = match param {
+            Parameter::Wff(WellFormedFormula::Atomic(id)) => return proof.push(self.wff_variables[*id].1.clone()),
+            Parameter::Object(Object::Atomic(id)) => return proof.push(self.object_variables[*id].1.clone()),
+            Parameter::Wff(wff) => Subproof::Wff(wff.clone()),
+            Parameter::Object(obj) => Subproof::Object(obj.clone())
+        };
+        if proof.repeat(&subproof) { return; };
+        let Some((syntax_ref, wff_parameters, object_parameters)) = composite(param) else { return; };
+        for variable in &self.syntax_variables[syntax_ref] {
+            match variable {
+                Variable::Wff(id) => self.push_syntax_proof(Parameter::Wff(&wff_parameters[*id]), proof),
+                Variable::Object(id) => self.push_syntax_proof(Parameter::Object(&object_parameters[*id]), proof)
+            };
+        };
+        if let Some(label) = &self.syntax_labels[syntax_ref] {
+            proof.push(label.clone());
+        };
+        proof.end(subproof);
+    }
+
+    /// Pushes the proof of a step: the syntax proofs of the expressions substituted for the variables
+    /// of the referenced statement, then the proofs of its hypotheses, then its label
+    fn push_step_proof(
+        &self, steps: &[LogicStep], hypothesis_labels: &[String], step_id: usize, proof: &mut CompressedProof
+    ) -> Option<()> {
+        let step = &steps[step_id];
+        let label = match step.theorem_ref {
+            Reference::HypothesisReference(hyp_id) => {
+                proof.push(hypothesis_labels.get(hyp_id)?.clone());
+                return Some(());
+            },
+            Reference::DefinitionReference(def_id) => self.definition_labels.get(def_id)?.clone()?,
+            Reference::AxiomReference(ax_id, assert_id) => self.axiom_labels.get(ax_id)?.as_ref()?.get(assert_id)?.clone(),
+            Reference::TheoremReference(theo_id, assert_id) => {
+                self.theorem_labels.get(theo_id)?.as_ref()?.get(assert_id)?.clone()
+            },
+            Reference::UnprovenReference => return None
+        };
+        if proof.repeat(&Subproof::Step(step_id)) { return Some(()); };
+        let lib = self.lib;
+        let statement = referenced_statement(step.theorem_ref, &lib.definitions, &lib.axioms, &lib.theorems)?;
+        let mut mapping = Substitution::new(statement.wff_count, statement.object_count);
+        find_wff_substitutions(&statement.assertion, &step.resulting_formula, &mut mapping).ok()?;
+        // The used hypotheses are in the order of the hypotheses of the statement
+        for (hypothesis, used) in statement.hypotheses.iter().zip(&step.used_hypotheses) {
+            find_wff_substitutions(hypothesis, &steps.get(*used)?.resulting_formula, &mut mapping).ok()?;
+        };
+        for variable in statement_variables(statement.hypotheses, &statement.assertion) {
+            let param = match variable {
+                Variable::Wff(id) => Parameter::Wff(mapping.wffs[id]?),
+                Variable::Object(id) => Parameter::Object(mapping.objects[id]?)
+            };
+            self.push_syntax_proof(param, proof);
+        };
+        for used in &step.used_hypotheses {
+            self.push_step_proof(steps, hypothesis_labels, *used, proof)?;
+        };
+        proof.push(label);
+        proof.end(Subproof::Step(step_id));
+        Some(())
+    }
+
+    /// The labels of the hypotheses a statement has in Metamath, in the order proofs give them
+    fn mandatory_hypotheses(
+        &self, hypotheses: &[WellFormedFormula], hypothesis_labels: &[String], assertion: &WellFormedFormula
+    ) -> Vec<String> {
+        statement_variables(hypotheses, assertion).into_iter()
+            .map(|variable| match variable {
+                Variable::Wff(id) => self.wff_variables[id].1.clone(),
+                Variable::Object(id) => self.object_variables[id].1.clone()
+            })
+            .chain(hypothesis_labels.iter().cloned())
+            .collect()
+    }
+
+    /// Writes statements sharing their hypotheses and distinct variables, in a block of their own if they have some
+    fn write_block(
+        &self,
+        res: &mut String,
+        hypotheses: &[(String, &WellFormedFormula)],
+        distinct: &[(Variable, Variable)],
+        statements: Vec<Vec<String>>
+    ) {
+        let in_block = !hypotheses.is_empty() || !distinct.is_empty();
+        let indent = if in_block { "  " } else { "" };
+        if in_block { res.push_str("${\n"); };
+        for (var1, var2) in distinct {
+            let tokens = ["$d".to_owned(), self.variable_symbol(*var1), self.variable_symbol(*var2), "$.".to_owned()];
+            res.push_str(&wrapped(indent, &tokens));
+        };
+        for (label, hypothesis) in hypotheses {
+            let mut tokens = self.assertion_tokens(label, "$e", hypothesis);
+            tokens.push("$.".to_owned());
+            res.push_str(&wrapped(indent, &tokens));
+        };
+        for tokens in statements {
+            res.push_str(&wrapped(indent, &tokens));
+        };
+        if in_block { res.push_str("$}\n"); };
+    }
+
+    fn hypothesis_labels<'f>(
+        &mut self, name: &str, hypotheses: &'f [WellFormedFormula]
+    ) -> Vec<(String, &'f WellFormedFormula)> {
+        hypotheses.iter()
+            .enumerate()
+            .map(|(hyp_id, hypothesis)| (unique(format!("{}.h{}", label_base(name), hyp_id + 1), &mut self.taken), hypothesis))
+            .collect()
+    }
+
+    fn write_symbols(&self, res: &mut String) {
+        let constants = ["$c"].into_iter()
+            .chain(TYPECODES)
+            .map(str::to_owned)
+            .chain(self.constants.iter().map(|(_, token)| token.clone()))
+            .chain(["$.".to_owned()])
+            .collect::<Vec<_>>();
+        res.push_str(&wrapped("", &constants));
+        let variables = self.wff_variables.iter().chain(&self.object_variables).collect::<Vec<_>>();
+        if variables.is_empty() { return; };
+        let tokens = ["$v".to_owned()].into_iter()
+            .chain(variables.iter().map(|(symbol, _)| symbol.clone()))
+            .chain(["$.".to_owned()])
+            .collect::<Vec<_>>();
+        res.push_str(&wrapped("", &tokens));
+        for (symbol, label) in &self.wff_variables {
+            res.push_str(&format!("{label} $f wff {symbol} $.\n"));
+        };
+        for (symbol, label) in &self.object_variables {
+            res.push_str(&format!("{label} $f object {symbol} $.\n"));
+        };
+    }
+
+    fn write_syntaxes(&mut self, res: &mut String) {
+        let lib = self.lib;
+        res.push_str("\n$( Syntaxes $)\n");
+        for (syntax_id, syntax) in lib.syntaxes.iter().enumerate() {
+            let mut variables = syntax.formula.iter()
+                .filter_map(|placeholder| match placeholder {
+                    Placeholder::WellFormedFormula(id) => Some(Variable::Wff(*id)),
+                    Placeholder::Object(id) => Some(Variable::Object(*id)),
+                    Placeholder::LiteralChar(_) | Placeholder::Repetition => None
+                })
+                .collect::<Vec<_>>();
+            variables.sort();
+            variables.dedup();
+            self.syntax_variables.push(variables);
+            let name = self.syntax_name(syntax_id);
+            if syntax.formula.contains(&Placeholder::Repetition) {
+                self.skipped.push((name, "variadic syntaxes have no Metamath counterpart".to_string()));
+                self.syntax_labels.push(None);
+                continue;
+            };
+            let (prefix, typecode) = match syntax.syntax_type {
+                SyntaxType::Formula => ("w", "wff"),
+                SyntaxType::Object => ("c", "object")
+            };
+            let label = unique(prefix.to_owned() + &label_base(&name), &mut self.taken);
+            let mut tokens = vec![label.clone(), "$a".to_owned(), typecode.to_owned()];
+            for placeholder in &syntax.formula {
+                tokens.push(match placeholder {
+                    Placeholder::LiteralChar(c) => self.constant(*c),
+                    Placeholder::WellFormedFormula(id) => self.variable_symbol(Variable::Wff(*id)),
+                    Placeholder::Object(id) => self.variable_symbol(Variable::Object(*id)),
+                    Placeholder::Repetition => unreachable!()
+                });
+            };
+            tokens.push("$.".to_owned());
+            res.push_str(&wrapped("", &tokens));
+            self.syntax_labels.push(Some(label));
+        };
+    }
+
+    fn write_definitions(&mut self, res: &mut String) {
+        let lib = self.lib;
+        res.push_str("\n$( Definitions $)\n");
+        for definition in &lib.definitions {
+            if let Some(reason) = self.parameter_problem(Parameter::Wff(&definition.definition)) {
+                self.skipped.push((definition.name.clone(), reason));
+                self.definition_labels.push(None);
+                continue;
+            };
+            let label = unique(format!("df-{}", label_base(&definition.name)), &mut self.taken);
+            let mut tokens = self.assertion_tokens(&label, "$a", &definition.definition);
+            tokens.push("$.".to_owned());
+            res.push_str(&wrapped("", &tokens));
+            self.definition_labels.push(Some(label));
+        };
+    }
+
+    fn write_axioms(&mut self, res: &mut String) {
+        let lib = self.lib;
+        res.push_str("\n$( Axioms $)\n");
+        for (ax_id, axiom) in lib.axioms.iter().enumerate() {
+            if let Some(reason) = self.formulas_problem(axiom.hypotheses.iter().chain(&axiom.assertions)) {
+                self.skipped.push((axiom.name.clone(), reason));
+                self.axiom_labels.push(None);
+                continue;
+            };
+            let hypotheses = self.hypothesis_labels(&axiom.name, &axiom.hypotheses);
+            let labels = (0..axiom.assertions.len())
+                .map(|assert_id| {
+                    let name = lib.reference_name(&Reference::AxiomReference(ax_id, assert_id)).unwrap_or_default();
+                    unique(label_base(&name), &mut self.taken)
+                })
+                .collect::<Vec<_>>();
+            let statements = labels.iter()
+                .zip(&axiom.assertions)
+                .map(|(label, assertion)| {
+                    let mut tokens = self.assertion_tokens(label, "$a", assertion);
+                    tokens.push("$.".to_owned());
+                    tokens
+                })
+                .collect();
+            self.write_block(res, &hypotheses, &axiom.distinct_variables, statements);
+            self.axiom_labels.push(Some(labels));
+        };
+    }
+
+    fn theorem_problem(&self, theo_id: usize) -> Option<String> {
+        let lib = self.lib;
+        let theorem = &lib.theorems[theo_id];
+        let formulas = theorem.hypotheses.iter()
+            .chain(&theorem.assertions)
+            .chain(theorem.proof.iter().map(|step| &step.resulting_formula));
+        if let Some(reason) = self.formulas_problem(formulas) { return Some(reason); };
+        theorem.proof.iter().find_map(|step| {
+            let skipped = match step.theorem_ref {
+                Reference::DefinitionReference(def_id) => self.definition_labels[def_id].is_none(),
+                Reference::AxiomReference(ax_id, _) => self.axiom_labels[ax_id].is_none(),
+                Reference::TheoremReference(theo_id, _) => self.theorem_labels[theo_id].is_none(),
+                Reference::HypothesisReference(_) | Reference::UnprovenReference => false
+            };
+            let name = lib.reference_name(&step.theorem_ref)?;
+            skipped.then(|| format!("its proof uses {name}, which was skipped"))
+        })
+    }
+
+    fn write_theorems(&mut self, res: &mut String) {
+        let lib = self.lib;
+        res.push_str("\n$( Theorems $)\n");
+        for (theo_id, theorem) in lib.theorems.iter().enumerate() {
+            if let Some(reason) = self.theorem_problem(theo_id) {
+                self.skipped.push((theorem.name.clone(), reason));
+                self.theorem_labels.push(None);
+                continue;
+            };
+            let hypotheses = self.hypothesis_labels(&theorem.name, &theorem.hypotheses);
+            let hypothesis_labels = hypotheses.iter().map(|(label, _)| label.clone()).collect::<Vec<_>>();
+            let proofs = theorem.assertions.iter()
+                .map(|assertion| {
+                    let step_id = theorem.proof.iter().position(|step| step.resulting_formula == *assertion)?;
+                    let mut proof = CompressedProof::default();
+                    self.push_step_proof(&theorem.proof, &hypothesis_labels, step_id, &mut proof)?;
+                    let mandatory = self.mandatory_hypotheses(&theorem.hypotheses, &hypothesis_labels, assertion);
+                    Some(proof.tokens(&mandatory))
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(proofs) = proofs else {
+                self.skipped.push((theorem.name.clone(), "its proof could not be translated".to_string()));
+                self.theorem_labels.push(None);
+                continue;
+            };
+            let labels = (0..theorem.assertions.len())
+                .map(|assert_id| {
+                    let name = lib.reference_name(&Reference::TheoremReference(theo_id, assert_id)).unwrap_or_default();
+                    unique(label_base(&name), &mut self.taken)
+                })
+                .collect::<Vec<_>>();
+            let statements = labels.iter()
+                .zip(&theorem.assertions)
+                .zip(proofs)
+                .map(|((label, assertion), proof)| {
+                    let mut tokens = self.assertion_tokens(label, "$p", assertion);
+                    tokens.push("$=".to_owned());
+                    tokens.extend(proof);
+                    tokens.push("$.".to_owned());
+                    tokens
+                })
+                .collect();
+            self.write_block(res, &hypotheses, &theorem.distinct_variables, statements);
+            self.theorem_labels.push(Some(labels));
+        };
+    }
+}
+
+/// Writes the library as a Metamath database: its syntaxes become syntax axioms, its definitions and axioms
+/// axioms, and its theorems theorems whose proofs give the parse trees of the formulas along with the steps.
+/// The variadic syntaxes, and the items using them or using skipped items, are skipped
+pub fn export_metamath(lib: &Library, syntax_names: &[String]) -> MetamathExport {
+    let mut exporter = Exporter::new(lib, syntax_names);
+    let mut res = String::from("$( Written by math_lib from a compiled library $)\n\n");
+    exporter.write_symbols(&mut res);
+    exporter.write_syntaxes(&mut res);
+    exporter.write_definitions(&mut res);
+    exporter.write_axioms(&mut res);
+    exporter.write_theorems(&mut res);
+    MetamathExport { database: res, skipped: exporter.skipped }
+}
@@ -0,0 +1,160 @@
+use super::{
+    WellFormedFormula, Reference,
+    Definition, Axiom, Theorem,
+    PartialTheorem, referenced_statement,
+    find_used_hypotheses
+};
+
+/// A line of the proof being minimized, whose used lines are counted from 0
+#[derive(Clone)]
+pub struct MinimizedLine {
+    pub used_lines: Vec<usize>,
+    pub reference: Option<Reference>,  // None when the line keeps the reference it was written with
+    pub original_line: usize,
+    pub formula: WellFormedFormula
+}
+
+/// The lines an assertion is proven by, and the lines they use
+fn needed_lines(lines: &[MinimizedLine], assertions: &[WellFormedFormula]) -> Vec<bool> {
+    let mut needed = vec![false; lines.len()];
+    for assertion in assertions {
+        if let Some(index) = lines.iter().position(|line| line.formula == *assertion) {
+            needed[index] = true;
+        };
+    };
+    for index in (0..lines.len()).rev() {
+        if !needed[index] { continue; };
+        for used in &lines[index].used_lines {
+            needed[*used] = true;
+        };
+    };
+    needed
+}
+
+/// Keeps the needed lines only, renumbered
+fn prune(lines: Vec<MinimizedLine>, assertions: &[WellFormedFormula]) -> (Vec<MinimizedLine>, Vec<usize>) {
+    let needed = needed_lines(&lines, assertions);
+    let mut new_indices = vec![0; lines.len()];
+    let mut res = Vec::new();
+    for (index, mut line) in lines.into_iter().enumerate() {
+        if !needed[index] { continue; };
+        line.used_lines = line.used_lines.iter().map(|used| new_indices[*used]).collect();
+        new_indices[index] = res.len();
+        res.push(line);
+    };
+    (res, new_indices)
+}
+
+/// Makes the lines using a line that proves the same formula as an earlier one use the earlier one instead,
+/// and gives these lines along with the earlier ones
+fn merge_duplicates(lines: &mut [MinimizedLine]) -> Vec<(usize, usize)> {
+    let mut duplicates = Vec::new();
+    let mut first_lines = Vec::new();
+    for index in 0..lines.len() {
+        let first = lines[..index].iter()
+            .position(|line| line.formula == lines[index].formula)
+            .unwrap_or(index);
+        if first != index { duplicates.push((index, first)); };
+        first_lines.push(first);
+    };
+    for line in lines.iter_mut() {
+        line.used_lines = line.used_lines.iter().map(|used| first_lines[*used]).collect();
+    };
+    duplicates
+}
+
+/// The lines no assertion depends on, and the lines proving the same formula as an earlier line, counted from 1
+pub fn useless_lines(partial: &PartialTheorem) -> (Vec<usize>, Vec<(usize, usize)>) {
+    let mut lines = proof_lines(partial);
+    let duplicates = merge_duplicates(&mut lines);
+    let unused = needed_lines(&lines, &partial.theorem.assertions).iter()
+        .enumerate()
+        .filter(|(index, needed)| !**needed && duplicates.iter().all(|(duplicate, _)| duplicate != index))
+        .map(|(index, _)| index + 1)
+        .collect();
+    let duplicates = duplicates.iter()
+        .map(|(duplicate, first)| (duplicate + 1, first + 1))
+        .collect();
+    (unused, duplicates)
+}
+
+/// Removes the lines no assertion depends on, and the lines proving the same formula as an earlier line
+pub fn tidy_proof(mut lines: Vec<MinimizedLine>, assertions: &[WellFormedFormula]) -> Vec<MinimizedLine> {
+    merge_duplicates(&mut lines);
+    prune(lines, assertions).0
+}
+
+/// The lines of a compiled proof, a line using the lines whose last step its steps rely on,
+/// in the order the steps use them
+pub fn proof_lines(partial: &PartialTheorem) -> Vec<MinimizedLine> {
+    let proof = &partial.theorem.proof;
+    let mut lines = Vec::new();
+    for (index, step_id) in partial.line_steps.iter().enumerate() {
+        let mut used_lines = Vec::new();
+        // The steps are popped in the order they are used, those of other lines being replaced by the steps they use
+        let mut steps = proof[*step_id].used_hypotheses.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(step) = steps.pop() {
+            match partial.line_steps.iter().position(|id| *id == step) {
+                Some(line) => if !used_lines.contains(&line) { used_lines.push(line); },
+                None => steps.extend(proof[step].used_hypotheses.iter().rev())
+            };
+        };
+        lines.push(MinimizedLine {
+            used_lines,
+            reference: None,
+            original_line: index + 1,
+            formula: proof[*step_id].resulting_formula.clone()
+        });
+    };
+    lines
+}
+
+/// Replaces lines with a single use of one of the theorems, as long as it makes the proof shorter
+pub fn minimize_proof(
+    lines: Vec<MinimizedLine>,
+    theorem: &Theorem,
+    definitions: &[Definition],
+    axioms: &[Axiom],
+    theorems: &[Theorem]
+) -> Vec<MinimizedLine> {
+    let references = theorems.iter().enumerate()
+        .flat_map(|(theo_id, theorem)| {
+            (0..theorem.assertions.len()).map(move |assert_id| Reference::TheoremReference(theo_id, assert_id))
+        })
+        .collect::<Vec<_>>();
+    let (mut lines, _) = prune(lines, &theorem.assertions);
+    let mut index = 0;
+    while index < lines.len() {
+        // A line that uses no other line cannot be replaced by a shorter one
+        if lines[index].used_lines.is_empty() {
+            index += 1;
+            continue;
+        };
+        let candidates = lines[..index].iter()
+            .map(|line| &line.formula)
+            .collect::<Vec<_>>();
+        let mut shortest: Option<(Vec<MinimizedLine>, Vec<usize>)> = None;
+        for reference in &references {
+            let Some(statement) = referenced_statement(*reference, definitions, axioms, theorems) else { continue; };
+            let found = find_used_hypotheses(
+                &candidates, true, Some(&lines[index].formula), &statement, &theorem.distinct_variables
+            );
+            for used_lines in found {
+                let mut replaced = lines.clone();
+                replaced[index].used_lines = used_lines;
+                replaced[index].reference = Some(*reference);
+                let (replaced, new_indices) = prune(replaced, &theorem.assertions);
+                if replaced.len() < shortest.as_ref().map_or(lines.len(), |(shorter, _)| shorter.len()) {
+                    shortest = Some((replaced, new_indices));
+                };
+            };
+        };
+        // Removing lines gives no new way to prove the lines before, so the next tries start after the line
+        if let Some((shorter, new_indices)) = shortest {
+            lines = shorter;
+            index = new_indices[index];
+        };
+        index += 1;
+    };
+    lines
+}
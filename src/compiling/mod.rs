@@ -4,34 +4,107 @@ pub use compiler::{
     add_axiom_to_lib,
     add_theo_to_lib,
     verify_theo,
-    compile_directory
+    prove_tautology,
+    complete_proof,
+    minimize,
+    tidy,
+    fill_proof,
+    syntax_names,
+    compile_directory,
+    compile_sorted_directory,
+    dependency_order
 };
 
 mod types;
 pub use types::{
     Syntax, SyntaxType, Placeholder,
-    WellFormedFormula, Object,
+    Precedence, Associativity,
+    WellFormedFormula, Object, Variable,
     Definition, Axiom, Theorem,
     LogicStep, Reference,
-    PartiallyCompiled,
     Library
 };
+use types::repetition_indices;
 
 mod math_file;
 use math_file::{
     compile_syntax,
     compile_definition,
     compile_axiom,
-    compile_theorem
+    compile_theorem,
+    compile_partial_theorem,
+    assertions_are_proven,
+    PartialTheorem,
+    Statement,
+    referenced_statement
 };
 
+mod earley;
+use earley::{Grammar, Token, precedence_allows};
+
+mod display;
+pub use display::{
+    VariableNames,
+    wff_to_string,
+    wff_to_explicit_string
+};
+use display::{
+    Parameter, Piece, Parentheses, pieces, parameter_to_string,
+    parameter_to_latex, syntax_to_latex, syntax_to_string,
+    WFF_NAMES, OBJECT_NAMES
+};
+
+mod ambiguity;
+use ambiguity::find_ambiguity;
+
+mod tautology;
+use tautology::{PropositionalSystem, tautology_error};
+
+mod search;
+use search::{search_proof, SearchedLine};
+
+mod dependencies;
+pub use dependencies::{LibraryItem, axioms_used, used_by, dependents, dependency_graph};
+use dependencies::reverse_index;
+
+mod cache;
+pub use cache::BuildCache;
+use cache::{CachedFile, PreviousBuild, content_hash};
+
+mod parallel;
+use parallel::{TheoremTask, verify_theorems};
+
+mod metamath;
+pub use metamath::export_metamath;
+
+mod latex;
+pub use latex::{library_item_to_latex, theorem_to_latex, latex_document};
+
+mod site;
+pub use site::{library_site, SiteSection};
+
+mod minimize;
+use minimize::{MinimizedLine, minimize_proof, tidy_proof, proof_lines, useless_lines};
+
 mod formula;
-use formula::compile_formula;
+pub use formula::FormulaParser;
+use formula::{
+    compile_formula,
+    compile_proof_formula,
+    compile_distinct_variables
+};
+
+mod unification;
+use unification::{WorkVariables, wff_has_work_variables};
 
 mod verification;
 use verification::{
-    formula_is_contained,
-    formula_is_substitution
+    Substitution,
+    find_wff_substitutions,
+    wff_variables,
+    formula_is_substitution,
+    substituted_assertion,
+    find_used_hypotheses
 };
 
 mod error;
@@ -0,0 +1,97 @@
+use std::{thread, sync::Mutex, collections::{HashMap, VecDeque, BTreeMap}};
+use crate::parsing::MathFile;
+use super::{Library, Theorem, Reference, CompileError, FormulaParser, compile_theorem};
+
+/// A theorem left to be verified
+pub struct TheoremTask {
+    pub id: usize,
+    pub math_file: MathFile,
+    pub dependencies: Vec<usize>  // The theorems whose statements the proof needs
+}
+
+/// Stands for a theorem that is not verified yet, which the proofs verified meanwhile do not use
+fn unverified_theorem() -> Theorem {
+    Theorem {
+        name: String::new(),
+        hypotheses: Vec::new(),
+        assertions: Vec::new(),
+        distinct_variables: Vec::new(),
+        proof: Vec::new(),
+        distinct_wff_count: 0,
+        distinct_object_count: 0
+    }
+}
+
+/// Only the theorems before the task can be cited, the names of the later ones being unknown to it
+fn verify_task(
+    task: TheoremTask,
+    lib: &Library,
+    parser: &FormulaParser,
+    theorems: &[Theorem],
+    references: &HashMap<String, Reference>
+) -> Result<Theorem, CompileError> {
+    compile_theorem(task.math_file, parser, &lib.definitions, &lib.axioms, &theorems[..task.id], references)
+}
+
+/// Verifies the theorems on 'jobs' threads, a theorem only once the theorems it depends on are.
+/// 'theorems' holds the theorems already known, and None at the indices of the tasks.
+/// Gives back the theorems, along with the error of the first theorem of the library that could not be verified,
+/// so that the error is the one compiling the theorems one by one stops at
+pub fn verify_theorems(
+    lib: &Library,
+    parser: &FormulaParser,
+    references: &HashMap<String, Reference>,
+    tasks: Vec<TheoremTask>,
+    theorems: Vec<Option<Theorem>>,
+    jobs: usize
+) -> (Vec<Option<Theorem>>, Option<CompileError>) {
+    let mut verified = theorems.iter().map(Option::is_some).collect::<Vec<_>>();
+    let mut theorems = theorems.into_iter()
+        .map(|theorem| theorem.unwrap_or_else(unverified_theorem))
+        .collect::<Vec<_>>();
+    // The theorems of a level only depend on the theorems of the levels before
+    let mut levels = vec![0; theorems.len()];
+    let mut tasks_by_level = BTreeMap::<usize, Vec<TheoremTask>>::new();
+    for task in tasks {
+        let level = task.dependencies.iter().map(|used| levels[*used] + 1).max().unwrap_or(0);
+        levels[task.id] = level;
+        tasks_by_level.entry(level).or_default().push(task);
+    };
+    let mut first_error: Option<(usize, CompileError)> = None;
+    for (_, level_tasks) in tasks_by_level {
+        // There is no need to verify what comes after a failed theorem
+        let queue = level_tasks.into_iter()
+            .filter(|task| first_error.as_ref().is_none_or(|(failed_id, _)| task.id < *failed_id))
+            .filter(|task| task.dependencies.iter().all(|used| verified[*used]))
+            .collect::<VecDeque<_>>();
+        let task_count = queue.len();
+        let queue = Mutex::new(queue);
+        let results = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, task_count.max(1)) {
+                scope.spawn(|| loop {
+                    let Some(task) = queue.lock().unwrap().pop_front() else { break; };
+                    let theo_id = task.id;
+                    let result = verify_task(task, lib, parser, &theorems, references);
+                    results.lock().unwrap().push((theo_id, result));
+                });
+            };
+        });
+        for (theo_id, result) in results.into_inner().unwrap() {
+            match result {
+                Ok(theorem) => {
+                    theorems[theo_id] = theorem;
+                    verified[theo_id] = true;
+                },
+                Err(e) => if first_error.as_ref().is_none_or(|(failed_id, _)| theo_id < *failed_id) {
+                    first_error = Some((theo_id, e));
+                }
+            };
+        };
+    };
+    let theorems = theorems.into_iter()
+        .zip(verified)
+        .map(|(theorem, verified)| verified.then_some(theorem))
+        .collect();
+    (theorems, first_error.map(|(_, e)| e))
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use super::{
+    WellFormedFormula, Object, Variable, Reference,
+    Definition, Axiom, Theorem,
+    PartialTheorem, Statement, referenced_statement,
+    Substitution, find_wff_substitutions, wff_variables,
+    formula_is_substitution,
+    WorkVariables, wff_has_work_variables,
+    CompileError
+};
+
+/// Maximum number of ways to use a statement that are tried for a single goal
+const MAX_INSTANTIATIONS: usize = 64;
+
+/// Where a formula known to be true comes from
+enum Source {
+    Line(usize),  // A line already in the proof, with its new number
+    Hypothesis(usize),
+    Step { reference: Reference, used_facts: Vec<usize> }
+}
+
+struct Fact {
+    formula: WellFormedFormula,
+    source: Source
+}
+
+/// A proof line written by the search; its reference is a HypothesisReference for the hypotheses of the theorem
+pub struct SearchedLine {
+    pub line_no: usize,
+    pub used_lines: Vec<usize>,
+    pub reference: Reference,
+    pub formula: WellFormedFormula
+}
+
+/// The proof completed by the search
+pub struct SearchedProof {
+    pub line_numbers: Vec<usize>,  // The new number of each line of the proof
+    pub goal_lines: Vec<(usize, Vec<SearchedLine>)>,  // The steps left to be proven, with the lines replacing them
+    pub added_lines: Vec<SearchedLine>  // The lines proving the assertions that no line proves, written at the end
+}
+
+struct Search<'a> {
+    statements: Vec<(Reference, Statement<'a>)>,
+    distinct: &'a [(Variable, Variable)],
+    facts: Vec<Fact>,
+    given_fact_count: usize,  // The facts from the theorem and its proof come first
+    failures: HashMap<WellFormedFormula, usize>,  // The greatest depth at which a goal could not be proven
+    goals: Vec<WellFormedFormula>,  // The goals being proven, which cannot be used to prove themselves
+    deadline: Instant
+}
+
+struct TimedOut;
+
+/// A formula of a proof found by unification, whose work variables are found along the way
+#[derive(Clone)]
+struct UnifiedNode {
+    formula: WellFormedFormula,
+    proof: Option<UnifiedProof>  // None while it is left to be proven
+}
+
+#[derive(Clone)]
+enum UnifiedProof {
+    Fact(usize),
+    Step(Reference, Vec<usize>)  // The nodes proving the hypotheses of the statement
+}
+
+#[derive(Clone)]
+struct Unification {
+    work: WorkVariables,
+    nodes: Vec<UnifiedNode>,
+    pending: Vec<(usize, usize)>  // The nodes left to be proven, with the depth left to prove them
+}
+
+fn wff_subexpressions<'a>(
+    wff: &'a WellFormedFormula, wffs: &mut Vec<&'a WellFormedFormula>, objects: &mut Vec<&'a Object>
+) {
+    if !wffs.contains(&wff) { wffs.push(wff); };
+    if let WellFormedFormula::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } = wff {
+        wff_parameters.iter().for_each(|wff| wff_subexpressions(wff, wffs, objects));
+        object_parameters.iter().for_each(|obj| object_subexpressions(obj, wffs, objects));
+    };
+}
+
+fn object_subexpressions<'a>(
+    obj: &'a Object, wffs: &mut Vec<&'a WellFormedFormula>, objects: &mut Vec<&'a Object>
+) {
+    if !objects.contains(&obj) { objects.push(obj); };
+    if let Object::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } = obj {
+        wff_parameters.iter().for_each(|wff| wff_subexpressions(wff, wffs, objects));
+        object_parameters.iter().for_each(|obj| object_subexpressions(obj, wffs, objects));
+    };
+}
+
+impl Search<'_> {
+    /// The hypotheses a statement needs to prove the goal, for every way found to substitute its variables.
+    /// The variables that only appear in the hypotheses are taken from the known facts,
+    /// or else from the subexpressions of the goal
+    fn instantiations(&self, statement: &Statement, goal: &WellFormedFormula) -> Vec<Vec<WellFormedFormula>> {
+        let mut candidate_wffs = Vec::new();
+        let mut candidate_objects = Vec::new();
+        wff_subexpressions(goal, &mut candidate_wffs, &mut candidate_objects);
+        let mut mapping = Substitution::new(statement.wff_count, statement.object_count);
+        if find_wff_substitutions(&statement.assertion, goal, &mut mapping).is_err() { return Vec::new(); };
+        let mut mappings = vec![mapping];
+        for hypothesis in statement.hypotheses {
+            let mut next_mappings = Vec::new();
+            for mapping in mappings {
+                // A hypothesis reduced to a variable would match every fact, so it only takes the given ones
+                let facts = match hypothesis {
+                    WellFormedFormula::Atomic(_) => &self.facts[..self.given_fact_count],
+                    _ => &self.facts[..]
+                };
+                if mapping.apply_to_wff(hypothesis).is_none() {
+                    for fact in facts {
+                        let mut extended = mapping.clone();
+                        if find_wff_substitutions(hypothesis, &fact.formula, &mut extended).is_ok() {
+                            next_mappings.push(extended);
+                        };
+                    };
+                };
+                next_mappings.push(mapping);
+            };
+            next_mappings.truncate(MAX_INSTANTIATIONS);
+            mappings = next_mappings;
+        };
+        let mut variables = Vec::new();
+        statement.hypotheses.iter().for_each(|hyp| wff_variables(hyp, &mut variables));
+        for variable in variables {
+            let mut next_mappings = Vec::new();
+            for mapping in mappings {
+                match variable {
+                    Variable::Wff(id) if mapping.wffs[id].is_none() => for wff in &candidate_wffs {
+                        let mut extended = mapping.clone();
+                        extended.wffs[id] = Some(wff);
+                        next_mappings.push(extended);
+                    },
+                    Variable::Object(id) if mapping.objects[id].is_none() => for obj in &candidate_objects {
+                        let mut extended = mapping.clone();
+                        extended.objects[id] = Some(obj);
+                        next_mappings.push(extended);
+                    },
+                    _ => next_mappings.push(mapping)
+                };
+            };
+            next_mappings.truncate(MAX_INSTANTIATIONS);
+            mappings = next_mappings;
+        };
+        let mut res = Vec::new();
+        for mapping in mappings {
+            let Some(hypotheses) = statement.hypotheses.iter()
+                .map(|hyp| mapping.apply_to_wff(hyp))
+                .collect::<Option<Vec<_>>>() else { continue; };
+            if res.contains(&hypotheses) { continue; };
+            if !formula_is_substitution(
+                goal, &hypotheses, statement, self.distinct
+            ) { continue; };
+            res.push(hypotheses);
+        };
+        res
+    }
+
+    /// Starts the search of a new goal from the given facts
+    fn set_facts(&mut self, facts: Vec<Fact>) {
+        self.given_fact_count = facts.len();
+        self.facts = facts;
+        self.failures.clear();
+    }
+
+    /// Proves the goal with deeper and deeper proofs, until 'max_depth' is reached
+    fn prove_iteratively(&mut self, goal: &WellFormedFormula, max_depth: usize) -> Result<Option<usize>, TimedOut> {
+        for depth in 0..=max_depth {
+            if let Some(fact_id) = self.prove(goal, depth)? { return Ok(Some(fact_id)); };
+            if let Some(fact_id) = self.prove_by_unification(goal, depth)? { return Ok(Some(fact_id)); };
+        };
+        Ok(None)
+    }
+
+    /// Proves the goal with at most 'depth' nested uses of statements, the variables that only appear in the
+    /// hypotheses of a statement becoming work variables, found by proving the hypotheses.
+    /// The work variables left at the end stand for a variable of the goal
+    fn prove_by_unification(&mut self, goal: &WellFormedFormula, depth: usize) -> Result<Option<usize>, TimedOut> {
+        let mut variables = Vec::new();
+        wff_variables(goal, &mut variables);
+        let wff = variables.iter().find_map(|variable| match variable {
+            Variable::Wff(id) => Some(WellFormedFormula::Atomic(*id)),
+            Variable::Object(_) => None
+        });
+        let obj = variables.iter().find_map(|variable| match variable {
+            Variable::Object(id) => Some(Object::Atomic(*id)),
+            Variable::Wff(_) => None
+        });
+        let unification = Unification {
+            work: WorkVariables::new(&[]),
+            nodes: vec![UnifiedNode { formula: goal.clone(), proof: None }],
+            pending: vec![(0, depth)]
+        };
+        self.unify_pending(unification, (wff.as_ref(), obj.as_ref()))
+    }
+
+    fn unify_pending(
+        &mut self, mut unification: Unification, defaults: (Option<&WellFormedFormula>, Option<&Object>)
+    ) -> Result<Option<usize>, TimedOut> {
+        if Instant::now() > self.deadline { return Err(TimedOut); };
+        if unification.pending.is_empty() { return Ok(self.add_unified_facts(unification, defaults)); };
+        // A goal reduced to a work variable would match every fact, so it waits for the others to find it
+        let position = unification.pending.iter()
+            .position(|(node_id, _)| !matches!(
+                unification.work.resolve_wff(&unification.nodes[*node_id].formula),
+                WellFormedFormula::WorkVariable(_)
+            ))
+            .unwrap_or(0);
+        let (node_id, depth) = unification.pending.remove(position);
+        let goal = unification.work.resolve_wff(&unification.nodes[node_id].formula);
+        let is_work_variable = matches!(goal, WellFormedFormula::WorkVariable(_));
+        if !wff_has_work_variables(&goal) {
+            if let Some(fact_id) = self.prove(&goal, depth)? {
+                unification.nodes[node_id].proof = Some(UnifiedProof::Fact(fact_id));
+                return self.unify_pending(unification, defaults);
+            };
+        } else {
+            let fact_count = if is_work_variable { self.given_fact_count } else { self.facts.len() };
+            for fact_id in 0..fact_count {
+                let mut next = unification.clone();
+                if !next.work.unify(&[(goal.clone(), self.facts[fact_id].formula.clone())]) { continue; };
+                next.nodes[node_id].proof = Some(UnifiedProof::Fact(fact_id));
+                if let Some(fact_id) = self.unify_pending(next, defaults)? { return Ok(Some(fact_id)); };
+            };
+        };
+        if depth == 0 { return Ok(None); };
+        for statement_id in 0..self.statements.len() {
+            let (reference, statement) = &self.statements[statement_id];
+            if is_work_variable && !statement.hypotheses.is_empty() { continue; };
+            let reference = *reference;
+            let mut next = unification.clone();
+            let Some((hypotheses, assertion)) = next.work.instantiate(statement) else { continue; };
+            if !next.work.unify(&[(goal.clone(), assertion)]) { continue; };
+            let first_node = next.nodes.len();
+            for hypothesis in hypotheses {
+                next.pending.push((next.nodes.len(), depth - 1));
+                next.nodes.push(UnifiedNode { formula: hypothesis, proof: None });
+            };
+            next.nodes[node_id].proof = Some(UnifiedProof::Step(reference, (first_node..next.nodes.len()).collect()));
+            if let Some(fact_id) = self.unify_pending(next, defaults)? { return Ok(Some(fact_id)); };
+        };
+        Ok(None)
+    }
+
+    /// Adds the facts of a proof found by unification, once its work variables are all known,
+    /// and returns the fact proving its goal, unless the proof breaks the distinct variables
+    fn add_unified_facts(
+        &mut self, mut unification: Unification, defaults: (Option<&WellFormedFormula>, Option<&Object>)
+    ) -> Option<usize> {
+        let formulas = unification.nodes.iter().map(|node| &node.formula).collect::<Vec<_>>();
+        if !unification.work.complete(&formulas, defaults.0, defaults.1) { return None; };
+        let fact_count = self.facts.len();
+        let fact_id = self.add_unified_fact(&unification, 0);
+        if fact_id.is_none() { self.facts.truncate(fact_count); };
+        fact_id
+    }
+
+    fn add_unified_fact(&mut self, unification: &Unification, node_id: usize) -> Option<usize> {
+        let node = &unification.nodes[node_id];
+        let (reference, used_nodes) = match &node.proof {
+            Some(UnifiedProof::Fact(fact_id)) => return Some(*fact_id),
+            Some(UnifiedProof::Step(reference, used_nodes)) => (*reference, used_nodes),
+            None => return None
+        };
+        let used_facts = used_nodes.iter()
+            .map(|id| self.add_unified_fact(unification, *id))
+            .collect::<Option<Vec<_>>>()?;
+        let formula = unification.work.resolve_wff(&node.formula);
+        let used_formulas = used_facts.iter()
+            .map(|id| self.facts[*id].formula.clone())
+            .collect::<Vec<_>>();
+        let (_, statement) = self.statements.iter().find(|(r, _)| *r == reference)?;
+        if !formula_is_substitution(
+            &formula, &used_formulas, statement, self.distinct
+        ) { return None; };
+        self.facts.push(Fact { formula, source: Source::Step { reference, used_facts } });
+        Some(self.facts.len() - 1)
+    }
+
+    /// Proves the goal with at most 'depth' nested uses of statements, and returns the fact proving it
+    fn prove(&mut self, goal: &WellFormedFormula, depth: usize) -> Result<Option<usize>, TimedOut> {
+        if let Some(fact_id) = self.facts.iter().position(|fact| fact.formula == *goal) {
+            return Ok(Some(fact_id));
+        };
+        if depth == 0 || self.goals.contains(goal) { return Ok(None); };
+        if self.failures.get(goal).is_some_and(|failed_depth| *failed_depth >= depth) { return Ok(None); };
+        if Instant::now() > self.deadline { return Err(TimedOut); };
+        self.goals.push(goal.clone());
+        let res = self.prove_with_statements(goal, depth);
+        self.goals.pop();
+        if let Ok(None) = res {
+            self.failures.insert(goal.clone(), depth);
+        };
+        res
+    }
+
+    fn prove_with_statements(&mut self, goal: &WellFormedFormula, depth: usize) -> Result<Option<usize>, TimedOut> {
+        for statement_id in 0..self.statements.len() {
+            let (reference, statement) = &self.statements[statement_id];
+            let reference = *reference;
+            'instantiations: for hypotheses in self.instantiations(statement, goal) {
+                let mut used_facts = Vec::new();
+                for hypothesis in &hypotheses {
+                    match self.prove(hypothesis, depth - 1)? {
+                        Some(fact_id) => used_facts.push(fact_id),
+                        None => continue 'instantiations
+                    };
+                };
+                self.facts.push(Fact {
+                    formula: goal.clone(),
+                    source: Source::Step { reference, used_facts }
+                });
+                return Ok(Some(self.facts.len() - 1));
+            };
+        };
+        Ok(None)
+    }
+
+    /// Writes the lines needed to prove a fact, and returns the number of the line proving it
+    fn write_lines(
+        &self, fact_id: usize, lines: &mut Vec<SearchedLine>, line_numbers: &mut HashMap<usize, usize>, first_line: usize
+    ) -> usize {
+        if let Some(&line_no) = line_numbers.get(&fact_id) { return line_no; };
+        let fact = &self.facts[fact_id];
+        let (reference, used_lines) = match &fact.source {
+            Source::Line(line_no) => return *line_no,
+            Source::Hypothesis(hypot_id) => (Reference::HypothesisReference(*hypot_id), Vec::new()),
+            Source::Step { reference, used_facts } => {
+                let used_lines = used_facts.iter()
+                    .map(|id| self.write_lines(*id, lines, line_numbers, first_line))
+                    .collect();
+                (*reference, used_lines)
+            }
+        };
+        let line_no = first_line + lines.len();
+        lines.push(SearchedLine { line_no, used_lines, reference, formula: fact.formula.clone() });
+        line_numbers.insert(fact_id, line_no);
+        line_no
+    }
+}
+
+/// Completes the proof of a theorem by chaining backwards from its steps left to be proven, and then from the
+/// assertions that no line proves, with the given statements, trying deeper proofs until 'max_depth' is reached
+/// or the time runs out.
+/// The lines proving a step replace it, the following lines being renumbered
+pub fn search_proof(
+    partial: &PartialTheorem,
+    definitions: &[Definition],
+    axioms: &[Axiom],
+    theorems: &[Theorem],
+    max_depth: usize,
+    time_limit: Duration
+) -> Result<SearchedProof, CompileError> {
+    let theorem = &partial.theorem;
+    // The formula of a goal must be known to be searched
+    for line_no in &partial.open_goals {
+        let step = &theorem.proof[partial.line_steps[line_no - 1]];
+        if step.theorem_ref != Reference::UnprovenReference || wff_has_work_variables(&step.resulting_formula) {
+            return Err(CompileError::GoalNotFound(*line_no));
+        };
+    };
+    let mut statements = Vec::new();
+    let references = (0..definitions.len()).map(Reference::DefinitionReference)
+        .chain(axioms.iter().enumerate().flat_map(|(ax_id, axiom)| {
+            (0..axiom.assertions.len()).map(move |assert_id| Reference::AxiomReference(ax_id, assert_id))
+        }))
+        .chain(theorems.iter().enumerate().flat_map(|(theo_id, theorem)| {
+            (0..theorem.assertions.len()).map(move |assert_id| Reference::TheoremReference(theo_id, assert_id))
+        }));
+    for reference in references {
+        if let Some(statement) = referenced_statement(reference, definitions, axioms, theorems) {
+            statements.push((reference, statement));
+        };
+    };
+    // Statements with fewer hypotheses give shorter proofs
+    statements.sort_by_key(|(_, statement)| statement.hypotheses.len());
+    let mut search = Search {
+        statements,
+        distinct: &theorem.distinct_variables,
+        given_fact_count: 0,
+        facts: Vec::new(),
+        failures: HashMap::new(),
+        goals: Vec::new(),
+        deadline: Instant::now() + time_limit
+    };
+    // The earlier lines and the hypotheses are known to be true, the earlier goals being proven first
+    let known_facts = |line_numbers: &[usize]| partial.line_steps.iter()
+        .zip(line_numbers)
+        .map(|(step_id, line_no)| Fact {
+            formula: theorem.proof[*step_id].resulting_formula.clone(),
+            source: Source::Line(*line_no)
+        })
+        .chain(theorem.hypotheses.iter().enumerate().map(|(hypot_id, hypothesis)| Fact {
+            formula: hypothesis.clone(),
+            source: Source::Hypothesis(hypot_id)
+        }))
+        .collect::<Vec<_>>();
+    let mut line_numbers = Vec::new();
+    let mut goal_lines = Vec::new();
+    let mut next_line = 1;
+    for (index, step_id) in partial.line_steps.iter().enumerate() {
+        if !partial.open_goals.contains(&(index + 1)) {
+            line_numbers.push(next_line);
+            next_line += 1;
+            continue;
+        };
+        search.set_facts(known_facts(&line_numbers));
+        let goal = &theorem.proof[*step_id].resulting_formula;
+        let fact_id = match search.prove_iteratively(goal, max_depth) {
+            Ok(Some(fact_id)) => fact_id,
+            Ok(None) => return Err(CompileError::GoalNotFound(index + 1)),
+            Err(TimedOut) => return Err(CompileError::GoalSearchTimedOut(index + 1))
+        };
+        // A goal already proven by an earlier line is replaced by that line
+        let mut lines = Vec::new();
+        line_numbers.push(search.write_lines(fact_id, &mut lines, &mut HashMap::new(), next_line));
+        next_line += lines.len();
+        goal_lines.push((index + 1, lines));
+    };
+    search.set_facts(known_facts(&line_numbers));
+    let mut proven = Vec::new();
+    for (index, assertion) in theorem.assertions.iter().enumerate() {
+        match search.prove_iteratively(assertion, max_depth) {
+            Ok(Some(fact_id)) => proven.push(fact_id),
+            Ok(None) => return Err(CompileError::ProofNotFound(index)),
+            Err(TimedOut) => return Err(CompileError::ProofSearchTimedOut(index))
+        };
+    };
+    let mut added_lines = Vec::new();
+    let mut written = HashMap::new();
+    for fact_id in proven {
+        search.write_lines(fact_id, &mut added_lines, &mut written, next_line);
+    };
+    Ok(SearchedProof { line_numbers, goal_lines, added_lines })
+}
@@ -0,0 +1,366 @@
+use std::collections::{HashMap, HashSet};
+use crate::parsing::MathFile;
+use super::{
+    WellFormedFormula, Object, Variable,
+    Reference, Library, LibraryItem, CompileError, FormulaParser,
+    VariableNames, Parameter, Parentheses,
+    parameter_to_string, syntax_to_string, reverse_index,
+    compile_partial_theorem, proof_lines
+};
+
+const STYLE_SHEET: &str = "body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }\n\
+    nav { display: flex; justify-content: space-between; border-bottom: 1px solid #ccc; padding-bottom: 0.5em; }\n\
+    table { border-collapse: collapse; margin: 1em 0; }\n\
+    th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }\n\
+    th { background: #eee; }\n\
+    .formula { font-family: serif; font-size: 1.1em; white-space: nowrap; }\n\
+    .definition { background: #ffffe0; }\n\
+    .axiom { background: #e0f0ff; }\n\
+    .theorem { background: #e0ffe0; }\n";
+
+/// A section of 'order.txt', with its items in order, the theorems along with their files
+pub struct SiteSection {
+    pub title: String,
+    pub items: Vec<(LibraryItem, Option<MathFile>)>
+}
+
+fn escape_html(text: &str) -> String {
+    let mut res = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            c => res.push(c)
+        };
+    };
+    res
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+        <link rel=\"stylesheet\" href=\"style.css\">\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+fn formula_html(formula: String) -> String {
+    format!("<span class=\"formula\">{}</span>", escape_html(&formula))
+}
+
+fn wff_html(wff: &WellFormedFormula, lib: &Library, names: &VariableNames) -> String {
+    formula_html(parameter_to_string(Parameter::Wff(wff), &lib.syntaxes, names, Parentheses::Minimal))
+}
+
+fn variable_html(var: Variable, lib: &Library, names: &VariableNames) -> String {
+    let param = match var {
+        Variable::Wff(id) => parameter_to_string(
+            Parameter::Wff(&WellFormedFormula::Atomic(id)), &lib.syntaxes, names, Parentheses::Minimal
+        ),
+        Variable::Object(id) => parameter_to_string(
+            Parameter::Object(&Object::Atomic(id)), &lib.syntaxes, names, Parentheses::Minimal
+        )
+    };
+    formula_html(param)
+}
+
+/// The item whose page shows a syntax, definition, axiom or theorem, syntaxes only having one through their definition
+fn page_item(item: LibraryItem, lib: &Library, syntax_names: &[String]) -> Option<LibraryItem> {
+    match item {
+        LibraryItem::Syntax(syntax_id) => lib.definitions.iter()
+            .position(|definition| Some(&definition.name) == syntax_names.get(syntax_id))
+            .map(LibraryItem::Definition),
+        item => Some(item)
+    }
+}
+
+/// The file names of the pages, made of the characters of the names that are safe in a path
+fn page_names(lib: &Library, syntax_names: &[String]) -> HashMap<LibraryItem, String> {
+    let mut taken = HashSet::from(["index".to_string(), "style".to_string()]);
+    let mut pages = HashMap::new();
+    let items = (0..lib.definitions.len()).map(LibraryItem::Definition)
+        .chain((0..lib.axioms.len()).map(LibraryItem::Axiom))
+        .chain((0..lib.theorems.len()).map(LibraryItem::Theorem));
+    for item in items {
+        let mut name = item.name(lib, syntax_names).chars()
+            .map(|c| match c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                true => c,
+                false => '_'
+            })
+            .collect::<String>();
+        while taken.contains(&name) {
+            name.push('_');
+        };
+        taken.insert(name.clone());
+        pages.insert(item, name + ".html");
+    };
+    pages
+}
+
+/// The pages of a library
+struct Site<'a> {
+    lib: &'a Library,
+    parser: FormulaParser<'a>,
+    syntax_names: &'a [String],
+    pages: HashMap<LibraryItem, String>,
+    users: HashMap<LibraryItem, Vec<usize>>
+}
+
+impl Site<'_> {
+    fn link(&self, item: LibraryItem, text: &str) -> String {
+        match self.pages.get(&item) {
+            Some(page) => format!("<a href=\"{page}\">{}</a>", escape_html(text)),
+            None => escape_html(text)
+        }
+    }
+
+    fn item_link(&self, item: LibraryItem) -> String {
+        self.link(item, &item.name(self.lib, self.syntax_names))
+    }
+
+    /// The links to the index and to the pages before and after, in the order of the index
+    fn navigation(&self, previous: Option<LibraryItem>, next: Option<LibraryItem>) -> String {
+        let previous = previous.map_or(String::new(), |item| format!("&lt; {}", self.item_link(item)));
+        let next = next.map_or(String::new(), |item| format!("{} &gt;", self.item_link(item)));
+        format!("<nav><span>{previous}</span><a href=\"index.html\">Index</a><span>{next}</span></nav>\n")
+    }
+
+    /// The hypotheses, assertions and distinct variables of a statement, as a table
+    fn statement(
+        &self,
+        hypotheses: &[(String, &WellFormedFormula)],
+        assertions: &[WellFormedFormula],
+        distinct_variables: &[(Variable, Variable)],
+        names: &VariableNames
+    ) -> String {
+        let mut res = String::from("<table class=\"statement\">\n");
+        for (name, hypothesis) in hypotheses {
+            res.push_str(&format!(
+                "<tr><th>Hypothesis</th><td>{}</td><td>{}</td></tr>\n",
+                escape_html(name), wff_html(hypothesis, self.lib, names)
+            ));
+        };
+        for (index, assertion) in assertions.iter().enumerate() {
+            // Assertions after the first one are referred to as 'name.1', 'name.2'...
+            let number = match index {
+                0 => String::new(),
+                _ => format!(".{index}")
+            };
+            res.push_str(&format!(
+                "<tr><th>Assertion</th><td>{number}</td><td>{}</td></tr>\n", wff_html(assertion, self.lib, names)
+            ));
+        };
+        res.push_str("</table>\n");
+        if !distinct_variables.is_empty() {
+            let pairs = distinct_variables.iter()
+                .map(|(var1, var2)| {
+                    format!("{} {}", variable_html(*var1, self.lib, names), variable_html(*var2, self.lib, names))
+                })
+                .collect::<Vec<_>>();
+            res.push_str(&format!("<p>Distinct variables: {}</p>\n", pairs.join(", ")));
+        };
+        res
+    }
+
+    fn used_by(&self, item: LibraryItem) -> String {
+        let Some(users) = self.users.get(&item) else {
+            return "<h2>Used by</h2>\n<p>No theorem uses it.</p>\n".to_string();
+        };
+        let links = users.iter()
+            .map(|theo_id| self.item_link(LibraryItem::Theorem(*theo_id)))
+            .collect::<Vec<_>>();
+        format!("<h2>Used by</h2>\n<p>{}</p>\n", links.join(", "))
+    }
+
+    /// The statement of a theorem and the table of its proof, with the names and references of its file,
+    /// each proof line linking to what it refers to
+    fn theorem(&self, file: MathFile, references: &HashMap<String, Reference>) -> Result<String, CompileError> {
+        let MathFile::Theorem { proof, .. } = &file else {
+            return Err(CompileError::IncorrectFileType);
+        };
+        let written_references = proof.iter()
+            .map(|(_, _, reference, _)| reference.clone())
+            .collect::<Vec<_>>();
+        let lib = self.lib;
+        let partial = compile_partial_theorem(
+            file, &self.parser, &lib.definitions, &lib.axioms, &lib.theorems, references
+        )?;
+        let theorem = &partial.theorem;
+        let hypotheses = partial.hypothesis_names.iter()
+            .cloned()
+            .zip(&theorem.hypotheses)
+            .collect::<Vec<_>>();
+        let mut res = self.statement(&hypotheses, &theorem.assertions, &theorem.distinct_variables, &partial.names);
+
+        res.push_str("<h2>Proof</h2>\n<table class=\"proof\">\n");
+        res.push_str("<tr><th>Step</th><th>Hyp</th><th>Ref</th><th>Expression</th></tr>\n");
+        for (index, line) in proof_lines(&partial).iter().enumerate() {
+            let used_lines = line.used_lines.iter()
+                .map(|used| format!("<a href=\"#line-{0}\">{0}</a>", used + 1))
+                .collect::<Vec<_>>();
+            let written_reference = written_references.get(line.original_line - 1).map_or("", String::as_str);
+            let theorem_ref = theorem.proof[partial.line_steps[index]].theorem_ref;
+            // The lines written with a tactic, like 'taut', end with a step citing something else
+            let cited = lib.reference_name(&theorem_ref).is_some_and(|name| name == written_reference);
+            let reference = match theorem_ref {
+                _ if !cited => escape_html(written_reference),
+                Reference::DefinitionReference(def_id) => self.link(LibraryItem::Definition(def_id), written_reference),
+                Reference::AxiomReference(ax_id, _) => self.link(LibraryItem::Axiom(ax_id), written_reference),
+                Reference::TheoremReference(theo_id, _) => self.link(LibraryItem::Theorem(theo_id), written_reference),
+                Reference::HypothesisReference(_) | Reference::UnprovenReference => escape_html(written_reference)
+            };
+            res.push_str(&format!(
+                "<tr id=\"line-{0}\"><td>{0}</td><td>{1}</td><td>{reference}</td><td>{2}</td></tr>\n",
+                line.original_line, used_lines.join(", "), wff_html(&line.formula, lib, &partial.names)
+            ));
+        };
+        res.push_str("</table>\n");
+        Ok(res)
+    }
+
+    fn item_page(
+        &self, item: LibraryItem, file: Option<MathFile>, references: &HashMap<String, Reference>,
+        previous: Option<LibraryItem>, next: Option<LibraryItem>
+    ) -> Result<String, CompileError> {
+        let lib = self.lib;
+        let names = VariableNames::default();
+        let name = item.name(lib, self.syntax_names);
+        let (kind, body) = match item {
+            // Syntaxes are shown on the pages of their definitions
+            LibraryItem::Syntax(_) => unreachable!(),
+            LibraryItem::Definition(def_id) => {
+                let definition = &lib.definitions[def_id];
+                let syntax = self.syntax_names.iter()
+                    .position(|syntax_name| *syntax_name == definition.name)
+                    .and_then(|syntax_id| lib.syntaxes.get(syntax_id));
+                let mut body = String::new();
+                if let Some(syntax) = syntax {
+                    body.push_str(&format!("<p>Syntax: {}</p>\n", formula_html(syntax_to_string(syntax))));
+                };
+                body.push_str(&format!("<p>Definition: {}</p>\n", wff_html(&definition.definition, lib, &names)));
+                ("Definition", body)
+            },
+            LibraryItem::Axiom(ax_id) => {
+                let axiom = &lib.axioms[ax_id];
+                let hypotheses = axiom.hypotheses.iter()
+                    .enumerate()
+                    .map(|(index, hypothesis)| ((index + 1).to_string(), hypothesis))
+                    .collect::<Vec<_>>();
+                ("Axiom", self.statement(&hypotheses, &axiom.assertions, &axiom.distinct_variables, &names))
+            },
+            LibraryItem::Theorem(theo_id) => match file {
+                Some(file) => ("Theorem", self.theorem(file, references)?),
+                None => {
+                    // Without its file, only the statement of the theorem is known
+                    let theorem = &lib.theorems[theo_id];
+                    let hypotheses = theorem.hypotheses.iter()
+                        .enumerate()
+                        .map(|(index, hypothesis)| ((index + 1).to_string(), hypothesis))
+                        .collect::<Vec<_>>();
+                    ("Theorem", self.statement(&hypotheses, &theorem.assertions, &theorem.distinct_variables, &names))
+                }
+            }
+        };
+        let mut body = format!(
+            "{}<h1 class=\"{}\">{kind} {}</h1>\n{body}",
+            self.navigation(previous, next), kind.to_lowercase(), escape_html(&name)
+        );
+        body.push_str(&self.used_by(item));
+        Ok(html_page(&format!("{kind} {name}"), &body))
+    }
+
+    /// The row of the index showing an item, with its statement
+    fn index_row(&self, item: LibraryItem) -> Result<String, CompileError> {
+        let lib = self.lib;
+        let names = VariableNames::default();
+        let missing = CompileError::ItemNotInLibrary(item);
+        let (link, statement) = match item {
+            LibraryItem::Syntax(syntax_id) => {
+                let link = match page_item(item, lib, self.syntax_names) {
+                    Some(def_item) => self.link(def_item, &item.name(lib, self.syntax_names)),
+                    None => escape_html(&item.name(lib, self.syntax_names))
+                };
+                (link, formula_html(syntax_to_string(lib.syntaxes.get(syntax_id).ok_or(missing)?)))
+            },
+            LibraryItem::Definition(def_id) => {
+                (self.item_link(item), wff_html(&lib.definitions.get(def_id).ok_or(missing)?.definition, lib, &names))
+            },
+            LibraryItem::Axiom(_) | LibraryItem::Theorem(_) => {
+                let (hypotheses, assertions) = match item {
+                    LibraryItem::Axiom(ax_id) => {
+                        let axiom = lib.axioms.get(ax_id).ok_or(missing)?;
+                        (&axiom.hypotheses, &axiom.assertions)
+                    },
+                    LibraryItem::Theorem(theo_id) => {
+                        let theorem = lib.theorems.get(theo_id).ok_or(missing)?;
+                        (&theorem.hypotheses, &theorem.assertions)
+                    },
+                    _ => unreachable!()
+                };
+                // Written 'hypotheses ⊢ assertion', an assertion per line
+                let hypotheses = hypotheses.iter()
+                    .map(|hypothesis| wff_html(hypothesis, lib, &names))
+                    .collect::<Vec<_>>();
+                let prefix = match hypotheses.is_empty() {
+                    true => String::new(),
+                    false => format!("{} ⊢ ", hypotheses.join(", "))
+                };
+                let assertions = assertions.iter()
+                    .map(|assertion| prefix.clone() + &wff_html(assertion, lib, &names))
+                    .collect::<Vec<_>>();
+                (self.item_link(item), assertions.join("<br>"))
+            }
+        };
+        Ok(format!("<tr><td>{link}</td><td>{statement}</td></tr>\n"))
+    }
+}
+
+/// The pages of a website showing the library, with their file names: an index listing the items section by section,
+/// and a page for each definition, axiom and theorem, linking to the items its proof refers to
+/// and to the theorems using it. Fails with the name of the theorem whose file could not be compiled
+pub fn library_site(
+    lib: &Library,
+    syntax_names: &[String],
+    references: &HashMap<String, Reference>,
+    sections: Vec<SiteSection>
+) -> Result<Vec<(String, String)>, (String, CompileError)> {
+    let site = Site {
+        lib,
+        parser: FormulaParser::new(&lib.syntaxes),
+        syntax_names,
+        pages: page_names(lib, syntax_names),
+        users: reverse_index(lib)
+    };
+    let mut index = format!(
+        "<h1>Library</h1>\n<p>{} syntaxes, {} definitions, {} axioms and {} theorems</p>\n",
+        lib.syntaxes.len(), lib.definitions.len(), lib.axioms.len(), lib.theorems.len()
+    );
+    let mut items = Vec::new();
+    for section in sections {
+        index.push_str(&format!("<h2>{}</h2>\n<table class=\"index\">\n", escape_html(&section.title)));
+        for (item, file) in section.items {
+            index.push_str(&site.index_row(item).map_err(|e| (item.name(lib, syntax_names), e))?);
+            if let Some(page_item) = page_item(item, lib, syntax_names) {
+                items.push((page_item, file));
+            };
+        };
+        index.push_str("</table>\n");
+    };
+
+    let mut pages = vec![
+        ("index.html".to_string(), html_page("Library", &index)),
+        ("style.css".to_string(), STYLE_SHEET.to_string())
+    ];
+    let page_items = items.iter().map(|(item, _)| *item).collect::<Vec<_>>();
+    for (index, (item, file)) in items.into_iter().enumerate() {
+        let Some(page) = site.pages.get(&item) else { continue; };
+        let previous = index.checked_sub(1).map(|previous| page_items[previous]);
+        let next = page_items.get(index + 1).copied();
+        let content = site.item_page(item, file, references, previous, next)
+            .map_err(|e| (item.name(lib, syntax_names), e))?;
+        pages.push((page.clone(), content));
+    };
+    Ok(pages)
+}
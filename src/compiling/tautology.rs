@@ -0,0 +1,592 @@
+use std::collections::HashMap;
+use super::{
+    Syntax, SyntaxType,
+    WellFormedFormula, Reference,
+    Axiom, Theorem,
+    VariableNames, wff_to_string,
+    CompileError
+};
+
+/// Maximum number of atoms of a formula for its truth table to be computed
+const MAX_ATOMS: usize = 12;
+
+/// The statements of the library that proofs of tautologies are made of:
+/// modus ponens, and the axioms '𝜑 ⇒ (𝜓 ⇒ 𝜑)', '(𝜑 ⇒ (𝜓 ⇒ 𝜒)) ⇒ ((𝜑 ⇒ 𝜓) ⇒ (𝜑 ⇒ 𝜒))' and '(¬𝜑 ⇒ ¬𝜓) ⇒ (𝜓 ⇒ 𝜑)'
+pub struct PropositionalSystem {
+    implication: usize,
+    negation: usize,
+    modus_ponens: Reference,
+    minor_first: bool,  // Whether the hypotheses of modus ponens are '𝜑' then '𝜑 ⇒ 𝜓'
+    axioms: [Reference; 3]
+}
+
+pub enum TautologyError {
+    TooManyAtoms(usize),
+    FalseWhen(Vec<(WellFormedFormula, bool)>)  // Values of the atoms for which the formula is false
+}
+
+/// The error of a proof line, with the atoms written as in the theorem
+pub fn tautology_error(error: TautologyError, syntaxes: &[Syntax], names: &VariableNames, line: usize) -> CompileError {
+    match error {
+        TautologyError::TooManyAtoms(count) => CompileError::TooManyAtoms(count, line),
+        TautologyError::FalseWhen(values) => {
+            let values = values.iter()
+                .map(|(atom, value)| format!("{}: {value}", wff_to_string(atom, syntaxes, names)))
+                .collect();
+            CompileError::NotATautology(values, line)
+        }
+    }
+}
+
+/// A step of a generated proof, which uses the results of previous steps
+pub struct ProofStep {
+    pub used_steps: Vec<usize>,
+    pub reference: Reference,
+    pub formula: WellFormedFormula
+}
+
+enum Connective<'a> {
+    Implication(&'a WellFormedFormula, &'a WellFormedFormula),
+    Negation(&'a WellFormedFormula),
+    Atom  // Any other formula, seen as a propositional variable
+}
+
+fn composite(syntax_ref: usize, wff_parameters: Vec<WellFormedFormula>) -> WellFormedFormula {
+    WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters: Vec::new() }
+}
+
+/// Whether two formulas are the same once the variables of the first one are renamed
+fn same_up_to_renaming(
+    formula1: &WellFormedFormula, formula2: &WellFormedFormula, renaming: &mut HashMap<usize, usize>
+) -> bool {
+    match (formula1, formula2) {
+        (WellFormedFormula::Atomic(id1), WellFormedFormula::Atomic(id2)) => match renaming.get(id1) {
+            Some(id) => id == id2,
+            None if renaming.values().any(|id| id == id2) => false,
+            None => {
+                renaming.insert(*id1, *id2);
+                true
+            }
+        },
+        (
+            WellFormedFormula::SyntaxComposite {
+                syntax_ref: ref1, wff_parameters: wffs1, object_parameters: objects1
+            },
+            WellFormedFormula::SyntaxComposite {
+                syntax_ref: ref2, wff_parameters: wffs2, object_parameters: objects2
+            }
+        ) => {
+            ref1 == ref2 && objects1.is_empty() && objects2.is_empty() && wffs1.len() == wffs2.len()
+            && wffs1.iter().zip(wffs2).all(|(wff1, wff2)| same_up_to_renaming(wff1, wff2, renaming))
+        },
+        _ => false
+    }
+}
+
+impl PropositionalSystem {
+    /// Looks for modus ponens and the three axioms among the axioms and theorems of the library
+    pub fn find(syntaxes: &[Syntax], axioms: &[Axiom], theorems: &[Theorem]) -> Option<PropositionalSystem> {
+        let mut statements = Vec::new();
+        for (ax_id, axiom) in axioms.iter().enumerate() {
+            if !axiom.distinct_variables.is_empty() { continue; };
+            for (assert_id, assertion) in axiom.assertions.iter().enumerate() {
+                statements.push((Reference::AxiomReference(ax_id, assert_id), &axiom.hypotheses, assertion));
+            };
+        };
+        for (theo_id, theorem) in theorems.iter().enumerate() {
+            if !theorem.distinct_variables.is_empty() { continue; };
+            for (assert_id, assertion) in theorem.assertions.iter().enumerate() {
+                statements.push((Reference::TheoremReference(theo_id, assert_id), &theorem.hypotheses, assertion));
+            };
+        };
+        let connectives = |parameter_count: usize| syntaxes.iter()
+            .enumerate()
+            .filter(move |(_, syntax)| {
+                matches!(syntax.syntax_type, SyntaxType::Formula)
+                && syntax.repetition().is_none()
+                && syntax.distinct_wff_count == parameter_count
+                && syntax.distinct_object_count == 0
+            })
+            .map(|(syntax_ref, _)| syntax_ref)
+            .collect::<Vec<_>>();
+        let (ph, ps, ch) = (
+            WellFormedFormula::Atomic(0),
+            WellFormedFormula::Atomic(1),
+            WellFormedFormula::Atomic(2)
+        );
+        let find_axiom = |expected: &WellFormedFormula| statements.iter()
+            .find(|(_, hypotheses, assertion)| {
+                hypotheses.is_empty() && same_up_to_renaming(expected, assertion, &mut HashMap::new())
+            })
+            .map(|(reference, _, _)| *reference);
+        for implication in connectives(2) {
+            let im = |a: &WellFormedFormula, b: &WellFormedFormula| composite(implication, vec![a.clone(), b.clone()]);
+            let modus_ponens = statements.iter().find_map(|(reference, hypotheses, assertion)| {
+                let [first, second] = &hypotheses[..] else { return None; };
+                let mut renaming = HashMap::new();
+                if same_up_to_renaming(&ph, first, &mut renaming)
+                    && same_up_to_renaming(&im(&ph, &ps), second, &mut renaming)
+                    && same_up_to_renaming(&ps, assertion, &mut renaming) {
+                    return Some((*reference, true));
+                };
+                let mut renaming = HashMap::new();
+                if same_up_to_renaming(&im(&ph, &ps), first, &mut renaming)
+                    && same_up_to_renaming(&ph, second, &mut renaming)
+                    && same_up_to_renaming(&ps, assertion, &mut renaming) {
+                    return Some((*reference, false));
+                };
+                None
+            });
+            let Some((modus_ponens, minor_first)) = modus_ponens else { continue; };
+            let Some(a1) = find_axiom(&im(&ph, &im(&ps, &ph))) else { continue; };
+            let Some(a2) = find_axiom(
+                &im(&im(&ph, &im(&ps, &ch)), &im(&im(&ph, &ps), &im(&ph, &ch)))
+            ) else { continue; };
+            for negation in connectives(1) {
+                let not = |a: &WellFormedFormula| composite(negation, vec![a.clone()]);
+                let Some(a3) = find_axiom(&im(&im(&not(&ph), &not(&ps)), &im(&ps, &ph))) else { continue; };
+                return Some(PropositionalSystem {
+                    implication,
+                    negation,
+                    modus_ponens,
+                    minor_first,
+                    axioms: [a1, a2, a3]
+                });
+            };
+        };
+        None
+    }
+
+    fn connective<'a>(&self, wff: &'a WellFormedFormula) -> Connective<'a> {
+        let WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } = wff else {
+            return Connective::Atom;
+        };
+        match (&wff_parameters[..], object_parameters.is_empty()) {
+            ([a, b], true) if *syntax_ref == self.implication => Connective::Implication(a, b),
+            ([a], true) if *syntax_ref == self.negation => Connective::Negation(a),
+            _ => Connective::Atom
+        }
+    }
+
+    /// Every formula that is seen as a propositional variable, in order of appearance
+    fn atoms(&self, wff: &WellFormedFormula, res: &mut Vec<WellFormedFormula>) {
+        match self.connective(wff) {
+            Connective::Implication(a, b) => {
+                self.atoms(a, res);
+                self.atoms(b, res);
+            },
+            Connective::Negation(a) => self.atoms(a, res),
+            Connective::Atom => if !res.contains(wff) {
+                res.push(wff.clone());
+            }
+        };
+    }
+
+    /// The truth value of a formula, where the n-th bit of 'assignment' is the value of the n-th atom
+    fn value(&self, wff: &WellFormedFormula, atoms: &[WellFormedFormula], assignment: u64) -> bool {
+        match self.connective(wff) {
+            Connective::Implication(a, b) => !self.value(a, atoms, assignment) || self.value(b, atoms, assignment),
+            Connective::Negation(a) => !self.value(a, atoms, assignment),
+            Connective::Atom => {
+                let index = atoms.iter().position(|atom| atom == wff).unwrap_or(0);
+                assignment & (1 << index) != 0
+            }
+        }
+    }
+
+    /// Checks with its truth table that the formula 'premise 1 ⇒ (premise 2 ⇒ ... conclusion)' is a tautology,
+    /// then proves the conclusion from the axioms and the premises (given by hypothesis references)
+    pub fn prove(&self, premises: &[WellFormedFormula], conclusion: &WellFormedFormula) -> Result<Vec<ProofStep>, TautologyError> {
+        let formula = premises.iter()
+            .rev()
+            .fold(conclusion.clone(), |res, premise| composite(self.implication, vec![premise.clone(), res]));
+        let mut atoms = Vec::new();
+        self.atoms(&formula, &mut atoms);
+        if atoms.len() > MAX_ATOMS {
+            return Err(TautologyError::TooManyAtoms(atoms.len()));
+        };
+        for assignment in 0..1u64 << atoms.len() {
+            if !self.value(&formula, &atoms, assignment) {
+                let values = atoms.iter()
+                    .enumerate()
+                    .map(|(index, atom)| (atom.clone(), assignment & (1 << index) != 0))
+                    .collect();
+                return Err(TautologyError::FalseWhen(values));
+            };
+        };
+        let mut derivation = Derivation::new(self);
+        let premises = premises.iter()
+            .map(|premise| derivation.assume(premise.clone()))
+            .collect::<Vec<_>>();
+        let mut target = derivation.eliminate(&formula, &atoms, 0, 0);
+        for premise in premises {
+            target = derivation.modus_ponens(premise, target);
+        };
+        Ok(derivation.into_proof(target))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Justification {
+    Hypothesis,
+    Axiom(usize),
+    ModusPonens(usize, usize)  // The steps proving 𝜑 and 𝜑 ⇒ 𝜓
+}
+
+struct Step {
+    formula: WellFormedFormula,
+    justification: Justification,
+    hypotheses: u64  // The hypotheses the step depends on, as a set of bits
+}
+
+/// A proof that can use hypotheses, and get rid of them with the deduction theorem
+struct Derivation<'a> {
+    system: &'a PropositionalSystem,
+    steps: Vec<Step>,
+    proven: HashMap<WellFormedFormula, Vec<usize>>,
+    hypotheses: Vec<WellFormedFormula>,
+    discharged: HashMap<(usize, usize), usize>
+}
+
+impl<'a> Derivation<'a> {
+    fn new(system: &'a PropositionalSystem) -> Derivation<'a> {
+        Derivation {
+            system,
+            steps: Vec::new(),
+            proven: HashMap::new(),
+            hypotheses: Vec::new(),
+            discharged: HashMap::new()
+        }
+    }
+
+    fn im(&self, a: &WellFormedFormula, b: &WellFormedFormula) -> WellFormedFormula {
+        composite(self.system.implication, vec![a.clone(), b.clone()])
+    }
+
+    fn not(&self, a: &WellFormedFormula) -> WellFormedFormula {
+        composite(self.system.negation, vec![a.clone()])
+    }
+
+    /// Steps are reused when their formula is already proven with fewer hypotheses
+    fn add(&mut self, formula: WellFormedFormula, justification: Justification, hypotheses: u64) -> usize {
+        if let Some(ids) = self.proven.get(&formula) {
+            if let Some(&id) = ids.iter().find(|id| self.steps[**id].hypotheses & !hypotheses == 0) {
+                return id;
+            };
+        };
+        let id = self.steps.len();
+        self.proven.entry(formula.clone()).or_default().push(id);
+        self.steps.push(Step { formula, justification, hypotheses });
+        id
+    }
+
+    fn assume(&mut self, formula: WellFormedFormula) -> usize {
+        let bit = match self.hypotheses.iter().position(|hyp| *hyp == formula) {
+            Some(bit) => bit,
+            None => {
+                self.hypotheses.push(formula.clone());
+                self.hypotheses.len() - 1
+            }
+        };
+        let hypotheses = 1 << bit;
+        if let Some(&id) = self.proven.get(&formula).and_then(|ids| {
+            ids.iter().find(|id| self.steps[**id].hypotheses == hypotheses)
+        }) {
+            return id;
+        };
+        let id = self.steps.len();
+        self.proven.entry(formula.clone()).or_default().push(id);
+        self.steps.push(Step { formula, justification: Justification::Hypothesis, hypotheses });
+        id
+    }
+
+    fn modus_ponens(&mut self, minor: usize, major: usize) -> usize {
+        let WellFormedFormula::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters: _ } = &self.steps[major].formula else {
+            return major;
+        };
+        let consequent = wff_parameters[1].clone();
+        let hypotheses = self.steps[minor].hypotheses | self.steps[major].hypotheses;
+        self.add(consequent, Justification::ModusPonens(minor, major), hypotheses)
+    }
+
+    /// ⊢ A ⇒ (B ⇒ A)
+    fn a1(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        let formula = self.im(a, &self.im(b, a));
+        self.add(formula, Justification::Axiom(0), 0)
+    }
+
+    /// ⊢ (A ⇒ (B ⇒ C)) ⇒ ((A ⇒ B) ⇒ (A ⇒ C))
+    fn a2(&mut self, a: &WellFormedFormula, b: &WellFormedFormula, c: &WellFormedFormula) -> usize {
+        let formula = self.im(
+            &self.im(a, &self.im(b, c)),
+            &self.im(&self.im(a, b), &self.im(a, c))
+        );
+        self.add(formula, Justification::Axiom(1), 0)
+    }
+
+    /// ⊢ (¬A ⇒ ¬B) ⇒ (B ⇒ A)
+    fn a3(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        let formula = self.im(&self.im(&self.not(a), &self.not(b)), &self.im(b, a));
+        self.add(formula, Justification::Axiom(2), 0)
+    }
+
+    /// ⊢ A ⇒ A
+    fn identity(&mut self, a: &WellFormedFormula) -> usize {
+        let aa = self.im(a, a);
+        let s1 = self.a1(a, &aa);
+        let s2 = self.a2(a, &aa, a);
+        let s3 = self.modus_ponens(s1, s2);
+        let s4 = self.a1(a, a);
+        self.modus_ponens(s4, s3)
+    }
+
+    /// Deduction theorem: turns a step depending on a hypothesis H into a proof of 'H ⇒ step'
+    fn discharge(&mut self, hypothesis: usize, step: usize) -> usize {
+        let bit = self.steps[hypothesis].hypotheses.trailing_zeros() as usize;
+        if let Some(&id) = self.discharged.get(&(bit, step)) {
+            return id;
+        };
+        let h = self.steps[hypothesis].formula.clone();
+        let result = self.steps[step].formula.clone();
+        let id = match self.steps[step].justification {
+            _ if self.steps[step].hypotheses & (1 << bit) == 0 => {
+                let s1 = self.a1(&result, &h);
+                self.modus_ponens(step, s1)
+            },
+            Justification::ModusPonens(minor, major) => {
+                let premise = self.steps[minor].formula.clone();
+                let s1 = self.discharge(hypothesis, minor);
+                let s2 = self.discharge(hypothesis, major);
+                let s3 = self.a2(&h, &premise, &result);
+                let s4 = self.modus_ponens(s2, s3);
+                self.modus_ponens(s1, s4)
+            },
+            _ => self.identity(&h)
+        };
+        self.discharged.insert((bit, step), id);
+        id
+    }
+
+    /// ⊢ X ⇒ Z, from steps proving X ⇒ Y and Y ⇒ Z
+    fn chain(&mut self, xy: usize, yz: usize) -> usize {
+        let WellFormedFormula::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters: _ } = &self.steps[xy].formula else {
+            return xy;
+        };
+        let x = wff_parameters[0].clone();
+        let h = self.assume(x);
+        let y = self.modus_ponens(h, xy);
+        let z = self.modus_ponens(y, yz);
+        self.discharge(h, z)
+    }
+
+    /// Copies a step of another derivation that depends on no hypothesis, with the steps it uses
+    fn import(&mut self, other: &Derivation, step: usize, imported: &mut HashMap<usize, usize>) -> usize {
+        if let Some(&id) = imported.get(&step) {
+            return id;
+        };
+        let id = match other.steps[step].justification {
+            Justification::ModusPonens(minor, major) => {
+                let minor = self.import(other, minor, imported);
+                let major = self.import(other, major, imported);
+                self.modus_ponens(minor, major)
+            },
+            justification => self.add(other.steps[step].formula.clone(), justification, 0)
+        };
+        imported.insert(step, id);
+        id
+    }
+
+    /// Proves a lemma apart, so that its hypotheses do not mix with the ones of this derivation
+    fn lemma(&mut self, build: impl FnOnce(&mut Derivation<'a>) -> usize) -> usize {
+        let mut scratch = Derivation::new(self.system);
+        let step = build(&mut scratch);
+        self.import(&scratch, step, &mut HashMap::new())
+    }
+
+    /// ⊢ ¬¬A ⇒ A
+    fn double_negation_elimination(&mut self, a: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let na = d.not(a);
+            let nna = d.not(&na);
+            let nnna = d.not(&nna);
+            let nnnna = d.not(&nnna);
+            let h = d.assume(nna.clone());
+            let s1 = d.a1(&nna, &nnnna);
+            let s2 = d.modus_ponens(h, s1);
+            let s3 = d.a3(&nnna, &na);
+            let s4 = d.modus_ponens(s2, s3);
+            let s5 = d.a3(a, &nna);
+            let s6 = d.modus_ponens(s4, s5);
+            let s7 = d.modus_ponens(h, s6);
+            d.discharge(h, s7)
+        })
+    }
+
+    /// ⊢ A ⇒ ¬¬A
+    fn double_negation_introduction(&mut self, a: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let na = d.not(a);
+            let nna = d.not(&na);
+            let s1 = d.double_negation_elimination(&na);
+            let s2 = d.a3(&nna, a);
+            d.modus_ponens(s1, s2)
+        })
+    }
+
+    /// ⊢ ¬A ⇒ (A ⇒ B)
+    fn explosion(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let na = d.not(a);
+            let nb = d.not(b);
+            let h = d.assume(na.clone());
+            let s1 = d.a1(&na, &nb);
+            let s2 = d.modus_ponens(h, s1);
+            let s3 = d.a3(b, a);
+            let s4 = d.modus_ponens(s2, s3);
+            d.discharge(h, s4)
+        })
+    }
+
+    /// ⊢ (A ⇒ B) ⇒ (¬B ⇒ ¬A)
+    fn contraposition(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let h = d.assume(d.im(a, b));
+            let s1 = d.double_negation_elimination(a);
+            let s2 = d.chain(s1, h);
+            let s3 = d.double_negation_introduction(b);
+            let s4 = d.chain(s2, s3);
+            let s5 = d.a3(&d.not(a), &d.not(b));
+            let s6 = d.modus_ponens(s4, s5);
+            d.discharge(h, s6)
+        })
+    }
+
+    /// ⊢ A ⇒ (¬B ⇒ ¬(A ⇒ B))
+    fn negated_implication(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let ab = d.im(a, b);
+            let h1 = d.assume(a.clone());
+            let h2 = d.assume(ab.clone());
+            let s1 = d.modus_ponens(h1, h2);
+            let s2 = d.discharge(h2, s1);
+            let s3 = d.contraposition(&ab, b);
+            let s4 = d.modus_ponens(s2, s3);
+            d.discharge(h1, s4)
+        })
+    }
+
+    /// ⊢ (A ⇒ B) ⇒ ((¬A ⇒ B) ⇒ B)
+    fn case_analysis(&mut self, a: &WellFormedFormula, b: &WellFormedFormula) -> usize {
+        self.lemma(|d| {
+            let na = d.not(a);
+            let nb = d.not(b);
+            let bb = d.im(b, b);
+            let h1 = d.assume(d.im(a, b));
+            let h2 = d.assume(d.im(&na, b));
+            let h3 = d.assume(nb);
+            let s1 = d.contraposition(a, b);
+            let s2 = d.modus_ponens(h1, s1);
+            let s3 = d.modus_ponens(h3, s2);
+            let s4 = d.contraposition(&na, b);
+            let s5 = d.modus_ponens(h2, s4);
+            let s6 = d.modus_ponens(h3, s5);
+            let s7 = d.explosion(&na, &d.not(&bb));
+            let s8 = d.modus_ponens(s6, s7);
+            let s9 = d.modus_ponens(s3, s8);
+            let s10 = d.discharge(h3, s9);
+            let s11 = d.a3(b, &bb);
+            let s12 = d.modus_ponens(s10, s11);
+            let s13 = d.identity(b);
+            let s14 = d.modus_ponens(s13, s12);
+            let s15 = d.discharge(h2, s14);
+            d.discharge(h1, s15)
+        })
+    }
+
+    /// Kalmár's lemma: proves the formula if it is true (or its negation otherwise),
+    /// from the atoms that are true and the negations of those that are false
+    fn kalmar(&mut self, wff: &WellFormedFormula, atoms: &[WellFormedFormula], assignment: u64) -> usize {
+        let system = self.system;
+        match system.connective(wff) {
+            Connective::Atom => match system.value(wff, atoms, assignment) {
+                true => self.assume(wff.clone()),
+                false => self.assume(self.not(wff))
+            },
+            Connective::Negation(a) => {
+                let s1 = self.kalmar(a, atoms, assignment);
+                if !system.value(a, atoms, assignment) { return s1; };
+                let s2 = self.double_negation_introduction(a);
+                self.modus_ponens(s1, s2)
+            },
+            Connective::Implication(a, b) => {
+                if system.value(b, atoms, assignment) {
+                    let s1 = self.kalmar(b, atoms, assignment);
+                    let s2 = self.a1(b, a);
+                    return self.modus_ponens(s1, s2);
+                };
+                if !system.value(a, atoms, assignment) {
+                    let s1 = self.kalmar(a, atoms, assignment);
+                    let s2 = self.explosion(a, b);
+                    return self.modus_ponens(s1, s2);
+                };
+                let s1 = self.kalmar(a, atoms, assignment);
+                let s2 = self.kalmar(b, atoms, assignment);
+                let s3 = self.negated_implication(a, b);
+                let s4 = self.modus_ponens(s1, s3);
+                self.modus_ponens(s2, s4)
+            }
+        }
+    }
+
+    /// Proves a tautology from the values of its first 'fixed' atoms, by cases on the value of the others
+    fn eliminate(&mut self, wff: &WellFormedFormula, atoms: &[WellFormedFormula], fixed: usize, assignment: u64) -> usize {
+        let Some(atom) = atoms.get(fixed) else {
+            return self.kalmar(wff, atoms, assignment);
+        };
+        let when_true = self.eliminate(wff, atoms, fixed+1, assignment | 1 << fixed);
+        let h1 = self.assume(atom.clone());
+        // The value of the atom may not matter once the others are fixed
+        if self.steps[when_true].hypotheses & self.steps[h1].hypotheses == 0 {
+            return when_true;
+        };
+        let s1 = self.discharge(h1, when_true);
+        let when_false = self.eliminate(wff, atoms, fixed+1, assignment);
+        let h2 = self.assume(self.not(atom));
+        let s2 = self.discharge(h2, when_false);
+        let s3 = self.case_analysis(atom, wff);
+        let s4 = self.modus_ponens(s1, s3);
+        self.modus_ponens(s2, s4)
+    }
+
+    /// The steps needed by the target, in the order they were added
+    fn into_proof(self, target: usize) -> Vec<ProofStep> {
+        let mut needed = vec![false; self.steps.len()];
+        needed[target] = true;
+        for id in (0..=target).rev() {
+            if !needed[id] { continue; };
+            if let Justification::ModusPonens(minor, major) = self.steps[id].justification {
+                needed[minor] = true;
+                needed[major] = true;
+            };
+        };
+        let mut new_ids = vec![0; self.steps.len()];
+        let mut proof = Vec::new();
+        for (id, step) in self.steps.into_iter().enumerate() {
+            if !needed[id] { continue; };
+            new_ids[id] = proof.len();
+            let (used_steps, reference) = match step.justification {
+                Justification::ModusPonens(minor, major) => match self.system.minor_first {
+                    true => (vec![new_ids[minor], new_ids[major]], self.system.modus_ponens),
+                    false => (vec![new_ids[major], new_ids[minor]], self.system.modus_ponens)
+                },
+                Justification::Axiom(axiom) => (Vec::new(), self.system.axioms[axiom]),
+                Justification::Hypothesis => {
+                    let premise = step.hypotheses.trailing_zeros() as usize;
+                    (Vec::new(), Reference::HypothesisReference(premise))
+                }
+            };
+            proof.push(ProofStep { used_steps, reference, formula: step.formula });
+        };
+        proof
+    }
+}
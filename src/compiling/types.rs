@@ -1,4 +1,4 @@
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Placeholder {
     LiteralChar(char),
     WellFormedFormula(usize),
@@ -6,41 +6,100 @@ pub enum Placeholder {
     Repetition
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SyntaxType {
     Formula,
     Object
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssociative
+}
+
+/// The higher the level, the tighter the syntax binds its parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precedence {
+    pub level: usize,
+    pub associativity: Associativity
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Syntax {
     pub syntax_type: SyntaxType,
     pub formula: Vec<Placeholder>,
+    pub precedence: Option<Precedence>,
     pub distinct_wff_count: usize,
-    pub distinct_object_count: usize
+    pub distinct_object_count: usize,
+    pub latex: Option<Vec<Placeholder>>  // How the syntax is written in LaTeX, with the placeholders of its formula
+}
+
+/// If the formula is variadic, returns the indices of the placeholder that gets repeated
+/// and of the repetition character
+pub fn repetition_indices(formula: &[Placeholder]) -> Option<(usize, usize)> {
+    let rep_index = formula.iter()
+        .position(|pl| *pl == Placeholder::Repetition)?;
+    let repeated_index = formula[..rep_index].iter()
+        .rposition(|pl| !matches!(pl, Placeholder::LiteralChar(_)))?;
+    Some((repeated_index, rep_index))
+}
+
+impl Syntax {
+    /// If the syntax is variadic, returns the indices in its formula
+    /// of the placeholder that gets repeated and of the repetition character
+    pub fn repetition(&self) -> Option<(usize, usize)> {
+        repetition_indices(&self.formula)
+    }
+}
+
+#[cfg(test)]
+impl Syntax {
+    /// A syntax without a LaTeX template, built the same way in the tests of the editor's copy of the grammar
+    pub fn new(
+        syntax_type: SyntaxType, formula: Vec<Placeholder>, precedence: Option<Precedence>,
+        distinct_wff_count: usize, distinct_object_count: usize
+    ) -> Syntax {
+        Syntax { syntax_type, formula, precedence, distinct_wff_count, distinct_object_count, latex: None }
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum WellFormedFormula {
     Atomic(usize),
     SyntaxComposite {
         syntax_ref: usize,
         wff_parameters: Vec<WellFormedFormula>,
         object_parameters: Vec<Object>
-    }
+    },
+    // Stands for any number of formulas in the parameters of a variadic syntax
+    Repetition,
+    // A formula of an incomplete proof that is not known yet
+    WorkVariable(usize)
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Object {
     Atomic(usize),
     SyntaxComposite {
         syntax_ref: usize,
         wff_parameters: Vec<WellFormedFormula>,
         object_parameters: Vec<Object>
-    }
+    },
+    // Stands for any number of objects in the parameters of a variadic syntax
+    Repetition,
+    // An object of an incomplete proof that is not known yet
+    WorkVariable(usize)
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Variable {
+    Wff(usize),
+    Object(usize)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Definition {
     pub name: String,
     pub definition: WellFormedFormula,
@@ -48,52 +107,63 @@ pub struct Definition {
     pub distinct_object_count: usize
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Axiom {
     pub name: String,
     pub hypotheses: Vec<WellFormedFormula>,
     pub assertions: Vec<WellFormedFormula>,
+    pub distinct_variables: Vec<(Variable, Variable)>,
     pub distinct_wff_count: usize,
     pub distinct_object_count: usize
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Reference {
     HypothesisReference(usize),
     DefinitionReference(usize),
     AxiomReference(usize, usize),
-    TheoremReference(usize, usize)
+    TheoremReference(usize, usize),
+    UnprovenReference  // A step of an incomplete proof, left to be proven
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct LogicStep {
     pub used_hypotheses: Vec<usize>,
     pub theorem_ref: Reference,
     pub resulting_formula: WellFormedFormula
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Theorem {
     pub name: String,
     pub hypotheses: Vec<WellFormedFormula>,
     pub assertions: Vec<WellFormedFormula>,
+    pub distinct_variables: Vec<(Variable, Variable)>,
     pub proof: Vec<LogicStep>,
     pub distinct_wff_count: usize,
     pub distinct_object_count: usize
 }
 
-use crate::parsing::FormulaChar;
-#[derive(Debug)]
-pub enum PartiallyCompiled {
-    NotCompiled(FormulaChar),
-    CompiledFormula(WellFormedFormula),
-    CompiledObject(Object)
-}
-
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Library {
     pub syntaxes: Vec<Syntax>,
     pub definitions: Vec<Definition>,
     pub axioms: Vec<Axiom>,
     pub theorems: Vec<Theorem>
 }
+
+impl Library {
+    /// The name proof lines use to refer to a definition, an axiom or a theorem
+    pub fn reference_name(&self, reference: &Reference) -> Option<String> {
+        let (name, assert_id) = match reference {
+            Reference::DefinitionReference(def_id) => (&self.definitions.get(*def_id)?.name, 0),
+            Reference::AxiomReference(ax_id, assert_id) => (&self.axioms.get(*ax_id)?.name, *assert_id),
+            Reference::TheoremReference(theo_id, assert_id) => (&self.theorems.get(*theo_id)?.name, *assert_id),
+            Reference::HypothesisReference(_) | Reference::UnprovenReference => return None
+        };
+        match assert_id {
+            0 => Some(name.clone()),
+            _ => Some(format!("{name}.{assert_id}"))
+        }
+    }
+}
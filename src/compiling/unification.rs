@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use crate::parsing::FormulaChar;
+use super::{WellFormedFormula, Object, Variable, Statement};
+
+/// The formulas and objects found so far for the work variables of a proof
+#[derive(Clone)]
+pub(super) struct WorkVariables {
+    wffs: HashMap<usize, WellFormedFormula>,
+    objects: HashMap<usize, Object>,
+    next_id: usize  // Work variables created by the compiler are numbered after those of the proof
+}
+
+pub(super) fn wff_has_work_variables(wff: &WellFormedFormula) -> bool {
+    let mut variables = Vec::new();
+    wff_work_variables(wff, &mut variables);
+    !variables.is_empty()
+}
+
+impl WorkVariables {
+    /// The proof is scanned for the work variables it already uses
+    pub fn new(proof: &[Option<&Vec<FormulaChar>>]) -> Self {
+        let greatest_id = proof.iter()
+            .flatten()
+            .flat_map(|formula| formula.iter())
+            .filter_map(|c| match c {
+                FormulaChar::WorkWff(id) | FormulaChar::WorkObject(id) => Some(*id),
+                _ => None
+            })
+            .max()
+            .unwrap_or(0);
+        WorkVariables { wffs: HashMap::new(), objects: HashMap::new(), next_id: greatest_id + 1 }
+    }
+
+    pub fn new_wff(&mut self) -> WellFormedFormula {
+        self.next_id += 1;
+        WellFormedFormula::WorkVariable(self.next_id - 1)
+    }
+
+    /// Replaces the work variables with what was found for them, as far as it is known
+    pub fn resolve_wff(&self, wff: &WellFormedFormula) -> WellFormedFormula {
+        match wff {
+            WellFormedFormula::WorkVariable(id) => match self.wffs.get(id) {
+                Some(found) => self.resolve_wff(found),
+                None => wff.clone()
+            },
+            WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                WellFormedFormula::SyntaxComposite {
+                    syntax_ref: *syntax_ref,
+                    wff_parameters: wff_parameters.iter().map(|wff| self.resolve_wff(wff)).collect(),
+                    object_parameters: object_parameters.iter().map(|obj| self.resolve_object(obj)).collect()
+                }
+            },
+            WellFormedFormula::Atomic(_) | WellFormedFormula::Repetition => wff.clone()
+        }
+    }
+
+    fn resolve_object(&self, obj: &Object) -> Object {
+        match obj {
+            Object::WorkVariable(id) => match self.objects.get(id) {
+                Some(found) => self.resolve_object(found),
+                None => obj.clone()
+            },
+            Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                Object::SyntaxComposite {
+                    syntax_ref: *syntax_ref,
+                    wff_parameters: wff_parameters.iter().map(|wff| self.resolve_wff(wff)).collect(),
+                    object_parameters: object_parameters.iter().map(|obj| self.resolve_object(obj)).collect()
+                }
+            },
+            Object::Atomic(_) | Object::Repetition => obj.clone()
+        }
+    }
+
+    /// The hypotheses and the assertion of a statement, with a new work variable for each of its variables.
+    /// Statements whose formulas contain a '…' cannot be used that way
+    pub fn instantiate(&mut self, statement: &Statement) -> Option<(Vec<WellFormedFormula>, WellFormedFormula)> {
+        let first_id = self.next_id;
+        self.next_id += statement.wff_count + statement.object_count;
+        let offsets = (first_id, first_id + statement.wff_count);
+        let hypotheses = statement.hypotheses.iter()
+            .map(|hyp| instantiate_wff(hyp, offsets))
+            .collect::<Option<Vec<_>>>()?;
+        Some((hypotheses, instantiate_wff(&statement.assertion, offsets)?))
+    }
+
+    /// Gives the given formula and object to the work variables of the formulas that are still unknown,
+    /// and fails if one of them cannot be given any
+    pub fn complete(
+        &mut self, formulas: &[&WellFormedFormula], wff: Option<&WellFormedFormula>, obj: Option<&Object>
+    ) -> bool {
+        let mut variables = Vec::new();
+        formulas.iter().for_each(|formula| wff_work_variables(&self.resolve_wff(formula), &mut variables));
+        for variable in variables {
+            match (variable, wff, obj) {
+                (Variable::Wff(id), Some(wff), _) => { self.wffs.insert(id, wff.clone()); },
+                (Variable::Object(id), _, Some(obj)) => { self.objects.insert(id, obj.clone()); },
+                _ => return false
+            };
+        };
+        true
+    }
+
+    /// Makes the two formulas equal by finding the work variables they contain,
+    /// and leaves the work variables as they were if it cannot be done
+    pub fn unify(&mut self, pairs: &[(WellFormedFormula, WellFormedFormula)]) -> bool {
+        let saved = self.clone();
+        let unified = pairs.iter().all(|(wff1, wff2)| self.unify_wffs(wff1, wff2));
+        if !unified { *self = saved; };
+        unified
+    }
+
+    fn unify_wffs(&mut self, wff1: &WellFormedFormula, wff2: &WellFormedFormula) -> bool {
+        let wff1 = self.resolve_wff(wff1);
+        let wff2 = self.resolve_wff(wff2);
+        match (&wff1, &wff2) {
+            (WellFormedFormula::WorkVariable(id1), WellFormedFormula::WorkVariable(id2)) if id1 == id2 => true,
+            (WellFormedFormula::WorkVariable(id), wff) | (wff, WellFormedFormula::WorkVariable(id)) => {
+                // A work variable cannot stand for a formula that contains it
+                let mut variables = Vec::new();
+                wff_work_variables(wff, &mut variables);
+                if variables.contains(&Variable::Wff(*id)) { return false; };
+                self.wffs.insert(*id, wff.clone());
+                true
+            },
+            (
+                WellFormedFormula::SyntaxComposite { syntax_ref: ref1, wff_parameters: wffs1, object_parameters: objs1 },
+                WellFormedFormula::SyntaxComposite { syntax_ref: ref2, wff_parameters: wffs2, object_parameters: objs2 }
+            ) => {
+                ref1 == ref2 && wffs1.len() == wffs2.len() && objs1.len() == objs2.len()
+                && wffs1.iter().zip(wffs2).all(|(w1, w2)| self.unify_wffs(w1, w2))
+                && objs1.iter().zip(objs2).all(|(o1, o2)| self.unify_objects(o1, o2))
+            },
+            (wff1, wff2) => wff1 == wff2
+        }
+    }
+
+    fn unify_objects(&mut self, obj1: &Object, obj2: &Object) -> bool {
+        let obj1 = self.resolve_object(obj1);
+        let obj2 = self.resolve_object(obj2);
+        match (&obj1, &obj2) {
+            (Object::WorkVariable(id1), Object::WorkVariable(id2)) if id1 == id2 => true,
+            (Object::WorkVariable(id), obj) | (obj, Object::WorkVariable(id)) => {
+                let mut variables = Vec::new();
+                object_work_variables(obj, &mut variables);
+                if variables.contains(&Variable::Object(*id)) { return false; };
+                self.objects.insert(*id, obj.clone());
+                true
+            },
+            (
+                Object::SyntaxComposite { syntax_ref: ref1, wff_parameters: wffs1, object_parameters: objs1 },
+                Object::SyntaxComposite { syntax_ref: ref2, wff_parameters: wffs2, object_parameters: objs2 }
+            ) => {
+                ref1 == ref2 && wffs1.len() == wffs2.len() && objs1.len() == objs2.len()
+                && wffs1.iter().zip(wffs2).all(|(w1, w2)| self.unify_wffs(w1, w2))
+                && objs1.iter().zip(objs2).all(|(o1, o2)| self.unify_objects(o1, o2))
+            },
+            (obj1, obj2) => obj1 == obj2
+        }
+    }
+}
+
+fn wff_work_variables(wff: &WellFormedFormula, variables: &mut Vec<Variable>) {
+    match wff {
+        WellFormedFormula::WorkVariable(id) => variables.push(Variable::Wff(*id)),
+        WellFormedFormula::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } => {
+            wff_parameters.iter().for_each(|wff| wff_work_variables(wff, variables));
+            object_parameters.iter().for_each(|obj| object_work_variables(obj, variables));
+        },
+        WellFormedFormula::Atomic(_) | WellFormedFormula::Repetition => ()
+    };
+}
+
+fn object_work_variables(obj: &Object, variables: &mut Vec<Variable>) {
+    match obj {
+        Object::WorkVariable(id) => variables.push(Variable::Object(*id)),
+        Object::SyntaxComposite { syntax_ref: _, wff_parameters, object_parameters } => {
+            wff_parameters.iter().for_each(|wff| wff_work_variables(wff, variables));
+            object_parameters.iter().for_each(|obj| object_work_variables(obj, variables));
+        },
+        Object::Atomic(_) | Object::Repetition => ()
+    };
+}
+
+/// The offsets are the numbers of the work variables given to the first wff and the first object variable
+fn instantiate_wff(wff: &WellFormedFormula, offsets: (usize, usize)) -> Option<WellFormedFormula> {
+    match wff {
+        WellFormedFormula::Atomic(id) => Some(WellFormedFormula::WorkVariable(offsets.0 + id)),
+        WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+            Some(WellFormedFormula::SyntaxComposite {
+                syntax_ref: *syntax_ref,
+                wff_parameters: wff_parameters.iter()
+                    .map(|wff| instantiate_wff(wff, offsets))
+                    .collect::<Option<_>>()?,
+                object_parameters: object_parameters.iter()
+                    .map(|obj| instantiate_object(obj, offsets))
+                    .collect::<Option<_>>()?
+            })
+        },
+        WellFormedFormula::Repetition | WellFormedFormula::WorkVariable(_) => None
+    }
+}
+
+fn instantiate_object(obj: &Object, offsets: (usize, usize)) -> Option<Object> {
+    match obj {
+        Object::Atomic(id) => Some(Object::WorkVariable(offsets.1 + id)),
+        Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+            Some(Object::SyntaxComposite {
+                syntax_ref: *syntax_ref,
+                wff_parameters: wff_parameters.iter()
+                    .map(|wff| instantiate_wff(wff, offsets))
+                    .collect::<Option<_>>()?,
+                object_parameters: object_parameters.iter()
+                    .map(|obj| instantiate_object(obj, offsets))
+                    .collect::<Option<_>>()?
+            })
+        },
+        Object::Repetition | Object::WorkVariable(_) => None
+    }
+}
@@ -1,40 +1,147 @@
-use super::{Placeholder, WellFormedFormula, Object};
+use super::{WellFormedFormula, Object, Variable, Statement};
 
 
-fn equal_placeholders(p1: &Placeholder, p2: &Placeholder) -> bool {
-    match (p1, p2) {
-        (Placeholder::LiteralChar(c1), Placeholder::LiteralChar(c2)) => c1 == c2,
-        (Placeholder::WellFormedFormula(_), Placeholder::WellFormedFormula(_)) => true,
-        (Placeholder::Object(_), Placeholder::Object(_)) => true,
-        (Placeholder::Repetition, Placeholder::Repetition) => true,
-        _ => false
+/// The expressions substituted for the variables (and for the repetition) of a theorem
+#[derive(Clone)]
+pub(super) struct Substitution<'a> {
+    pub wffs: Vec<Option<&'a WellFormedFormula>>,
+    pub objects: Vec<Option<&'a Object>>,
+    wff_repetition: Option<&'a [WellFormedFormula]>,
+    object_repetition: Option<&'a [Object]>
+}
+
+impl<'a> Substitution<'a> {
+    pub fn new(wff_count: usize, object_count: usize) -> Substitution<'a> {
+        Substitution {
+            wffs: vec![None; wff_count],
+            objects: vec![None; object_count],
+            wff_repetition: None,
+            object_repetition: None
+        }
+    }
+
+    /// Applies the substitution to a formula of the theorem, if all of its variables are substituted
+    pub fn apply_to_wff(&self, wff: &WellFormedFormula) -> Option<WellFormedFormula> {
+        match wff {
+            WellFormedFormula::Atomic(id) => self.wffs.get(*id).copied().flatten().cloned(),
+            WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                Some(WellFormedFormula::SyntaxComposite {
+                    syntax_ref: *syntax_ref,
+                    wff_parameters: self.apply_to_wff_list(wff_parameters)?,
+                    object_parameters: self.apply_to_object_list(object_parameters)?
+                })
+            },
+            WellFormedFormula::Repetition => None,
+            WellFormedFormula::WorkVariable(_) => Some(wff.clone())
+        }
+    }
+
+    fn apply_to_object(&self, obj: &Object) -> Option<Object> {
+        match obj {
+            Object::Atomic(id) => self.objects.get(*id).copied().flatten().cloned(),
+            Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                Some(Object::SyntaxComposite {
+                    syntax_ref: *syntax_ref,
+                    wff_parameters: self.apply_to_wff_list(wff_parameters)?,
+                    object_parameters: self.apply_to_object_list(object_parameters)?
+                })
+            },
+            Object::Repetition => None,
+            Object::WorkVariable(_) => Some(obj.clone())
+        }
+    }
+
+    fn apply_to_wff_list(&self, wffs: &[WellFormedFormula]) -> Option<Vec<WellFormedFormula>> {
+        let mut res = Vec::new();
+        for wff in wffs {
+            match wff {
+                WellFormedFormula::Repetition => res.extend_from_slice(self.wff_repetition?),
+                wff => res.push(self.apply_to_wff(wff)?)
+            };
+        };
+        Some(res)
+    }
+
+    fn apply_to_object_list(&self, objects: &[Object]) -> Option<Vec<Object>> {
+        let mut res = Vec::new();
+        for obj in objects {
+            match obj {
+                Object::Repetition => res.extend_from_slice(self.object_repetition?),
+                obj => res.push(self.apply_to_object(obj)?)
+            };
+        };
+        Some(res)
     }
 }
 
-pub fn formula_is_contained(formula1: &Vec<Placeholder>, formula2: &Vec<Placeholder>) -> bool {
-    let max_len_to_check = match (formula1.len(), formula2.len()) {
-        (len1, _) if len1 == 0 => return true,
-        (len1, len2) if len1 > len2 => return false,
-        (len1, len2) => len2 - len1 + 1
-    };
-    for index in 0..max_len_to_check {
-        if formula2[index..].iter()
-            .zip(formula1)
-            .all(|(p1, p2)| equal_placeholders(p1, p2)) {
-            return true;
+fn find_wff_list_substitutions<'a>(
+    base: &[WellFormedFormula], sub: &'a [WellFormedFormula], mapping: &mut Substitution<'a>
+) -> Result<(), ()> {
+    let Some(rep_index) = base.iter().position(|wff| *wff == WellFormedFormula::Repetition) else {
+        if base.len() != sub.len() { return Err(()); };
+        for (bwp, swp) in base.iter().zip(sub) {
+            find_wff_substitutions(bwp, swp, mapping)?;
+        };
+        return Ok(());
+    };
+    // The repetition stands for everything between the parameters before and after it
+    let suffix_len = base.len() - rep_index - 1;
+    if sub.len() < rep_index + suffix_len { return Err(()); };
+    let rep_end = sub.len() - suffix_len;
+    for (bwp, swp) in base[..rep_index].iter().zip(&sub[..rep_index]) {
+        find_wff_substitutions(bwp, swp, mapping)?;
+    };
+    for (bwp, swp) in base[rep_index+1..].iter().zip(&sub[rep_end..]) {
+        find_wff_substitutions(bwp, swp, mapping)?;
+    };
+    let repeated = &sub[rep_index..rep_end];
+    match mapping.wff_repetition {
+        None => mapping.wff_repetition = Some(repeated),
+        Some(wffs) => {
+            if repeated != wffs { return Err(()); }
         }
     };
-    return false;
+    Ok(())
 }
 
+fn find_object_list_substitutions<'a>(
+    base: &[Object], sub: &'a [Object], mapping: &mut Substitution<'a>
+) -> Result<(), ()> {
+    let Some(rep_index) = base.iter().position(|obj| *obj == Object::Repetition) else {
+        if base.len() != sub.len() { return Err(()); };
+        for (bop, sop) in base.iter().zip(sub) {
+            find_object_substitutions(bop, sop, mapping)?;
+        };
+        return Ok(());
+    };
+    // The repetition stands for everything between the parameters before and after it
+    let suffix_len = base.len() - rep_index - 1;
+    if sub.len() < rep_index + suffix_len { return Err(()); };
+    let rep_end = sub.len() - suffix_len;
+    for (bop, sop) in base[..rep_index].iter().zip(&sub[..rep_index]) {
+        find_object_substitutions(bop, sop, mapping)?;
+    };
+    for (bop, sop) in base[rep_index+1..].iter().zip(&sub[rep_end..]) {
+        find_object_substitutions(bop, sop, mapping)?;
+    };
+    let repeated = &sub[rep_index..rep_end];
+    match mapping.object_repetition {
+        None => mapping.object_repetition = Some(repeated),
+        Some(objects) => {
+            if repeated != objects { return Err(()); }
+        }
+    };
+    Ok(())
+}
 
-fn find_wff_substitutions<'a>(
-    base: &WellFormedFormula, sub: &'a WellFormedFormula,
-    wff_mapping: &mut Vec<Option<&'a WellFormedFormula>>, object_mapping: &mut Vec<Option<&'a Object>>
+pub(super) fn find_wff_substitutions<'a>(
+    base: &WellFormedFormula, sub: &'a WellFormedFormula, mapping: &mut Substitution<'a>
 ) -> Result<(), ()> {
     match (base, sub) {
-        (WellFormedFormula::Atomic(id), sub) => match wff_mapping[*id] {
-            None => wff_mapping[*id] = Some(sub),
+        // A repetition is not a formula by itself, so it cannot be substituted for a variable
+        (WellFormedFormula::Atomic(_), WellFormedFormula::Repetition) => return Err(()),
+        (WellFormedFormula::Atomic(id), sub) => match mapping.wffs[*id] {
+            None => mapping.wffs[*id] = Some(sub),
             Some(wff) => {
                 if sub != wff { return Err(()); }
             }
@@ -52,18 +159,8 @@ fn find_wff_substitutions<'a>(
             }
         ) => {
             if sub_syn_ref != base_syn_ref { return Err(()); };
-            for (bwp, swp) in base_wff_params.into_iter().zip(sub_wff_params) {
-                match find_wff_substitutions(bwp, swp, wff_mapping, object_mapping) {
-                    Ok(()) => (),
-                    Err(()) => return Err(())
-                }
-            };
-            for (bop, sop) in base_obj_params.into_iter().zip(sub_obj_params) {
-                match find_object_substitutions(bop, sop, wff_mapping, object_mapping) {
-                    Ok(()) => (),
-                    Err(()) => return Err(())
-                }
-            };
+            find_wff_list_substitutions(base_wff_params, sub_wff_params, mapping)?;
+            find_object_list_substitutions(base_obj_params, sub_obj_params, mapping)?;
         },
         _ => return Err(())
     };
@@ -71,12 +168,13 @@ fn find_wff_substitutions<'a>(
 }
 
 fn find_object_substitutions<'a>(
-    base: &Object, sub: &'a Object,
-    wff_mapping: &mut Vec<Option<&'a WellFormedFormula>>, object_mapping: &mut Vec<Option<&'a Object>>
+    base: &Object, sub: &'a Object, mapping: &mut Substitution<'a>
 ) -> Result<(), ()> {
     match (base, sub) {
-        (Object::Atomic(id), sub) => match object_mapping[*id] {
-            None => object_mapping[*id] = Some(sub),
+        // A repetition is not an object by itself, so it cannot be substituted for a variable
+        (Object::Atomic(_), Object::Repetition) => return Err(()),
+        (Object::Atomic(id), sub) => match mapping.objects[*id] {
+            None => mapping.objects[*id] = Some(sub),
             Some(wff) => {
                 if sub != wff { return Err(()); }
             }
@@ -94,39 +192,178 @@ fn find_object_substitutions<'a>(
             }
         ) => {
             if sub_syn_ref != base_syn_ref { return Err(()); };
-            for (bwp, swp) in base_wff_params.into_iter().zip(sub_wff_params) {
-                match find_wff_substitutions(bwp, swp, wff_mapping, object_mapping) {
-                    Ok(()) => (),
-                    Err(()) => return Err(())
-                }
-            };
-            for (bop, sop) in base_obj_params.into_iter().zip(sub_obj_params) {
-                match find_object_substitutions(bop, sop, wff_mapping, object_mapping) {
-                    Ok(()) => (),
-                    Err(()) => return Err(())
-                }
-            };
+            find_wff_list_substitutions(base_wff_params, sub_wff_params, mapping)?;
+            find_object_list_substitutions(base_obj_params, sub_obj_params, mapping)?;
         },
         _ => return Err(())
     };
     Ok(())
 }
 
-pub fn formula_is_substitution(
-    formula: &WellFormedFormula, used_hypotheses: &Vec<WellFormedFormula>,
-    theo_hypotheses: &Vec<WellFormedFormula>, theo_assertion: &WellFormedFormula,
-    wff_count: usize, object_count: usize
+pub(super) fn wff_variables(wff: &WellFormedFormula, variables: &mut Vec<Variable>) {
+    match wff {
+        WellFormedFormula::Atomic(id) => {
+            if !variables.contains(&Variable::Wff(*id)) {
+                variables.push(Variable::Wff(*id));
+            };
+        },
+        WellFormedFormula::SyntaxComposite {
+            syntax_ref: _,
+            wff_parameters,
+            object_parameters
+        } => {
+            for param_wff in wff_parameters {
+                wff_variables(param_wff, variables);
+            };
+            for param_obj in object_parameters {
+                object_variables(param_obj, variables);
+            };
+        },
+        WellFormedFormula::Repetition | WellFormedFormula::WorkVariable(_) => ()
+    }
+}
+
+fn object_variables(obj: &Object, variables: &mut Vec<Variable>) {
+    match obj {
+        Object::Atomic(id) => {
+            if !variables.contains(&Variable::Object(*id)) {
+                variables.push(Variable::Object(*id));
+            };
+        },
+        Object::SyntaxComposite {
+            syntax_ref: _,
+            wff_parameters,
+            object_parameters
+        } => {
+            for param_wff in wff_parameters {
+                wff_variables(param_wff, variables);
+            };
+            for param_obj in object_parameters {
+                object_variables(param_obj, variables);
+            };
+        },
+        Object::Repetition | Object::WorkVariable(_) => ()
+    }
+}
+
+fn substituted_variables(variable: Variable, mapping: &Substitution) -> Option<Vec<Variable>> {
+    let mut variables = Vec::new();
+    match variable {
+        Variable::Wff(id) => wff_variables(mapping.wffs.get(id).copied().flatten()?, &mut variables),
+        Variable::Object(id) => object_variables(mapping.objects.get(id).copied().flatten()?, &mut variables)
+    };
+    Some(variables)
+}
+
+/// Checks that, for every pair of variables that the referenced theorem requires to be distinct,
+/// the substituted expressions share no variable, and that every pair of variables
+/// they contain is itself declared distinct in the current theorem
+fn distinct_variables_are_respected(
+    theo_distinct: &[(Variable, Variable)], distinct: &[(Variable, Variable)], mapping: &Substitution
+) -> bool {
+    for (v1, v2) in theo_distinct {
+        // A variable that does not appear in the statement is never substituted
+        let Some(vars1) = substituted_variables(*v1, mapping) else {
+            continue;
+        };
+        let Some(vars2) = substituted_variables(*v2, mapping) else {
+            continue;
+        };
+        for x in &vars1 {
+            for y in &vars2 {
+                if x == y { return false; };
+                if !distinct.contains(&(*x.min(y), *x.max(y))) { return false; };
+            };
+        };
+    };
+    true
+}
+
+pub(super) fn formula_is_substitution(
+    formula: &WellFormedFormula, used_hypotheses: &[WellFormedFormula],
+    statement: &Statement, distinct: &[(Variable, Variable)]
 ) -> bool {
-    let mut wff_mapping = vec![None; wff_count];
-    let mut object_mapping = vec![None; object_count];
-    for (theo_hyp, used_hyp) in theo_hypotheses.into_iter().zip(used_hypotheses) {
-        match find_wff_substitutions(theo_hyp, used_hyp, &mut wff_mapping, &mut object_mapping) {
+    let mut mapping = Substitution::new(statement.wff_count, statement.object_count);
+    for (theo_hyp, used_hyp) in statement.hypotheses.iter().zip(used_hypotheses) {
+        match find_wff_substitutions(theo_hyp, used_hyp, &mut mapping) {
             Ok(()) => (),
             Err(()) => return false
         };
     }
-    match find_wff_substitutions(theo_assertion, formula, &mut wff_mapping, &mut object_mapping) {
-        Ok(()) => true,
-        Err(()) => false
+    match find_wff_substitutions(&statement.assertion, formula, &mut mapping) {
+        Ok(()) => (),
+        Err(()) => return false
+    };
+    distinct_variables_are_respected(statement.distinct, distinct, &mapping)
+}
+
+/// The formula a theorem proves from the used hypotheses, when they determine all of its variables
+pub fn substituted_assertion(
+    used_hypotheses: &[WellFormedFormula], theo_hypotheses: &[WellFormedFormula],
+    theo_assertion: &WellFormedFormula, wff_count: usize, object_count: usize
+) -> Option<WellFormedFormula> {
+    let mut mapping = Substitution::new(wff_count, object_count);
+    for (theo_hyp, used_hyp) in theo_hypotheses.iter().zip(used_hypotheses) {
+        find_wff_substitutions(theo_hyp, used_hyp, &mut mapping).ok()?;
+    };
+    mapping.apply_to_wff(theo_assertion)
+}
+
+/// Every way to pick the used hypotheses of a theorem among the candidate formulas (each candidate at most once
+/// unless 'reuse'), such that the theorem proves the formula, or some formula when it is not given.
+/// Ways picking the same formulas are only listed once, and the search stops at the second way found
+pub(super) fn find_used_hypotheses<'a>(
+    candidates: &[&'a WellFormedFormula], reuse: bool, formula: Option<&'a WellFormedFormula>,
+    statement: &Statement, distinct: &[(Variable, Variable)]
+) -> Vec<Vec<usize>> {
+    let theo_hypotheses = statement.hypotheses;
+    let theo_assertion = &statement.assertion;
+    // The hypotheses reduced to a variable match anything, so they are picked last
+    let mut order = (0..theo_hypotheses.len()).collect::<Vec<_>>();
+    order.sort_by_key(|id| matches!(theo_hypotheses[*id], WellFormedFormula::Atomic(_)));
+    let mut found = Vec::new();
+    let mut picked = vec![0; theo_hypotheses.len()];
+    let mut mapping = Substitution::new(statement.wff_count, statement.object_count);
+    if let Some(formula) = formula {
+        if find_wff_substitutions(theo_assertion, formula, &mut mapping).is_err() { return found; };
+    };
+    let search = HypothesisSearch {
+        candidates, reuse, order: &order, theo_hypotheses, theo_assertion, theo_distinct: statement.distinct, distinct
+    };
+    search.pick(0, mapping, &mut picked, &mut found);
+    found
+}
+
+struct HypothesisSearch<'a, 'b> {
+    candidates: &'b [&'a WellFormedFormula],
+    reuse: bool,
+    order: &'b [usize],
+    theo_hypotheses: &'b [WellFormedFormula],
+    theo_assertion: &'b WellFormedFormula,
+    theo_distinct: &'b [(Variable, Variable)],
+    distinct: &'b [(Variable, Variable)]
+}
+
+impl<'a> HypothesisSearch<'a, '_> {
+    fn pick(&self, depth: usize, mapping: Substitution<'a>, picked: &mut [usize], found: &mut Vec<Vec<usize>>) {
+        if found.len() >= 2 { return; };
+        let Some(&hypot_id) = self.order.get(depth) else {
+            // The assertion must be determined by the hypotheses (and the formula when given)
+            if mapping.apply_to_wff(self.theo_assertion).is_none() { return; };
+            let same_formulas = found.iter().any(|other| {
+                other.iter().zip(picked.iter()).all(|(a, b)| self.candidates[*a] == self.candidates[*b])
+            });
+            if !same_formulas && distinct_variables_are_respected(self.theo_distinct, self.distinct, &mapping) {
+                found.push(picked.to_vec());
+            };
+            return;
+        };
+        for (candidate_id, candidate) in self.candidates.iter().enumerate() {
+            if !self.reuse && self.order[..depth].iter().any(|id| picked[*id] == candidate_id) { continue; };
+            let mut extended = mapping.clone();
+            if find_wff_substitutions(&self.theo_hypotheses[hypot_id], candidate, &mut extended).is_err() { continue; };
+            picked[hypot_id] = candidate_id;
+            self.pick(depth + 1, extended, picked, found);
+        };
     }
 }
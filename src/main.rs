@@ -4,9 +4,14 @@ mod serializing;
 
 mod commands;
 use commands::{
-    compile, add_syndef,
+    compile_library, reorder, add_syndef,
     add_axiom, add_theo,
-    verify, open_editor
+    verify, prove_taut,
+    search_proof, minimize_theo,
+    tidy_theo, fill,
+    axioms_used_by, used_by_theo, graph,
+    remove, rename, import_mm, export_mm,
+    export_json, import_json, export_latex, site, open_editor
 };
 
 mod flags;
@@ -19,7 +24,13 @@ use macro_clap::*;
 cli!(
     const ARG_PARSER: ArgParser<"This is math_lib"> = [
         branch!(command as Command {
-            "compile" |> Compile => {},
+            "compile" |> Compile => {
+                opt!(options as CompileOptions {
+                    sorted: ["-s", "--sorted"] -> (Flag),
+                    jobs: ["-j", "--jobs"] -> (GrabLast<usize>)
+                })
+            },
+            "reorder" |> Reorder => {},
             "add_sd" |> AddSyndef => {
                 arg!(path as String)
             },
@@ -32,6 +43,76 @@ cli!(
             "verify" |> Verify => {
                 arg!(path as String)
             },
+            "prove-taut" |> ProveTaut => {
+                arg!(formula as String)
+            },
+            "search-proof" |> SearchProof => {
+                arg!(path as String),
+                opt!(options as SearchOptions {
+                    depth: ["-d", "--depth"] -> (GrabLast<usize>),
+                    time: ["-t", "--time"] -> (GrabLast<u64>)
+                })
+            },
+            "minimize" |> Minimize => {
+                arg!(name as String)
+            },
+            "tidy" |> Tidy => {
+                arg!(path as String)
+            },
+            "fill" |> Fill => {
+                arg!(path as String)
+            },
+            "axioms-used" |> AxiomsUsed => {
+                arg!(name as String),
+                opt!(options as AxiomsUsedOptions {
+                    path: ["-p", "--path"] -> (Flag)
+                })
+            },
+            "used-by" |> UsedBy => {
+                arg!(name as String),
+                opt!(options as UsedByOptions {
+                    depth: ["-d", "--depth"] -> (GrabLast<usize>)
+                })
+            },
+            "graph" |> Graph => {
+                opt!(options as GraphOptions {
+                    root: ["-r", "--root"] -> (GrabLast<String>),
+                    out: ["-o", "--out"] -> (GrabLast<String>),
+                    depth: ["-d", "--depth"] -> (GrabLast<usize>)
+                })
+            },
+            "remove" |> Remove => {
+                arg!(name as String),
+                opt!(options as RemoveOptions {
+                    cascade: ["-c", "--cascade"] -> (Flag)
+                })
+            },
+            "rename" |> Rename => {
+                arg!(old_name as String),
+                arg!(new_name as String)
+            },
+            "import-mm" |> ImportMm => {
+                arg!(path as String)
+            },
+            "export-mm" |> ExportMm => {
+                arg!(out as String)
+            },
+            "export-json" |> ExportJson => {
+                arg!(out as String)
+            },
+            "import-json" |> ImportJson => {
+                arg!(path as String)
+            },
+            "export-latex" |> ExportLatex => {
+                maybe!(name as (Option<String>)),
+                opt!(options as ExportLatexOptions {
+                    all: ["-a", "--all"] -> (Flag),
+                    out: ["-o", "--out"] -> (GrabLast<String>)
+                })
+            },
+            "site" |> Site => {
+                arg!(out_dir as String)
+            },
             "edit" |> Edit => {},
             "flag" |> Flag => {
                 maybe!(flag_name as (Option<String>)),
@@ -57,19 +138,47 @@ fn main() {
         }
     };
     let dir = settings.lib_path.clone();
+    // A document written on the standard output is followed by its status on the standard error,
+    // so that it can be redirected to a file
+    let document_to_stdout = match &command {
+        Command::Graph(options) => options.out.last.is_none(),
+        Command::ExportLatex(_, options) => options.out.last.is_none(),
+        _ => false
+    };
     let command_result = match command {
-        Command::Compile() => compile(dir),
+        Command::Compile(options) => compile_library(dir, options.sorted.state, options.jobs.last.unwrap_or(1)),
+        Command::Reorder() => reorder(dir),
         Command::AddSyndef(path) => add_syndef(dir, path),
         Command::AddAxiom(path) => add_axiom(dir, path),
         Command::AddTheo(path) => add_theo(dir, path),
         Command::Verify(path) => verify(dir, path),
+        Command::ProveTaut(formula) => prove_taut(dir, formula),
+        Command::SearchProof(path, options) => {
+            search_proof(dir, path, options.depth.last, options.time.last)
+        },
+        Command::Minimize(name) => minimize_theo(dir, name),
+        Command::Tidy(path) => tidy_theo(dir, path),
+        Command::Fill(path) => fill(dir, path),
+        Command::AxiomsUsed(name, options) => axioms_used_by(dir, name, options.path.state),
+        Command::UsedBy(name, options) => used_by_theo(dir, name, options.depth.last),
+        Command::Graph(options) => graph(dir, options.root.last, options.out.last, options.depth.last),
+        Command::Remove(name, options) => remove(dir, name, options.cascade.state),
+        Command::Rename(old_name, new_name) => rename(dir, old_name, new_name),
+        Command::ImportMm(path) => import_mm(dir, path),
+        Command::ExportMm(out) => export_mm(dir, out),
+        Command::ExportJson(out) => export_json(dir, out),
+        Command::ImportJson(path) => import_json(dir, path),
+        Command::ExportLatex(name, options) => export_latex(dir, name, options.all.state, options.out.last),
+        Command::Site(out_dir) => site(dir, out_dir),
         Command::Edit() => open_editor(dir),
         Command::Flag(name, value) => {
             handle_flag_command(name, value, &mut settings)
         }
     };
     match command_result {
+        Ok(message) if document_to_stdout => eprintln!("SUCCESS: {message}"),
         Ok(message) => println!("SUCCESS: {message}"),
+        Err(message) if document_to_stdout => eprintln!("ERROR: {message}"),
         Err(message) => println!("ERROR: {message}")
     }
 }
@@ -1,12 +1,37 @@
 use super::{
     Formula, FormulaChar,  // Formula is an alias for 'Vec<FormulaChar>'
-    ProofLine  // ProofLine is an alias for '(u32, Vec<u32>, String, Formula)'
+    ProofLine  // ProofLine is an alias for '(u32, Vec<u32>, String, Option<Formula>)'
 };
 
+/// Reads the number of a work variable, if the characters after a '&' make one
+fn parse_work_variable(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<FormulaChar> {
+    let mut lookahead = chars.clone();
+    let kind = lookahead.next()?;
+    let mut digits = String::new();
+    while let Some(c) = lookahead.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    };
+    let id = digits.parse::<usize>().ok()?;
+    let work_variable = match kind {
+        'W' => FormulaChar::WorkWff(id),
+        'C' => FormulaChar::WorkObject(id),
+        _ => return None
+    };
+    *chars = lookahead;
+    Some(work_variable)
+}
+
 pub fn parse_formula(fm: &str) -> Formula {
     let mut res = Formula::new();
-    for c in fm.chars() {
+    let mut chars = fm.chars().peekable();
+    while let Some(c) = chars.next() {
         if c == ' ' { continue; }
+        if c == '&' {
+            if let Some(work_variable) = parse_work_variable(&mut chars) {
+                res.push(work_variable);
+                continue;
+            };
+        };
         if c == '…' {
             res.push(FormulaChar::RepetitionChar);
         } else if '𝑎' <= c && c <= '𝑧' {  // '𝑎' and '𝑧' here are NOT ascii
@@ -22,6 +47,16 @@ pub fn parse_formula(fm: &str) -> Formula {
     res
 }
 
+/// Reads a LaTeX template, whose spaces matter (as in '\forall 𝑥')
+pub fn parse_template(template: &str) -> Formula {
+    template.chars()
+        .map(|c| match c {
+            ' ' => FormulaChar::Char(' '),
+            c => parse_formula(&c.to_string()).pop().unwrap_or(FormulaChar::Char(c))
+        })
+        .collect()
+}
+
 pub fn parse_named_formula(nfm: &str) -> Result<(String, Formula), ()> {
     let (name, formula) = match nfm.split_once(':') {
         Some((name, formula)) => (name, formula),
@@ -54,8 +89,19 @@ pub fn parse_proof_line(prline: &str) -> Result<ProofLine, ()> {
     let Some(theorem_reference) = split.next().and_then(|s| Some(s.trim().to_string())) else {
         return Err(());
     };
-    let Some(formula) = split.next().and_then(|fm| Some(parse_formula(fm))) else {
-        return Err(())
+    // The formula can be left empty, or be written '?', to be inferred by the compiler
+    let formula = match split.next().map(|fm| fm.trim()) {
+        None | Some("") | Some("?") => None,
+        Some(fm) => Some(parse_formula(fm))
     };
     Ok((line_no, used_hypots, theorem_reference, formula))
 }
+
+/// Writes a proof line with its columns aligned like in the library files
+pub fn proof_line_to_string(line_no: usize, used_hypots: &[usize], reference: &str, formula: &str) -> String {
+    let used_hypots = used_hypots.iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{:<3} {:<7} {:<7} {formula}", format!("{line_no};"), used_hypots + ";", reference.to_owned() + ";")
+}
@@ -0,0 +1,445 @@
+use std::collections::{HashMap, HashSet};
+use super::proof_line_to_string;
+
+#[derive(Debug)]
+pub enum MetamathError {
+    UnclosedComment,
+    UnclosedStatement(String),  // The label or keyword of the statement
+    UnexpectedToken(String),
+    UnmatchedBlockEnd,
+    IncludeNotSupported(String),  // The included file
+    UnsupportedConstant(String)  // Constants cannot contain the characters variables are written with
+}
+
+/// A file to write in the library, in the section 0 for syntax definitions, 1 for axioms and 2 for theorems
+pub struct ImportedFile {
+    pub section: usize,
+    pub name: String,
+    pub content: String
+}
+
+/// The files translated from a Metamath database, and the statements that could not be, with the reason why
+pub struct MetamathImport {
+    pub files: Vec<ImportedFile>,
+    pub skipped: Vec<(String, String)>
+}
+
+#[derive(Clone)]
+enum Hypothesis {
+    Floating { typecode: String, variable: String },
+    Essential { typecode: String, expression: Vec<String> }
+}
+
+struct Assertion {
+    typecode: String,
+    expression: Vec<String>,
+    hypotheses: Vec<Hypothesis>,  // The mandatory hypotheses, in the order proofs give them
+    name: Option<String>  // None for the syntax axioms, and the statements that were not imported
+}
+
+/// The variables most often used in Metamath databases, and the letters usually written for them
+pub const USUAL_WFFS: [(&str, char); 12] = [
+    ("ph", '𝜑'), ("ps", '𝜓'), ("ch", '𝜒'), ("th", '𝜃'), ("ta", '𝜏'), ("et", '𝜂'),
+    ("ze", '𝜁'), ("si", '𝜎'), ("rh", '𝜌'), ("mu", '𝜇'), ("la", '𝜆'), ("ka", '𝜅')
+];
+
+/// Metamath variables are words, and the variables of the library single letters,
+/// greek ones for the wffs and latin ones for the other typecodes
+#[derive(Default)]
+struct Letters {
+    letters: HashMap<String, char>
+}
+
+impl Letters {
+    fn letter(&mut self, variable: &str, is_wff: bool) -> Option<char> {
+        if let Some(letter) = self.letters.get(variable) { return Some(*letter); };
+        let usual = match is_wff {
+            true => USUAL_WFFS.iter().find(|(name, _)| *name == variable).map(|(_, letter)| *letter),
+            false => match variable.chars().collect::<Vec<_>>()[..] {
+                [c] if c.is_ascii_alphabetic() => char::from_u32('𝑎' as u32 + (c.to_ascii_lowercase() as u32 - 'a' as u32)),
+                _ => None
+            }
+        };
+        let (first, count) = match is_wff { true => ('𝛼', 25), false => ('𝑎', 26) };
+        let letter = usual.into_iter()
+            .chain((0..count).filter_map(|id| char::from_u32(first as u32 + id)))
+            // There is no italic 'h' in the block of the other latin letters
+            .filter(|letter| *letter != char::from_u32('𝑎' as u32 + 7).unwrap_or('𝑎'))
+            .find(|letter| self.letters.values().all(|used| used != letter))?;
+        self.letters.insert(variable.to_owned(), letter);
+        Some(letter)
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<&str>, MetamathError> {
+    let mut tokens = Vec::new();
+    let mut in_comment = false;
+    for token in source.split_whitespace() {
+        match (in_comment, token) {
+            (true, "$)") => in_comment = false,
+            (true, _) => (),
+            (false, "$(") => in_comment = true,
+            (false, token) => tokens.push(token)
+        };
+    };
+    match in_comment {
+        true => Err(MetamathError::UnclosedComment),
+        false => Ok(tokens)
+    }
+}
+
+fn substitute(expression: &[String], substitution: &HashMap<&str, &[String]>) -> Vec<String> {
+    expression.iter()
+        .flat_map(|token| match substitution.get(token.as_str()) {
+            Some(replacement) => replacement.to_vec(),
+            None => vec![token.clone()]
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Importer {
+    variables: Vec<String>,
+    hypotheses: Vec<(String, Hypothesis)>,
+    disjoint: Vec<(String, String)>,
+    scopes: Vec<(usize, usize, usize)>,
+    assertions: HashMap<String, Assertion>,
+    names: HashSet<String>,
+    files: Vec<ImportedFile>,
+    skipped: Vec<(String, String)>
+}
+
+impl Importer {
+    /// A name made of the letters and digits of the label, that no other statement has
+    fn new_name(&mut self, label: &str) -> String {
+        let base = label.chars().filter(char::is_ascii_alphanumeric).collect::<String>();
+        let base = if base.is_empty() { "s".to_string() } else { base };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        // 'taut' is how proofs refer to tautologies
+        while self.names.contains(&name) || name == "taut" {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        };
+        self.names.insert(name.clone());
+        name
+    }
+
+    fn typecode_of(&self, variable: &str) -> Option<&str> {
+        self.hypotheses.iter().rev().find_map(|(_, hypothesis)| match hypothesis {
+            Hypothesis::Floating { typecode, variable: var } if var == variable => Some(typecode.as_str()),
+            _ => None
+        })
+    }
+
+    /// Writes an expression with the letters of the statement, giving letters to the variables that have none yet
+    fn render(&self, expression: &[String], letters: &mut Letters) -> Result<String, String> {
+        let mut res = Vec::new();
+        for token in expression {
+            if !self.variables.contains(token) {
+                res.push(token.clone());
+                continue;
+            };
+            let Some(typecode) = self.typecode_of(token) else {
+                return Err(format!("the variable {token} has no type"));
+            };
+            let Some(letter) = letters.letter(token, typecode == "wff") else {
+                return Err("there are too many variables".to_string());
+            };
+            res.push(letter.to_string());
+        };
+        Ok(res.join(" "))
+    }
+
+    /// The hypotheses a statement needs: the floating ones of its variables, and every essential one
+    fn mandatory_hypotheses(&self, expression: &[String]) -> Vec<Hypothesis> {
+        let mut used_tokens = expression.iter().collect::<HashSet<_>>();
+        for (_, hypothesis) in &self.hypotheses {
+            if let Hypothesis::Essential { expression, .. } = hypothesis {
+                used_tokens.extend(expression);
+            };
+        };
+        self.hypotheses.iter()
+            .filter(|(_, hypothesis)| match hypothesis {
+                Hypothesis::Floating { variable, .. } => used_tokens.contains(variable),
+                Hypothesis::Essential { .. } => true
+            })
+            .map(|(_, hypothesis)| hypothesis.clone())
+            .collect()
+    }
+
+    /// The lines of the distinct section for the variables that have letters
+    fn distinct_lines(&self, letters: &Letters) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (var1, var2) in &self.disjoint {
+            let (Some(letter1), Some(letter2)) = (letters.letters.get(var1), letters.letters.get(var2)) else { continue; };
+            let line = format!("{letter1} {letter2}");
+            if !lines.contains(&line) { lines.push(line); };
+        };
+        lines
+    }
+
+    fn add_syntax(&mut self, label: &str, typecode: &str, expression: &[String]) -> Result<(), String> {
+        // A syntax made of a single variable, like turning a setvar into a class, is no syntax in the library
+        if let [token] = expression {
+            if self.variables.contains(token) { return Ok(()); };
+        };
+        let mut letters = Letters::default();
+        let syntax = self.render(expression, &mut letters)?;
+        let definition_type = match typecode { "wff" => "formula", _ => "object" };
+        let name = self.new_name(label);
+        let content = format!("## Syntax Definition ({definition_type}) {name}\n# Syntax\n{syntax}\n");
+        self.files.push(ImportedFile { section: 0, name, content });
+        Ok(())
+    }
+
+    /// The hypotheses and the assertion of an axiom or a theorem, written with its letters
+    fn statement_lines(&self, assertion: &Assertion, letters: &mut Letters) -> Result<(Vec<String>, String), String> {
+        let mut hypotheses = Vec::new();
+        for hypothesis in &assertion.hypotheses {
+            let Hypothesis::Essential { typecode, expression } = hypothesis else { continue; };
+            if typecode != "|-" { return Err(format!("a hypothesis has the typecode {typecode}")); };
+            hypotheses.push(self.render(expression, letters)?);
+        };
+        Ok((hypotheses, self.render(&assertion.expression, letters)?))
+    }
+
+    fn add_axiom(&mut self, label: &str, assertion: &Assertion) -> Result<String, String> {
+        let mut letters = Letters::default();
+        let (hypotheses, statement) = self.statement_lines(assertion, &mut letters)?;
+        let name = self.new_name(label);
+        let mut content = format!("## Axiom {name}\n# Hypotheses\n");
+        hypotheses.iter().for_each(|hypothesis| content.push_str(&format!("{hypothesis}\n")));
+        content.push_str(&format!("\n# Assertion\n{statement}\n"));
+        let distinct = self.distinct_lines(&letters);
+        if !distinct.is_empty() {
+            content.push_str(&format!("\n# Distinct\n{}\n", distinct.join("\n")));
+        };
+        self.files.push(ImportedFile { section: 1, name: name.clone(), content });
+        Ok(name)
+    }
+
+    /// Replays a normal proof, the syntax steps building the expressions and the other steps becoming proof lines
+    fn add_theorem(&mut self, label: &str, assertion: &Assertion, proof: &[&str]) -> Result<String, String> {
+        if proof.first() == Some(&"(") { return Err("compressed proofs are not supported".to_string()); };
+        let mut letters = Letters::default();
+        let (hypotheses, statement) = self.statement_lines(assertion, &mut letters)?;
+        let mut hypothesis_names = HashMap::new();
+        for hypothesis in &assertion.hypotheses {
+            let Hypothesis::Essential { expression, .. } = hypothesis else { continue; };
+            let mut name = format!("h{}", hypothesis_names.len() + 1);
+            // A hypothesis would hide the statement it is named after
+            while self.names.contains(&name) { name.push('h'); };
+            hypothesis_names.insert(expression.clone(), name);
+        };
+
+        let mut stack: Vec<(String, Vec<String>, Option<usize>)> = Vec::new();
+        let mut lines = Vec::new();
+        let mut line_numbers = HashMap::<Vec<String>, usize>::new();
+        for step in proof {
+            if *step == "?" { return Err("the proof is incomplete".to_string()); };
+            let hypothesis = self.hypotheses.iter().rev().find(|(hyp_label, _)| hyp_label == step);
+            let (reference, typecode, expression, used_lines) = match hypothesis {
+                Some((_, Hypothesis::Floating { typecode, variable })) => {
+                    stack.push((typecode.clone(), vec![variable.clone()], None));
+                    continue;
+                },
+                Some((_, Hypothesis::Essential { typecode, expression })) => {
+                    let Some(name) = hypothesis_names.get(expression) else {
+                        return Err(format!("{step} is not a hypothesis of the theorem"));
+                    };
+                    (name.clone(), typecode.clone(), expression.clone(), Vec::new())
+                },
+                None => {
+                    let Some(used) = self.assertions.get(*step) else {
+                        return Err(format!("the proof uses {step}, which is unknown"));
+                    };
+                    let Some(first_popped) = stack.len().checked_sub(used.hypotheses.len()) else {
+                        return Err(format!("{step} is missing hypotheses"));
+                    };
+                    let popped = stack.split_off(first_popped);
+                    let mut substitution = HashMap::new();
+                    for (hypothesis, (typecode, expression, _)) in used.hypotheses.iter().zip(&popped) {
+                        if let Hypothesis::Floating { typecode: expected, variable } = hypothesis {
+                            if typecode != expected { return Err(format!("{step} is given an expression of the wrong type")); };
+                            substitution.insert(variable.as_str(), expression.as_slice());
+                        };
+                    };
+                    let mut used_lines = Vec::new();
+                    for (hypothesis, (typecode, expression, line)) in used.hypotheses.iter().zip(&popped) {
+                        let Hypothesis::Essential { typecode: expected, expression: expected_expression } = hypothesis else {
+                            continue;
+                        };
+                        if typecode != expected || substitute(expected_expression, &substitution) != *expression {
+                            return Err(format!("a hypothesis of {step} does not match"));
+                        };
+                        used_lines.extend(line);
+                    };
+                    let expression = substitute(&used.expression, &substitution);
+                    match (&used.name, used.typecode == "|-") {
+                        (_, false) => {
+                            stack.push((used.typecode.clone(), expression, None));
+                            continue;
+                        },
+                        (None, true) => return Err(format!("the proof uses {step}, which was not imported")),
+                        (Some(name), true) => (name.clone(), used.typecode.clone(), expression, used_lines)
+                    }
+                }
+            };
+            // A formula proven again is taken from the line that proves it first
+            let line_no = match line_numbers.get(&expression) {
+                Some(line_no) => *line_no,
+                None => {
+                    let formula = self.render(&expression, &mut letters)?;
+                    lines.push(proof_line_to_string(lines.len() + 1, &used_lines, &reference, &formula));
+                    line_numbers.insert(expression.clone(), lines.len());
+                    lines.len()
+                }
+            };
+            stack.push((typecode, expression, Some(line_no)));
+        };
+        match &stack[..] {
+            [(typecode, expression, Some(_))] if *typecode == assertion.typecode && *expression == assertion.expression => (),
+            _ => return Err("the proof does not prove the assertion".to_string())
+        };
+
+        let name = self.new_name(label);
+        let mut content = format!("## Theorem {name}\n# Hypotheses\n");
+        let names = assertion.hypotheses.iter().filter_map(|hypothesis| match hypothesis {
+            Hypothesis::Essential { expression, .. } => hypothesis_names.get(expression),
+            Hypothesis::Floating { .. } => None
+        });
+        for (hyp_name, hypothesis) in names.zip(&hypotheses) {
+            content.push_str(&format!("{hyp_name}: {hypothesis}\n"));
+        };
+        content.push_str(&format!("\n# Assertion\n{statement}\n"));
+        let distinct = self.distinct_lines(&letters);
+        if !distinct.is_empty() {
+            content.push_str(&format!("\n# Distinct\n{}\n", distinct.join("\n")));
+        };
+        content.push_str("\n# Proof\n");
+        lines.iter().for_each(|line| content.push_str(&format!("{line}\n")));
+        self.files.push(ImportedFile { section: 2, name: name.clone(), content });
+        Ok(name)
+    }
+
+    fn add_assertion(&mut self, label: &str, keyword: &str, statement: Vec<String>, proof: &[&str]) {
+        let Some((typecode, expression)) = statement.split_first() else {
+            self.skipped.push((label.to_owned(), "the statement is empty".to_string()));
+            return;
+        };
+        let mut assertion = Assertion {
+            typecode: typecode.clone(),
+            expression: expression.to_vec(),
+            hypotheses: self.mandatory_hypotheses(expression),
+            name: None
+        };
+        let added = match (keyword, typecode.as_str()) {
+            ("$a", "|-") => self.add_axiom(label, &assertion).map(Some),
+            ("$a", typecode) => self.add_syntax(label, typecode, expression).map(|_| None),
+            ("$p", "|-") => self.add_theorem(label, &assertion, proof).map(Some),
+            (_, typecode) => Err(format!("theorems of typecode {typecode} are not imported"))
+        };
+        match added {
+            Ok(name) => assertion.name = name,
+            Err(reason) => self.skipped.push((label.to_owned(), reason))
+        };
+        self.assertions.insert(label.to_owned(), assertion);
+    }
+}
+
+/// Reads the tokens up to the end of a statement, which is '$.', or '$=' for the statement of a '$p'
+fn statement_tokens<'a>(tokens: &[&'a str], index: &mut usize, end: &str, label: &str) -> Result<Vec<&'a str>, MetamathError> {
+    let Some(length) = tokens[*index..].iter().position(|token| *token == end) else {
+        return Err(MetamathError::UnclosedStatement(label.to_owned()));
+    };
+    let res = tokens[*index..*index + length].to_vec();
+    *index += length + 1;
+    Ok(res)
+}
+
+/// Translates the syntax axioms, axioms and theorems with normal proofs of a Metamath database into files
+/// of the library. 'taken_names' are the names already used in the library
+pub fn import_metamath(source: &str, taken_names: HashSet<String>) -> Result<MetamathImport, MetamathError> {
+    let tokens = tokenize(source)?;
+    let mut importer = Importer { names: taken_names, ..Default::default() };
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        index += 1;
+        match token {
+            "${" => importer.scopes.push((importer.variables.len(), importer.hypotheses.len(), importer.disjoint.len())),
+            "$}" => {
+                let Some((variables, hypotheses, disjoint)) = importer.scopes.pop() else {
+                    return Err(MetamathError::UnmatchedBlockEnd);
+                };
+                importer.variables.truncate(variables);
+                importer.hypotheses.truncate(hypotheses);
+                importer.disjoint.truncate(disjoint);
+            },
+            "$c" => {
+                for constant in statement_tokens(&tokens, &mut index, "$.", token)? {
+                    let is_variable_char = |c: char| ('𝑎'..='𝑧').contains(&c) || ('𝛼'..='𝜔').contains(&c);
+                    if constant.chars().any(|c| c == '…' || c == '&' || is_variable_char(c)) {
+                        return Err(MetamathError::UnsupportedConstant(constant.to_owned()));
+                    };
+                };
+            },
+            "$v" => {
+                let variables = statement_tokens(&tokens, &mut index, "$.", token)?;
+                importer.variables.extend(variables.into_iter().map(str::to_owned));
+            },
+            "$d" => {
+                let variables = statement_tokens(&tokens, &mut index, "$.", token)?;
+                for (position, var1) in variables.iter().enumerate() {
+                    for var2 in &variables[position + 1..] {
+                        importer.disjoint.push((var1.to_string(), var2.to_string()));
+                    };
+                };
+            },
+            "$[" => {
+                let included = statement_tokens(&tokens, &mut index, "$]", token)?;
+                return Err(MetamathError::IncludeNotSupported(included.join(" ")));
+            },
+            label if !label.starts_with('$') => {
+                let Some(keyword) = tokens.get(index).copied() else {
+                    return Err(MetamathError::UnclosedStatement(label.to_owned()));
+                };
+                index += 1;
+                match keyword {
+                    "$f" => {
+                        let statement = statement_tokens(&tokens, &mut index, "$.", label)?;
+                        let [typecode, variable] = statement[..] else {
+                            return Err(MetamathError::UnexpectedToken(label.to_owned()));
+                        };
+                        let hypothesis = Hypothesis::Floating { typecode: typecode.to_owned(), variable: variable.to_owned() };
+                        importer.hypotheses.push((label.to_owned(), hypothesis));
+                    },
+                    "$e" => {
+                        let statement = statement_tokens(&tokens, &mut index, "$.", label)?;
+                        let Some((typecode, expression)) = statement.split_first() else {
+                            return Err(MetamathError::UnexpectedToken(label.to_owned()));
+                        };
+                        let hypothesis = Hypothesis::Essential {
+                            typecode: typecode.to_string(),
+                            expression: expression.iter().map(|token| token.to_string()).collect()
+                        };
+                        importer.hypotheses.push((label.to_owned(), hypothesis));
+                    },
+                    "$a" => {
+                        let statement = statement_tokens(&tokens, &mut index, "$.", label)?;
+                        importer.add_assertion(label, keyword, statement.into_iter().map(str::to_owned).collect(), &[]);
+                    },
+                    "$p" => {
+                        let statement = statement_tokens(&tokens, &mut index, "$=", label)?;
+                        let proof = statement_tokens(&tokens, &mut index, "$.", label)?;
+                        importer.add_assertion(label, keyword, statement.into_iter().map(str::to_owned).collect(), &proof);
+                    },
+                    keyword => return Err(MetamathError::UnexpectedToken(keyword.to_owned()))
+                };
+            },
+            token => return Err(MetamathError::UnexpectedToken(token.to_owned()))
+        };
+    };
+    Ok(MetamathImport { files: importer.files, skipped: importer.skipped })
+}
@@ -1,16 +1,20 @@
 mod types;
 pub use types::{
-    MathFile, DefinitionType,
+    MathFile, DefinitionType, Associativity,
     FileType, FileSection,
     FormulaChar, Formula, ProofLine
 };
 
 mod formula;
+pub use formula::{parse_formula, proof_line_to_string};
 use formula::{
-    parse_formula,
+    parse_template,
     parse_named_formula,
     parse_proof_line
 };
 
 mod read_file;
 pub use read_file::{parse_file, ParseError};
+
+mod metamath;
+pub use metamath::{import_metamath, USUAL_WFFS};
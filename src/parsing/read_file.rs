@@ -1,7 +1,7 @@
 use super::{
-    MathFile, DefinitionType,
+    MathFile, DefinitionType, Associativity,
     FileType, FileSection,
-    parse_formula, parse_named_formula, parse_proof_line
+    parse_formula, parse_template, parse_named_formula, parse_proof_line
 };
 
 #[derive(Debug)]
@@ -14,7 +14,23 @@ pub enum ParseError {
     InvalidSectionOrder,
     InvalidNamedHypothesis,
     InvalidProofLine,
-    InvalidName(String)
+    InvalidName(String),
+    InvalidPrecedence(String)
+}
+
+/// Reads a header like '# Precedence 30 right', where the associativity is optional
+fn parse_precedence_header(line: &str) -> Result<FileSection, ParseError> {
+    let (level, associativity) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["#", "Precedence", level] => (level, Associativity::NonAssociative),
+        ["#", "Precedence", level, "left"] => (level, Associativity::Left),
+        ["#", "Precedence", level, "right"] => (level, Associativity::Right),
+        ["#", "Precedence", level, "none"] => (level, Associativity::NonAssociative),
+        _ => return Err(ParseError::InvalidPrecedence(line.to_owned()))
+    };
+    match level.parse::<usize>() {
+        Ok(level) => Ok(FileSection::Precedence(level, associativity)),
+        Err(_) => Err(ParseError::InvalidPrecedence(line.to_owned()))
+    }
 }
 
 pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
@@ -55,7 +71,10 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
                     "# Hypotheses" => FileSection::HypothesisList,
                     "# Assertion" => FileSection::AssertionList,
                     "# Assertions" => FileSection::AssertionList,
+                    "# Distinct" => FileSection::DistinctList,
                     "# Proof" => FileSection::Proof,
+                    "# LaTeX" => FileSection::Latex,
+                    line if line.starts_with("# Precedence") => parse_precedence_header(line)?,
                     line => {
                         return Err(ParseError::InvalidSection(line.to_owned(), file_type))
                     }
@@ -69,6 +88,43 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
         section => file_contents.push( (section, section_contents) )
     };
 
+    // The precedence of a syntax is given right after it, in the header of an empty section
+    let precedence = match &file_contents[..] {
+        [
+            (FileSection::Syntax, _),
+            (FileSection::Precedence(level, associativity), precedence_lines),
+            ..
+        ] => {
+            if let Some(line) = precedence_lines.first() {
+                return Err(ParseError::InvalidPrecedence(line.to_string()));
+            };
+            Some((*level, *associativity))
+        },
+        _ => None
+    };
+    if precedence.is_some() {
+        file_contents.remove(1);
+    };
+    // The LaTeX template of a syntax comes last, the other files cannot have one
+    let latex = match (&file_type, &file_contents[..]) {
+        (
+            FileType::FormulaSyntaxDefinition | FileType::ObjectSyntaxDefinition,
+            [.., (FileSection::Latex, latex_lines)]
+        ) => {
+            match latex_lines.len() {
+                0 => return Err(ParseError::EmptySection(FileSection::Latex)),
+                1 => (),
+                _ => return Err(ParseError::MultilineSection(FileSection::Latex))
+            };
+            Some(parse_template(latex_lines[0]))
+        },
+        _ => None
+    };
+    if latex.is_some() {
+        file_contents.pop();
+    };
+
+    let empty_section = Vec::new();
     match file_type {
         FileType::FormulaSyntaxDefinition => {
             match &file_contents[..] {
@@ -85,7 +141,9 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
                             name,
                             definition_type: DefinitionType::Formula,
                             syntax: parse_formula(syntax_lines[0]),
-                            definition: None
+                            precedence,
+                            definition: None,
+                            latex
                         }
                     );
                 },
@@ -108,7 +166,9 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
                             name,
                             definition_type: DefinitionType::Formula,
                             syntax: parse_formula(syntax_lines[0]),
-                            definition: Some(parse_formula(definition_lines[0]))
+                            precedence,
+                            definition: Some(parse_formula(definition_lines[0])),
+                            latex
                         }
                     );
                 },
@@ -130,7 +190,9 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
                             name,
                             definition_type: DefinitionType::Object,
                             syntax: parse_formula(syntax_lines[0]),
-                            definition: None
+                            precedence,
+                            definition: None,
+                            latex
                         }
                     );
                 },
@@ -153,7 +215,9 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
                             name,
                             definition_type: DefinitionType::Object,
                             syntax: parse_formula(syntax_lines[0]),
-                            definition: Some(parse_formula(definition_lines[0]))
+                            precedence,
+                            definition: Some(parse_formula(definition_lines[0])),
+                            latex
                         }
                     );
                 },
@@ -161,50 +225,65 @@ pub fn parse_file(content: String) -> Result<MathFile, ParseError> {
             };
         },
         FileType::Axiom => {
-            match &file_contents[..] {
+            let (hypots, asserts, distinct_lines) = match &file_contents[..] {
                 [
                     (FileSection::HypothesisList, hypots),
                     (FileSection::AssertionList, asserts)
-                ] => {
-                    let hypotheses = hypots.into_iter()
-                        .map(|fm| parse_formula(fm))
-                        .collect();
-                    let assertions = asserts.into_iter()
-                        .map(|fm| parse_formula(fm))
-                        .collect();
-                    return Ok( MathFile::Axiom { name, hypotheses, assertions } );
-                },
+                ] => (hypots, asserts, &empty_section),
+                [
+                    (FileSection::HypothesisList, hypots),
+                    (FileSection::AssertionList, asserts),
+                    (FileSection::DistinctList, distinct_lines)
+                ] => (hypots, asserts, distinct_lines),
                 _ => return Err(ParseError::InvalidSectionOrder)
-            }
+            };
+            let hypotheses = hypots.into_iter()
+                .map(|fm| parse_formula(fm))
+                .collect();
+            let assertions = asserts.into_iter()
+                .map(|fm| parse_formula(fm))
+                .collect();
+            let distinct = distinct_lines.into_iter()
+                .map(|fm| parse_formula(fm))
+                .collect();
+            return Ok( MathFile::Axiom { name, hypotheses, assertions, distinct } );
         },
         FileType::Theorem => {
-            match &file_contents[..] {
+            let (hypots, asserts, distinct_lines, proof_lines) = match &file_contents[..] {
                 [
                     (FileSection::HypothesisList, hypots),
                     (FileSection::AssertionList, asserts),
                     (FileSection::Proof, proof_lines)
-                ] => {
-                    let try_hypotheses = hypots.into_iter()
-                        .map(|fm| parse_named_formula(fm))
-                        .collect();
-                    let hypotheses = match try_hypotheses {
-                        Ok(hypotheses) => hypotheses,
-                        Err(()) => return Err(ParseError::InvalidNamedHypothesis)
-                    };
-                    let assertions = asserts.into_iter()
-                        .map(|fm| parse_formula(fm))
-                        .collect();
-                    let try_proof = proof_lines.into_iter()
-                        .map(|prline| parse_proof_line(prline))
-                        .collect();
-                    let proof = match try_proof {
-                        Ok(proof) => proof,
-                        Err(()) => return Err(ParseError::InvalidProofLine)
-                    };
-                    return Ok( MathFile::Theorem { name, hypotheses, assertions, proof } );
-                },
+                ] => (hypots, asserts, &empty_section, proof_lines),
+                [
+                    (FileSection::HypothesisList, hypots),
+                    (FileSection::AssertionList, asserts),
+                    (FileSection::DistinctList, distinct_lines),
+                    (FileSection::Proof, proof_lines)
+                ] => (hypots, asserts, distinct_lines, proof_lines),
                 _ => return Err(ParseError::InvalidSectionOrder)
-            }
+            };
+            let try_hypotheses = hypots.into_iter()
+                .map(|fm| parse_named_formula(fm))
+                .collect();
+            let hypotheses = match try_hypotheses {
+                Ok(hypotheses) => hypotheses,
+                Err(()) => return Err(ParseError::InvalidNamedHypothesis)
+            };
+            let assertions = asserts.into_iter()
+                .map(|fm| parse_formula(fm))
+                .collect();
+            let distinct = distinct_lines.into_iter()
+                .map(|fm| parse_formula(fm))
+                .collect();
+            let try_proof = proof_lines.into_iter()
+                .map(|prline| parse_proof_line(prline))
+                .collect();
+            let proof = match try_proof {
+                Ok(proof) => proof,
+                Err(()) => return Err(ParseError::InvalidProofLine)
+            };
+            return Ok( MathFile::Theorem { name, hypotheses, assertions, distinct, proof } );
         }
     };
 }
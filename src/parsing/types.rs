@@ -1,16 +1,19 @@
 // --------------------------------------------- //
 // Types for mathematical formula representation //
 // --------------------------------------------- //
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FormulaChar {
     Char(char),
     Wff(usize),
     Object(usize),
-    RepetitionChar
+    RepetitionChar,
+    // Work variables, written '&W1' and '&C1', stand for a formula or an object not known yet
+    WorkWff(usize),
+    WorkObject(usize)
 }
 
 pub type Formula = Vec<FormulaChar>;
-pub type ProofLine = (usize, Vec<usize>, String, Formula);
+pub type ProofLine = (usize, Vec<usize>, String, Option<Formula>);  // No formula when it is left to be inferred
 
 // --------------------------------- //
 // Types for file type determination //
@@ -29,6 +32,9 @@ pub enum FileSection {
     Definition,
     HypothesisList,
     AssertionList,
+    DistinctList,
+    Precedence(usize, Associativity),
+    Latex,
     Proof,
     None
 }
@@ -43,23 +49,34 @@ pub enum DefinitionType {
     Object
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssociative
+}
+
 #[derive(Debug)]
 pub enum MathFile {
     SyntaxDefinition {
         name: String,
         definition_type: DefinitionType,
         syntax: Formula,
-        definition: Option<Formula>
+        precedence: Option<(usize, Associativity)>,
+        definition: Option<Formula>,
+        latex: Option<Formula>  // The template of the syntax in LaTeX, its spaces kept as characters
     },
     Axiom {
         name: String,
         hypotheses: Vec<Formula>,
-        assertions: Vec<Formula>
+        assertions: Vec<Formula>,
+        distinct: Vec<Formula>
     },
     Theorem {
         name: String,
         hypotheses: Vec<(String, Formula)>,
         assertions: Vec<Formula>,
+        distinct: Vec<Formula>,
         proof: Vec<ProofLine>
     }
 }
\ No newline at end of file
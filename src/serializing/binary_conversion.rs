@@ -1,11 +1,12 @@
 use super::{
-    BinaryConvert, Vectorizable,
+    BinaryConvert, SyntaxBinaryConvert, Vectorizable,
     RpnBlock,
     wff_to_rpn, rpn_to_wff
 };
 use crate::compiling::{
     Syntax, SyntaxType, Placeholder,
-    Definition, Axiom,
+    Precedence, Associativity,
+    Definition, Axiom, Variable,
     Theorem, LogicStep, Reference
 };
 
@@ -16,9 +17,6 @@ impl BinaryConvert<0> for u8 {
     fn from_binary<I: Iterator<Item = u8>>(source: &mut I) -> Option<Self> {
         source.next()
     }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
-    }
 }
 
 impl BinaryConvert<0> for usize {
@@ -32,9 +30,6 @@ impl BinaryConvert<0> for usize {
         };
         Some(u32::from_le_bytes(bytes) as usize)
     }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
-    }
 }
 
 impl BinaryConvert<0> for String {
@@ -48,9 +43,6 @@ impl BinaryConvert<0> for String {
             .collect::<Vec<_>>();
         String::from_utf8(string).ok()
     }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
-    }
 }
 
 impl<T, const N: usize> BinaryConvert<N> for Vec<T>
@@ -77,9 +69,6 @@ where T: Vectorizable<BinaryForm = [u8; N]> {
         };
         Some(res)
     }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
-    }
 }
 
 impl<T, const N: usize> BinaryConvert<N> for Vec<Vec<T>>
@@ -116,9 +105,6 @@ where T: Vectorizable<BinaryForm = [u8; N]> {
         };
         Some(res)
     }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
-    }
 }
 
 impl BinaryConvert<0> for Syntax {
@@ -126,8 +112,10 @@ impl BinaryConvert<0> for Syntax {
         let Syntax {
             syntax_type,
             formula,
+            precedence,
             distinct_wff_count,
-            distinct_object_count
+            distinct_object_count,
+            latex
         } = self;
         let mut res = match syntax_type {
             SyntaxType::Formula => vec![0x00],
@@ -136,6 +124,24 @@ impl BinaryConvert<0> for Syntax {
         res.append(&mut distinct_wff_count.to_binary());
         res.append(&mut distinct_object_count.to_binary());
         res.append(&mut formula.to_binary());
+        match precedence {
+            None => res.push(0x00),
+            Some(Precedence { level, associativity }) => {
+                res.push(match associativity {
+                    Associativity::Left => 0x01,
+                    Associativity::Right => 0x02,
+                    Associativity::NonAssociative => 0x03
+                });
+                res.append(&mut level.to_binary());
+            }
+        };
+        match latex {
+            None => res.push(0x00),
+            Some(template) => {
+                res.push(0x01);
+                res.append(&mut template.to_binary());
+            }
+        };
         res
     }
     fn from_binary<I: Iterator<Item = u8>>(source: &mut I) -> Option<Self> {
@@ -147,22 +153,35 @@ impl BinaryConvert<0> for Syntax {
         let distinct_wff_count = usize::from_binary(source)?;
         let distinct_object_count = usize::from_binary(source)?;
         let formula = Vec::<Placeholder>::from_binary(source)?;
-        Some(Syntax { syntax_type, formula, distinct_wff_count, distinct_object_count })
-    }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
-        Self::from_binary(source)
+        let associativity = match source.next() {
+            Some(0x00) => None,
+            Some(0x01) => Some(Associativity::Left),
+            Some(0x02) => Some(Associativity::Right),
+            Some(0x03) => Some(Associativity::NonAssociative),
+            _ => return None
+        };
+        let precedence = match associativity {
+            Some(associativity) => Some(Precedence { level: usize::from_binary(source)?, associativity }),
+            None => None
+        };
+        let latex = match source.next() {
+            Some(0x00) => None,
+            Some(0x01) => Some(Vec::<Placeholder>::from_binary(source)?),
+            _ => return None
+        };
+        Some(Syntax { syntax_type, formula, precedence, distinct_wff_count, distinct_object_count, latex })
     }
 }
 
-impl BinaryConvert<0> for Definition {
-    fn to_binary(self) -> Vec<u8> {
+impl SyntaxBinaryConvert for Definition {
+    fn to_binary_syntaxes(self, syntaxes: &Vec<Syntax>) -> Vec<u8> {
         let Definition {
             name,
             definition,
             distinct_wff_count,
             distinct_object_count
         } = self;
-        let definition_rpn = wff_to_rpn(definition);
+        let definition_rpn = wff_to_rpn(definition, syntaxes);
         let mut res = Vec::new();
         res.append(&mut name.to_binary());
         res.append(&mut distinct_wff_count.to_binary());
@@ -170,8 +189,7 @@ impl BinaryConvert<0> for Definition {
         res.append(&mut definition_rpn.to_binary());
         res
     }
-    fn from_binary<I>(_source: &mut I) -> Option<Self> { None }
-    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, syntaxes:&Vec<Syntax>) -> Option<Self> {
+    fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, syntaxes: &Vec<Syntax>) -> Option<Self> {
         let name = String::from_binary(source)?;
         let distinct_wff_count = usize::from_binary(source)?;
         let distinct_object_count = usize::from_binary(source)?;
@@ -181,73 +199,80 @@ impl BinaryConvert<0> for Definition {
     }
 }
 
-impl BinaryConvert<0> for Axiom {
-    fn to_binary(self) -> Vec<u8> {
+impl SyntaxBinaryConvert for Axiom {
+    fn to_binary_syntaxes(self, syntaxes: &Vec<Syntax>) -> Vec<u8> {
         let Axiom {
             name,
             hypotheses,
             assertions,
+            distinct_variables,
             distinct_wff_count,
             distinct_object_count
         } = self;
-        let hypots_rpn = hypotheses.into_iter().map(wff_to_rpn).collect::<Vec<_>>();
-        let asserts_rpn = assertions.into_iter().map(wff_to_rpn).collect::<Vec<_>>();
+        let hypots_rpn = hypotheses.into_iter().map(|wff| wff_to_rpn(wff, syntaxes)).collect::<Vec<_>>();
+        let asserts_rpn = assertions.into_iter().map(|wff| wff_to_rpn(wff, syntaxes)).collect::<Vec<_>>();
+        let distinct = pairs_to_vecs(distinct_variables);
         let mut res = Vec::new();
         res.append(&mut name.to_binary());
         res.append(&mut distinct_wff_count.to_binary());
         res.append(&mut distinct_object_count.to_binary());
         res.append(&mut hypots_rpn.to_binary());
         res.append(&mut asserts_rpn.to_binary());
+        res.append(&mut distinct.to_binary());
         res
     }
-    fn from_binary<I>(_source: &mut I) -> Option<Self> { None }
     fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, syntaxes: &Vec<Syntax>) -> Option<Self> {
         let name = String::from_binary(source)?;
         let distinct_wff_count = usize::from_binary(source)?;
         let distinct_object_count = usize::from_binary(source)?;
         let hypots_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
         let asserts_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
+        let distinct = Vec::<Vec<Variable>>::from_binary(source)?;
         let hypotheses = hypots_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
         let assertions = asserts_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
-        Some(Axiom { name, hypotheses, assertions, distinct_wff_count, distinct_object_count })
+        let distinct_variables = vecs_to_pairs(distinct)?;
+        Some(Axiom { name, hypotheses, assertions, distinct_variables, distinct_wff_count, distinct_object_count })
     }
 }
 
-impl BinaryConvert<0> for Theorem {
-    fn to_binary(self) -> Vec<u8> {
+impl SyntaxBinaryConvert for Theorem {
+    fn to_binary_syntaxes(self, syntaxes: &Vec<Syntax>) -> Vec<u8> {
         let Theorem {
             name,
             hypotheses,
             assertions,
+            distinct_variables,
             proof,
             distinct_wff_count,
             distinct_object_count
         } = self;
-        let hypots_rpn = hypotheses.into_iter().map(wff_to_rpn).collect::<Vec<_>>();
-        let asserts_rpn = assertions.into_iter().map(wff_to_rpn).collect::<Vec<_>>();
-        let (pr_hyps, pr_refs, pr_formulas) = transpose_steps(proof);
+        let hypots_rpn = hypotheses.into_iter().map(|wff| wff_to_rpn(wff, syntaxes)).collect::<Vec<_>>();
+        let asserts_rpn = assertions.into_iter().map(|wff| wff_to_rpn(wff, syntaxes)).collect::<Vec<_>>();
+        let distinct = pairs_to_vecs(distinct_variables);
+        let (pr_hyps, pr_refs, pr_formulas) = transpose_steps(proof, syntaxes);
         let mut res = Vec::new();
         res.append(&mut name.to_binary());
         res.append(&mut distinct_wff_count.to_binary());
         res.append(&mut distinct_object_count.to_binary());
         res.append(&mut hypots_rpn.to_binary());
         res.append(&mut asserts_rpn.to_binary());
+        res.append(&mut distinct.to_binary());
         res.append(&mut pr_hyps.to_binary());
         res.append(&mut pr_refs.to_binary());
         res.append(&mut pr_formulas.to_binary());
         res
     }
-    fn from_binary<I>(_source: &mut I) -> Option<Self> { None }
     fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, syntaxes: &Vec<Syntax>) -> Option<Self> {
         let name = String::from_binary(source)?;
         let distinct_wff_count = usize::from_binary(source)?;
         let distinct_object_count = usize::from_binary(source)?;
         let hypots_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
         let asserts_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
+        let distinct = Vec::<Vec<Variable>>::from_binary(source)?;
         let pr_hyps = Vec::<Vec<usize>>::from_binary(source)?;
         let pr_refs = Vec::<Reference>::from_binary(source)?;
         let pr_formulas = Vec::<Vec<RpnBlock>>::from_binary(source)?;
@@ -257,12 +282,15 @@ impl BinaryConvert<0> for Theorem {
         let assertions = asserts_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
+        let distinct_variables = vecs_to_pairs(distinct)?;
         let proof = transpose_3vec(pr_hyps, pr_refs, pr_formulas, syntaxes)?;
-        Some(Theorem { name, hypotheses, assertions, proof, distinct_wff_count, distinct_object_count })
+        Some(Theorem { name, hypotheses, assertions, distinct_variables, proof, distinct_wff_count, distinct_object_count })
     }
 }
 
-fn transpose_steps(proof: Vec<LogicStep>) -> (Vec<Vec<usize>>, Vec<Reference>, Vec<Vec<RpnBlock>>) {
+fn transpose_steps(
+    proof: Vec<LogicStep>, syntaxes: &Vec<Syntax>
+) -> (Vec<Vec<usize>>, Vec<Reference>, Vec<Vec<RpnBlock>>) {
     let mut hyps = Vec::new();
     let mut refs = Vec::new();
     let mut formulas = Vec::new();
@@ -274,7 +302,7 @@ fn transpose_steps(proof: Vec<LogicStep>) -> (Vec<Vec<usize>>, Vec<Reference>, V
         } = step;
         hyps.push(used_hypotheses);
         refs.push(theorem_ref);
-        formulas.push(wff_to_rpn(resulting_formula))
+        formulas.push(wff_to_rpn(resulting_formula, syntaxes))
     };
     (hyps, refs, formulas)
 }
@@ -295,3 +323,18 @@ fn transpose_3vec(
         ).collect::<Vec<_>>();
     Some(res)
 }
+
+fn pairs_to_vecs(pairs: Vec<(Variable, Variable)>) -> Vec<Vec<Variable>> {
+    pairs.into_iter()
+        .map(|(v1, v2)| vec![v1, v2])
+        .collect()
+}
+
+fn vecs_to_pairs(vecs: Vec<Vec<Variable>>) -> Option<Vec<(Variable, Variable)>> {
+    vecs.into_iter()
+        .map(|pair| match pair[..] {
+            [v1, v2] => Some((v1, v2)),
+            _ => None
+        })
+        .collect()
+}
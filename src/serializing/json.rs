@@ -0,0 +1,258 @@
+/// A JSON value, the numbers kept as they are written and the members of objects in their order
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>)
+}
+
+#[derive(Debug)]
+pub enum JsonError {
+    UnexpectedEnd,
+    UnexpectedCharacter(char, usize, usize),  // The character, with its line and column
+    InvalidEscape(usize, usize),
+    MissingField(String),  // The path of the field, like 'theorems[2].proof[0].theorem_ref'
+    WrongType(String, &'static str),  // The path of the value and the type it should have
+    UnknownKind(String, String),
+    UnsupportedVersion(String),
+    InvalidSyntaxRef(String),
+    WrongParameterCount(String),
+    InvalidVariable(String),  // A variable beyond the counts of its statement
+    UnexpectedWorkVariable(String),
+    InvalidReference(String)
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        let Json::Object(members) = self else { return None; };
+        members.iter()
+            .find(|(member_key, _)| member_key == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Writes the value on a single line
+    pub fn write(&self, res: &mut String) {
+        match self {
+            Json::Null => res.push_str("null"),
+            Json::Bool(value) => res.push_str(if *value { "true" } else { "false" }),
+            Json::Number(number) => res.push_str(number),
+            Json::String(string) => write_string(string, res),
+            Json::Array(values) => {
+                res.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 { res.push_str(", "); };
+                    value.write(res);
+                };
+                res.push(']');
+            },
+            Json::Object(members) => {
+                res.push('{');
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 { res.push_str(", "); };
+                    write_string(key, res);
+                    res.push_str(": ");
+                    value.write(res);
+                };
+                res.push('}');
+            }
+        };
+    }
+}
+
+fn write_string(string: &str, res: &mut String) {
+    res.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c)
+        };
+    };
+    res.push('"');
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize
+}
+
+impl Parser {
+    fn error(&self) -> JsonError {
+        let Some(c) = self.chars.get(self.position) else {
+            return JsonError::UnexpectedEnd;
+        };
+        let before = &self.chars[..self.position];
+        let line = before.iter().filter(|c| **c == '\n').count() + 1;
+        let column = self.position - before.iter().rposition(|c| *c == '\n').map_or(0, |index| index + 1) + 1;
+        JsonError::UnexpectedCharacter(*c, line, column)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.position).is_some_and(|c| " \t\n\r".contains(*c)) {
+            self.position += 1;
+        };
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        match self.chars.get(self.position) {
+            Some(c) if *c == expected => {
+                self.position += 1;
+                Ok(())
+            },
+            _ => Err(self.error())
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, JsonError> {
+        for expected in keyword.chars() {
+            if self.chars.get(self.position) != Some(&expected) { return Err(self.error()); };
+            self.position += 1;
+        };
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.chars.get(self.position) {
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some('[') => {
+                self.position += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.position) == Some(&']') {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                };
+                loop {
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.chars.get(self.position) {
+                        Some(',') => self.position += 1,
+                        Some(']') => { self.position += 1; break; },
+                        _ => return Err(self.error())
+                    };
+                };
+                Ok(Json::Array(values))
+            },
+            Some('{') => {
+                self.position += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.position) == Some(&'}') {
+                    self.position += 1;
+                    return Ok(Json::Object(members));
+                };
+                loop {
+                    self.skip_whitespace();
+                    if self.chars.get(self.position) != Some(&'"') { return Err(self.error()); };
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    members.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.chars.get(self.position) {
+                        Some(',') => self.position += 1,
+                        Some('}') => { self.position += 1; break; },
+                        _ => return Err(self.error())
+                    };
+                };
+                Ok(Json::Object(members))
+            },
+            _ => Err(self.error())
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        while self.chars.get(self.position).is_some_and(|c| "+-.eE".contains(*c) || c.is_ascii_digit()) {
+            self.position += 1;
+        };
+        Ok(Json::Number(self.chars[start..self.position].iter().collect()))
+    }
+
+    fn hex_code(&mut self) -> Result<u32, JsonError> {
+        let digits = self.chars.get(self.position..self.position+4)
+            .map(|digits| digits.iter().collect::<String>())
+            .ok_or(JsonError::UnexpectedEnd)?;
+        let code = u32::from_str_radix(&digits, 16).map_err(|_| self.escape_error())?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn escape_error(&self) -> JsonError {
+        match self.error() {
+            JsonError::UnexpectedCharacter(_, line, column) => JsonError::InvalidEscape(line, column),
+            e => e
+        }
+    }
+
+    /// Reads a string, the characters outside of the Basic Multilingual Plane (like '𝜑')
+    /// being possibly escaped as a surrogate pair
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.position += 1;
+        let mut res = String::new();
+        loop {
+            let Some(c) = self.chars.get(self.position).copied() else {
+                return Err(JsonError::UnexpectedEnd);
+            };
+            self.position += 1;
+            match c {
+                '"' => return Ok(res),
+                '\\' => {
+                    let escaped = match self.chars.get(self.position) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.position += 1;
+                            let mut code = self.hex_code()?;
+                            if (0xd800..0xdc00).contains(&code) {
+                                // A high surrogate is followed by the low one
+                                if self.chars.get(self.position..self.position+2) != Some(&['\\', 'u']) {
+                                    return Err(self.escape_error());
+                                };
+                                self.position += 2;
+                                let low = self.hex_code()?;
+                                if !(0xdc00..0xe000).contains(&low) { return Err(self.escape_error()); };
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            };
+                            res.push(char::from_u32(code).ok_or_else(|| self.escape_error())?);
+                            continue;
+                        },
+                        _ => return Err(self.escape_error())
+                    };
+                    self.position += 1;
+                    res.push(escaped);
+                },
+                c => res.push(c)
+            };
+        };
+    }
+}
+
+/// Reads a JSON document, which must hold a single value
+pub fn parse_json(text: &str) -> Result<Json, JsonError> {
+    let mut parser = Parser { chars: text.chars().collect(), position: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.position == parser.chars.len() {
+        true => Ok(value),
+        false => Err(parser.error())
+    }
+}
@@ -0,0 +1,722 @@
+use super::{Json, JsonError, JsonConvert, parse_json};
+use crate::compiling::{
+    Syntax, SyntaxType, Placeholder,
+    Precedence, Associativity,
+    WellFormedFormula, Object, Variable,
+    Definition, Axiom, Theorem,
+    LogicStep, Reference, Library
+};
+
+const JSON_VERSION: &str = "1";
+
+fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn kind(name: &str) -> (&'static str, Json) {
+    ("kind", Json::String(name.to_string()))
+}
+
+/// Reads a member of an object
+fn member<T: JsonConvert>(json: &Json, key: &str, path: &str, syntaxes: &[Syntax]) -> Result<T, JsonError> {
+    let path = format!("{path}.{key}");
+    match json.get(key) {
+        Some(value) => T::from_json(value, &path, syntaxes),
+        None => Err(JsonError::MissingField(path))
+    }
+}
+
+fn kind_of<'a>(json: &'a Json, path: &str) -> Result<&'a str, JsonError> {
+    match json.get("kind") {
+        Some(Json::String(kind)) => Ok(kind),
+        Some(_) => Err(JsonError::WrongType(format!("{path}.kind"), "string")),
+        None => Err(JsonError::MissingField(format!("{path}.kind")))
+    }
+}
+
+fn string_of<'a>(json: &'a Json, path: &str) -> Result<&'a str, JsonError> {
+    match json {
+        Json::String(string) => Ok(string),
+        _ => Err(JsonError::WrongType(path.to_string(), "string"))
+    }
+}
+
+impl JsonConvert for usize {
+    fn to_json(&self) -> Json {
+        Json::Number(self.to_string())
+    }
+    fn from_json(json: &Json, path: &str, _syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        // The library file stores the numbers on 4 bytes
+        match json {
+            Json::Number(number) => number.parse::<u32>()
+                .map(|number| number as usize)
+                .map_err(|_| JsonError::WrongType(path.to_string(), "integer from 0 to 4294967295")),
+            _ => Err(JsonError::WrongType(path.to_string(), "integer from 0 to 4294967295"))
+        }
+    }
+}
+
+impl JsonConvert for String {
+    fn to_json(&self) -> Json {
+        Json::String(self.clone())
+    }
+    fn from_json(json: &Json, path: &str, _syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        string_of(json, path).map(str::to_owned)
+    }
+}
+
+impl<T: JsonConvert> JsonConvert for Vec<T> {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(T::to_json).collect())
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let Json::Array(values) = json else {
+            return Err(JsonError::WrongType(path.to_string(), "array"));
+        };
+        values.iter()
+            .enumerate()
+            .map(|(index, value)| T::from_json(value, &format!("{path}[{index}]"), syntaxes))
+            .collect()
+    }
+}
+
+impl<T: JsonConvert> JsonConvert for Option<T> {
+    fn to_json(&self) -> Json {
+        match self {
+            Some(value) => value.to_json(),
+            None => Json::Null
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match json {
+            Json::Null => Ok(None),
+            json => T::from_json(json, path, syntaxes).map(Some)
+        }
+    }
+}
+
+impl JsonConvert for Placeholder {
+    fn to_json(&self) -> Json {
+        match self {
+            Placeholder::LiteralChar(c) => object(vec![kind("LiteralChar"), ("char", Json::String(c.to_string()))]),
+            Placeholder::WellFormedFormula(id) => object(vec![kind("WellFormedFormula"), ("id", id.to_json())]),
+            Placeholder::Object(id) => object(vec![kind("Object"), ("id", id.to_json())]),
+            Placeholder::Repetition => object(vec![kind("Repetition")])
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match kind_of(json, path)? {
+            "LiteralChar" => {
+                let char_path = format!("{path}.char");
+                let string = string_of(json.get("char").ok_or(JsonError::MissingField(char_path.clone()))?, &char_path)?;
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Placeholder::LiteralChar(c)),
+                    _ => Err(JsonError::WrongType(char_path, "string of a single character"))
+                }
+            },
+            "WellFormedFormula" => Ok(Placeholder::WellFormedFormula(member(json, "id", path, syntaxes)?)),
+            "Object" => Ok(Placeholder::Object(member(json, "id", path, syntaxes)?)),
+            "Repetition" => Ok(Placeholder::Repetition),
+            other => Err(JsonError::UnknownKind(path.to_string(), other.to_string()))
+        }
+    }
+}
+
+impl JsonConvert for Precedence {
+    fn to_json(&self) -> Json {
+        let associativity = match self.associativity {
+            Associativity::Left => "Left",
+            Associativity::Right => "Right",
+            Associativity::NonAssociative => "NonAssociative"
+        };
+        object(vec![("level", self.level.to_json()), ("associativity", Json::String(associativity.to_string()))])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let associativity_path = format!("{path}.associativity");
+        let associativity = json.get("associativity").ok_or(JsonError::MissingField(associativity_path.clone()))?;
+        let associativity = match string_of(associativity, &associativity_path)? {
+            "Left" => Associativity::Left,
+            "Right" => Associativity::Right,
+            "NonAssociative" => Associativity::NonAssociative,
+            other => return Err(JsonError::UnknownKind(associativity_path, other.to_string()))
+        };
+        Ok(Precedence { level: member(json, "level", path, syntaxes)?, associativity })
+    }
+}
+
+impl JsonConvert for Syntax {
+    fn to_json(&self) -> Json {
+        let syntax_type = match self.syntax_type {
+            SyntaxType::Formula => "Formula",
+            SyntaxType::Object => "Object"
+        };
+        object(vec![
+            ("syntax_type", Json::String(syntax_type.to_string())),
+            ("formula", self.formula.to_json()),
+            ("precedence", self.precedence.to_json()),
+            ("distinct_wff_count", self.distinct_wff_count.to_json()),
+            ("distinct_object_count", self.distinct_object_count.to_json()),
+            ("latex", self.latex.to_json())
+        ])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let type_path = format!("{path}.syntax_type");
+        let syntax_type = json.get("syntax_type").ok_or(JsonError::MissingField(type_path.clone()))?;
+        let syntax_type = match string_of(syntax_type, &type_path)? {
+            "Formula" => SyntaxType::Formula,
+            "Object" => SyntaxType::Object,
+            other => return Err(JsonError::UnknownKind(type_path, other.to_string()))
+        };
+        let syntax = Syntax {
+            syntax_type,
+            formula: member(json, "formula", path, syntaxes)?,
+            precedence: member(json, "precedence", path, syntaxes)?,
+            distinct_wff_count: member(json, "distinct_wff_count", path, syntaxes)?,
+            distinct_object_count: member(json, "distinct_object_count", path, syntaxes)?,
+            latex: member(json, "latex", path, syntaxes)?
+        };
+        let counts = (syntax.distinct_wff_count, syntax.distinct_object_count);
+        check_placeholders(&syntax.formula, counts, &format!("{path}.formula"))?;
+        if let Some(latex) = &syntax.latex {
+            check_placeholders(latex, counts, &format!("{path}.latex"))?;
+        };
+        Ok(syntax)
+    }
+}
+
+fn composite_to_json(syntax_ref: usize, wff_parameters: &Vec<WellFormedFormula>, object_parameters: &Vec<Object>) -> Json {
+    object(vec![
+        kind("SyntaxComposite"),
+        ("syntax_ref", syntax_ref.to_json()),
+        ("wff_parameters", wff_parameters.to_json()),
+        ("object_parameters", object_parameters.to_json())
+    ])
+}
+
+/// Reads the parameters of a composite, which must be those its syntax expects,
+/// as the library file only records how many parameters a variadic syntax repeats
+fn composite_from_json(
+    json: &Json, path: &str, syntaxes: &[Syntax], is_formula: bool
+) -> Result<(usize, Vec<WellFormedFormula>, Vec<Object>), JsonError> {
+    let syntax_ref: usize = member(json, "syntax_ref", path, syntaxes)?;
+    let syntax = match syntaxes.get(syntax_ref) {
+        Some(syntax) if matches!(syntax.syntax_type, SyntaxType::Formula) == is_formula => syntax,
+        _ => return Err(JsonError::InvalidSyntaxRef(format!("{path}.syntax_ref")))
+    };
+    let wff_parameters: Vec<WellFormedFormula> = member(json, "wff_parameters", path, syntaxes)?;
+    let object_parameters: Vec<Object> = member(json, "object_parameters", path, syntaxes)?;
+    let (wff_count, object_count) = (wff_parameters.len(), object_parameters.len());
+    let counts_are_valid = match syntax.repetition().map(|(repeated_index, _)| syntax.formula[repeated_index]) {
+        Some(Placeholder::WellFormedFormula(_)) => {
+            wff_count >= syntax.distinct_wff_count && object_count == syntax.distinct_object_count
+        },
+        Some(Placeholder::Object(_)) => {
+            wff_count == syntax.distinct_wff_count && object_count >= syntax.distinct_object_count
+        },
+        _ => wff_count == syntax.distinct_wff_count && object_count == syntax.distinct_object_count
+    };
+    if !counts_are_valid {
+        return Err(JsonError::WrongParameterCount(path.to_string()));
+    };
+    Ok((syntax_ref, wff_parameters, object_parameters))
+}
+
+impl JsonConvert for WellFormedFormula {
+    fn to_json(&self) -> Json {
+        match self {
+            WellFormedFormula::Atomic(id) => object(vec![kind("Atomic"), ("id", id.to_json())]),
+            WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                composite_to_json(*syntax_ref, wff_parameters, object_parameters)
+            },
+            WellFormedFormula::Repetition => object(vec![kind("Repetition")]),
+            WellFormedFormula::WorkVariable(id) => object(vec![kind("WorkVariable"), ("id", id.to_json())])
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match kind_of(json, path)? {
+            "Atomic" => Ok(WellFormedFormula::Atomic(member(json, "id", path, syntaxes)?)),
+            "SyntaxComposite" => {
+                let (syntax_ref, wff_parameters, object_parameters) = composite_from_json(json, path, syntaxes, true)?;
+                Ok(WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters })
+            },
+            "Repetition" => Ok(WellFormedFormula::Repetition),
+            "WorkVariable" => Ok(WellFormedFormula::WorkVariable(member(json, "id", path, syntaxes)?)),
+            other => Err(JsonError::UnknownKind(path.to_string(), other.to_string()))
+        }
+    }
+}
+
+impl JsonConvert for Object {
+    fn to_json(&self) -> Json {
+        match self {
+            Object::Atomic(id) => object(vec![kind("Atomic"), ("id", id.to_json())]),
+            Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters } => {
+                composite_to_json(*syntax_ref, wff_parameters, object_parameters)
+            },
+            Object::Repetition => object(vec![kind("Repetition")]),
+            Object::WorkVariable(id) => object(vec![kind("WorkVariable"), ("id", id.to_json())])
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match kind_of(json, path)? {
+            "Atomic" => Ok(Object::Atomic(member(json, "id", path, syntaxes)?)),
+            "SyntaxComposite" => {
+                let (syntax_ref, wff_parameters, object_parameters) = composite_from_json(json, path, syntaxes, false)?;
+                Ok(Object::SyntaxComposite { syntax_ref, wff_parameters, object_parameters })
+            },
+            "Repetition" => Ok(Object::Repetition),
+            "WorkVariable" => Ok(Object::WorkVariable(member(json, "id", path, syntaxes)?)),
+            other => Err(JsonError::UnknownKind(path.to_string(), other.to_string()))
+        }
+    }
+}
+
+impl JsonConvert for Variable {
+    fn to_json(&self) -> Json {
+        match self {
+            Variable::Wff(id) => object(vec![kind("Wff"), ("id", id.to_json())]),
+            Variable::Object(id) => object(vec![kind("Object"), ("id", id.to_json())])
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match kind_of(json, path)? {
+            "Wff" => Ok(Variable::Wff(member(json, "id", path, syntaxes)?)),
+            "Object" => Ok(Variable::Object(member(json, "id", path, syntaxes)?)),
+            other => Err(JsonError::UnknownKind(path.to_string(), other.to_string()))
+        }
+    }
+}
+
+impl JsonConvert for (Variable, Variable) {
+    fn to_json(&self) -> Json {
+        Json::Array(vec![self.0.to_json(), self.1.to_json()])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match json {
+            Json::Array(pair) if pair.len() == 2 => Ok((
+                Variable::from_json(&pair[0], &format!("{path}[0]"), syntaxes)?,
+                Variable::from_json(&pair[1], &format!("{path}[1]"), syntaxes)?
+            )),
+            _ => Err(JsonError::WrongType(path.to_string(), "array of two variables"))
+        }
+    }
+}
+
+impl JsonConvert for Reference {
+    fn to_json(&self) -> Json {
+        match self {
+            Reference::HypothesisReference(id) => object(vec![kind("HypothesisReference"), ("id", id.to_json())]),
+            Reference::DefinitionReference(id) => object(vec![kind("DefinitionReference"), ("id", id.to_json())]),
+            Reference::AxiomReference(id, assertion) => object(vec![
+                kind("AxiomReference"), ("id", id.to_json()), ("assertion", assertion.to_json())
+            ]),
+            Reference::TheoremReference(id, assertion) => object(vec![
+                kind("TheoremReference"), ("id", id.to_json()), ("assertion", assertion.to_json())
+            ]),
+            Reference::UnprovenReference => object(vec![kind("UnprovenReference")])
+        }
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        match kind_of(json, path)? {
+            "HypothesisReference" => Ok(Reference::HypothesisReference(member(json, "id", path, syntaxes)?)),
+            "DefinitionReference" => Ok(Reference::DefinitionReference(member(json, "id", path, syntaxes)?)),
+            "AxiomReference" => Ok(Reference::AxiomReference(
+                member(json, "id", path, syntaxes)?, member(json, "assertion", path, syntaxes)?
+            )),
+            "TheoremReference" => Ok(Reference::TheoremReference(
+                member(json, "id", path, syntaxes)?, member(json, "assertion", path, syntaxes)?
+            )),
+            "UnprovenReference" => Ok(Reference::UnprovenReference),
+            other => Err(JsonError::UnknownKind(path.to_string(), other.to_string()))
+        }
+    }
+}
+
+impl JsonConvert for LogicStep {
+    fn to_json(&self) -> Json {
+        object(vec![
+            ("used_hypotheses", self.used_hypotheses.to_json()),
+            ("theorem_ref", self.theorem_ref.to_json()),
+            ("resulting_formula", self.resulting_formula.to_json())
+        ])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        Ok(LogicStep {
+            used_hypotheses: member(json, "used_hypotheses", path, syntaxes)?,
+            theorem_ref: member(json, "theorem_ref", path, syntaxes)?,
+            resulting_formula: member(json, "resulting_formula", path, syntaxes)?
+        })
+    }
+}
+
+impl JsonConvert for Definition {
+    fn to_json(&self) -> Json {
+        object(vec![
+            ("name", self.name.to_json()),
+            ("definition", self.definition.to_json()),
+            ("distinct_wff_count", self.distinct_wff_count.to_json()),
+            ("distinct_object_count", self.distinct_object_count.to_json())
+        ])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let definition = Definition {
+            name: member(json, "name", path, syntaxes)?,
+            definition: member(json, "definition", path, syntaxes)?,
+            distinct_wff_count: member(json, "distinct_wff_count", path, syntaxes)?,
+            distinct_object_count: member(json, "distinct_object_count", path, syntaxes)?
+        };
+        let counts = (definition.distinct_wff_count, definition.distinct_object_count);
+        check_wff(&definition.definition, counts, false, &format!("{path}.definition"))?;
+        Ok(definition)
+    }
+}
+
+impl JsonConvert for Axiom {
+    fn to_json(&self) -> Json {
+        object(vec![
+            ("name", self.name.to_json()),
+            ("hypotheses", self.hypotheses.to_json()),
+            ("assertions", self.assertions.to_json()),
+            ("distinct_variables", self.distinct_variables.to_json()),
+            ("distinct_wff_count", self.distinct_wff_count.to_json()),
+            ("distinct_object_count", self.distinct_object_count.to_json())
+        ])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let axiom = Axiom {
+            name: member(json, "name", path, syntaxes)?,
+            hypotheses: member(json, "hypotheses", path, syntaxes)?,
+            assertions: member(json, "assertions", path, syntaxes)?,
+            distinct_variables: member(json, "distinct_variables", path, syntaxes)?,
+            distinct_wff_count: member(json, "distinct_wff_count", path, syntaxes)?,
+            distinct_object_count: member(json, "distinct_object_count", path, syntaxes)?
+        };
+        check_statement(
+            &axiom.hypotheses, &axiom.assertions, &axiom.distinct_variables,
+            (axiom.distinct_wff_count, axiom.distinct_object_count), path
+        )?;
+        Ok(axiom)
+    }
+}
+
+impl JsonConvert for Theorem {
+    fn to_json(&self) -> Json {
+        object(vec![
+            ("name", self.name.to_json()),
+            ("hypotheses", self.hypotheses.to_json()),
+            ("assertions", self.assertions.to_json()),
+            ("distinct_variables", self.distinct_variables.to_json()),
+            ("proof", self.proof.to_json()),
+            ("distinct_wff_count", self.distinct_wff_count.to_json()),
+            ("distinct_object_count", self.distinct_object_count.to_json())
+        ])
+    }
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError> {
+        let theorem = Theorem {
+            name: member(json, "name", path, syntaxes)?,
+            hypotheses: member(json, "hypotheses", path, syntaxes)?,
+            assertions: member(json, "assertions", path, syntaxes)?,
+            distinct_variables: member(json, "distinct_variables", path, syntaxes)?,
+            proof: member(json, "proof", path, syntaxes)?,
+            distinct_wff_count: member(json, "distinct_wff_count", path, syntaxes)?,
+            distinct_object_count: member(json, "distinct_object_count", path, syntaxes)?
+        };
+        let counts = (theorem.distinct_wff_count, theorem.distinct_object_count);
+        check_statement(&theorem.hypotheses, &theorem.assertions, &theorem.distinct_variables, counts, path)?;
+        // Only the formulas of the proof may hold work variables
+        for (step_id, step) in theorem.proof.iter().enumerate() {
+            check_wff(&step.resulting_formula, counts, true, &format!("{path}.proof[{step_id}].resulting_formula"))?;
+        };
+        Ok(theorem)
+    }
+}
+
+/// Whether the placeholders of a syntax are among its variables
+fn check_placeholders(placeholders: &[Placeholder], counts: (usize, usize), path: &str) -> Result<(), JsonError> {
+    for (index, placeholder) in placeholders.iter().enumerate() {
+        let in_range = match placeholder {
+            Placeholder::WellFormedFormula(id) => *id < counts.0,
+            Placeholder::Object(id) => *id < counts.1,
+            Placeholder::LiteralChar(_) | Placeholder::Repetition => true
+        };
+        if !in_range {
+            return Err(JsonError::InvalidVariable(format!("{path}[{index}].id")));
+        };
+    };
+    Ok(())
+}
+
+/// Whether the variables of a formula are among the variables of its statement, the counts being those of
+/// the statement, and whether it only holds work variables when it is in a proof
+fn check_wff(wff: &WellFormedFormula, counts: (usize, usize), in_proof: bool, path: &str) -> Result<(), JsonError> {
+    match wff {
+        WellFormedFormula::Atomic(id) if *id >= counts.0 => Err(JsonError::InvalidVariable(path.to_string())),
+        WellFormedFormula::WorkVariable(_) if !in_proof => Err(JsonError::UnexpectedWorkVariable(path.to_string())),
+        WellFormedFormula::SyntaxComposite { wff_parameters, object_parameters, .. } => {
+            check_parameters(wff_parameters, object_parameters, counts, in_proof, path)
+        },
+        _ => Ok(())
+    }
+}
+
+fn check_object(obj: &Object, counts: (usize, usize), in_proof: bool, path: &str) -> Result<(), JsonError> {
+    match obj {
+        Object::Atomic(id) if *id >= counts.1 => Err(JsonError::InvalidVariable(path.to_string())),
+        Object::WorkVariable(_) if !in_proof => Err(JsonError::UnexpectedWorkVariable(path.to_string())),
+        Object::SyntaxComposite { wff_parameters, object_parameters, .. } => {
+            check_parameters(wff_parameters, object_parameters, counts, in_proof, path)
+        },
+        _ => Ok(())
+    }
+}
+
+fn check_parameters(
+    wff_parameters: &[WellFormedFormula], object_parameters: &[Object],
+    counts: (usize, usize), in_proof: bool, path: &str
+) -> Result<(), JsonError> {
+    for (index, wff) in wff_parameters.iter().enumerate() {
+        check_wff(wff, counts, in_proof, &format!("{path}.wff_parameters[{index}]"))?;
+    };
+    for (index, obj) in object_parameters.iter().enumerate() {
+        check_object(obj, counts, in_proof, &format!("{path}.object_parameters[{index}]"))?;
+    };
+    Ok(())
+}
+
+/// Whether the hypotheses, assertions and distinct variables of an axiom or a theorem only use its variables
+fn check_statement(
+    hypotheses: &[WellFormedFormula], assertions: &[WellFormedFormula], distinct_variables: &[(Variable, Variable)],
+    counts: (usize, usize), path: &str
+) -> Result<(), JsonError> {
+    for (index, hypothesis) in hypotheses.iter().enumerate() {
+        check_wff(hypothesis, counts, false, &format!("{path}.hypotheses[{index}]"))?;
+    };
+    for (index, assertion) in assertions.iter().enumerate() {
+        check_wff(assertion, counts, false, &format!("{path}.assertions[{index}]"))?;
+    };
+    for (index, pair) in distinct_variables.iter().enumerate() {
+        for (var_index, var) in [pair.0, pair.1].iter().enumerate() {
+            let in_range = match var {
+                Variable::Wff(id) => *id < counts.0,
+                Variable::Object(id) => *id < counts.1
+            };
+            if !in_range {
+                return Err(JsonError::InvalidVariable(format!("{path}.distinct_variables[{index}][{var_index}]")));
+            };
+        };
+    };
+    Ok(())
+}
+
+/// Whether the steps of a proof only use earlier steps, and refer to statements that come before the theorem
+fn check_proof(theorem: &Theorem, lib: &Library, path: &str) -> Result<(), JsonError> {
+    for (step_id, step) in theorem.proof.iter().enumerate() {
+        let step_path = format!("{path}.proof[{step_id}]");
+        if step.used_hypotheses.iter().any(|used| *used >= step_id) {
+            return Err(JsonError::InvalidReference(step_path + ".used_hypotheses"));
+        };
+        let exists = match step.theorem_ref {
+            Reference::HypothesisReference(hypot_id) => hypot_id < theorem.hypotheses.len(),
+            Reference::DefinitionReference(def_id) => def_id < lib.definitions.len(),
+            Reference::AxiomReference(ax_id, assert_id) => {
+                lib.axioms.get(ax_id).is_some_and(|axiom| assert_id < axiom.assertions.len())
+            },
+            Reference::TheoremReference(theo_id, assert_id) => {
+                lib.theorems.get(theo_id).is_some_and(|used| assert_id < used.assertions.len())
+            },
+            Reference::UnprovenReference => true
+        };
+        if !exists {
+            return Err(JsonError::InvalidReference(step_path + ".theorem_ref"));
+        };
+    };
+    Ok(())
+}
+
+/// Writes a library as a JSON document, each syntax, definition, axiom and theorem on its own line
+pub fn library_to_json(lib: &Library) -> String {
+    let mut res = format!("{{\n  \"version\": {JSON_VERSION}");
+    let sections = [
+        ("syntaxes", lib.syntaxes.iter().map(Syntax::to_json).collect::<Vec<_>>()),
+        ("definitions", lib.definitions.iter().map(Definition::to_json).collect()),
+        ("axioms", lib.axioms.iter().map(Axiom::to_json).collect()),
+        ("theorems", lib.theorems.iter().map(Theorem::to_json).collect())
+    ];
+    for (key, items) in sections {
+        res.push_str(&format!(",\n  \"{key}\": ["));
+        for (index, item) in items.iter().enumerate() {
+            res.push_str(if index == 0 { "\n    " } else { ",\n    " });
+            item.write(&mut res);
+        };
+        res.push_str(if items.is_empty() { "]" } else { "\n  ]" });
+    };
+    res.push_str("\n}\n");
+    res
+}
+
+/// Reads a library written as a JSON document, checking that the formulas and the proofs
+/// only refer to what comes before them
+pub fn library_from_json(text: &str) -> Result<Library, JsonError> {
+    let json = parse_json(text)?;
+    let path = "library";
+    match json.get("version") {
+        Some(Json::Number(version)) if version == JSON_VERSION => (),
+        Some(version) => {
+            let mut written = String::new();
+            version.write(&mut written);
+            return Err(JsonError::UnsupportedVersion(written));
+        },
+        None => return Err(JsonError::MissingField(format!("{path}.version")))
+    };
+    // The syntaxes are only made of placeholders, and do not need to be checked against each other
+    let syntaxes: Vec<Syntax> = member(&json, "syntaxes", path, &[])?;
+    let mut lib = Library {
+        definitions: member(&json, "definitions", path, &syntaxes)?,
+        axioms: member(&json, "axioms", path, &syntaxes)?,
+        theorems: Vec::new(),
+        syntaxes
+    };
+    // The theorems are read one by one, as a proof may only use the theorems before it
+    let theorems_json = match json.get("theorems") {
+        Some(Json::Array(theorems)) => theorems,
+        Some(_) => return Err(JsonError::WrongType(format!("{path}.theorems"), "array")),
+        None => return Err(JsonError::MissingField(format!("{path}.theorems")))
+    };
+    for (index, theorem) in theorems_json.iter().enumerate() {
+        let theorem_path = format!("{path}.theorems[{index}]");
+        let theorem = Theorem::from_json(theorem, &theorem_path, &lib.syntaxes)?;
+        check_proof(&theorem, &lib, &theorem_path)?;
+        lib.theorems.push(theorem);
+    };
+    Ok(lib)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composite(syntax_ref: usize, wff_parameters: Vec<WellFormedFormula>, object_parameters: Vec<Object>) -> WellFormedFormula {
+        WellFormedFormula::SyntaxComposite { syntax_ref, wff_parameters, object_parameters }
+    }
+
+    fn implication(left: WellFormedFormula, right: WellFormedFormula) -> WellFormedFormula {
+        composite(0, vec![left, right], Vec::new())
+    }
+
+    // '𝜑 ⇒ 𝜓', '∀ x 𝜑' and the variadic '𝜑 ∧ … ∧ 𝜓'
+    fn syntaxes() -> Vec<Syntax> {
+        vec![
+            Syntax {
+                syntax_type: SyntaxType::Formula,
+                formula: vec![
+                    Placeholder::WellFormedFormula(0), Placeholder::LiteralChar('⇒'), Placeholder::WellFormedFormula(1)
+                ],
+                precedence: Some(Precedence { level: 1, associativity: Associativity::Right }),
+                distinct_wff_count: 2,
+                distinct_object_count: 0,
+                latex: Some(vec![
+                    Placeholder::WellFormedFormula(0), Placeholder::LiteralChar('\\'), Placeholder::LiteralChar('t'),
+                    Placeholder::LiteralChar('o'), Placeholder::WellFormedFormula(1)
+                ])
+            },
+            Syntax {
+                syntax_type: SyntaxType::Formula,
+                formula: vec![Placeholder::LiteralChar('∀'), Placeholder::Object(0), Placeholder::WellFormedFormula(0)],
+                precedence: None,
+                distinct_wff_count: 1,
+                distinct_object_count: 1,
+                latex: None
+            },
+            Syntax {
+                syntax_type: SyntaxType::Formula,
+                formula: vec![
+                    Placeholder::WellFormedFormula(0), Placeholder::LiteralChar('∧'), Placeholder::Repetition,
+                    Placeholder::LiteralChar('∧'), Placeholder::WellFormedFormula(1)
+                ],
+                precedence: Some(Precedence { level: 2, associativity: Associativity::NonAssociative }),
+                distinct_wff_count: 2,
+                distinct_object_count: 0,
+                latex: None
+            }
+        ]
+    }
+
+    fn library() -> Library {
+        let (phi, psi) = (WellFormedFormula::Atomic(0), WellFormedFormula::Atomic(1));
+        let conjunction = composite(2, vec![phi.clone(), WellFormedFormula::Repetition, psi.clone()], Vec::new());
+        let generalization = composite(1, vec![phi.clone()], vec![Object::Atomic(0)]);
+        Library {
+            syntaxes: syntaxes(),
+            definitions: vec![Definition {
+                name: "df-and".to_string(),
+                definition: implication(composite(2, vec![phi.clone(), psi.clone()], Vec::new()), phi.clone()),
+                distinct_wff_count: 2,
+                distinct_object_count: 0
+            }],
+            axioms: vec![
+                Axiom {
+                    name: "ax-and".to_string(),
+                    hypotheses: Vec::new(),
+                    assertions: vec![implication(conjunction, psi.clone())],
+                    distinct_variables: Vec::new(),
+                    distinct_wff_count: 2,
+                    distinct_object_count: 0
+                },
+                Axiom {
+                    name: "ax-gen".to_string(),
+                    hypotheses: vec![phi.clone()],
+                    assertions: vec![implication(phi.clone(), generalization.clone())],
+                    distinct_variables: vec![(Variable::Wff(0), Variable::Object(0))],
+                    distinct_wff_count: 1,
+                    distinct_object_count: 1
+                }
+            ],
+            theorems: vec![Theorem {
+                name: "gen".to_string(),
+                hypotheses: vec![phi.clone()],
+                assertions: vec![generalization.clone()],
+                distinct_variables: vec![(Variable::Wff(0), Variable::Object(0))],
+                proof: vec![
+                    LogicStep {
+                        used_hypotheses: Vec::new(),
+                        theorem_ref: Reference::HypothesisReference(0),
+                        resulting_formula: phi.clone()
+                    },
+                    LogicStep {
+                        used_hypotheses: vec![0],
+                        theorem_ref: Reference::UnprovenReference,
+                        resulting_formula: implication(phi.clone(), WellFormedFormula::WorkVariable(0))
+                    },
+                    LogicStep {
+                        used_hypotheses: vec![0],
+                        theorem_ref: Reference::AxiomReference(1, 0),
+                        resulting_formula: implication(phi, composite(1, vec![psi], vec![Object::WorkVariable(0)]))
+                    },
+                    LogicStep {
+                        used_hypotheses: vec![0, 2],
+                        theorem_ref: Reference::UnprovenReference,
+                        resulting_formula: generalization
+                    }
+                ],
+                distinct_wff_count: 2,
+                distinct_object_count: 1
+            }]
+        }
+    }
+
+    #[test]
+    fn libraries_are_read_back_as_they_were_written() {
+        let lib = library();
+        let json = library_to_json(&lib);
+        assert_eq!(library_from_json(&json).unwrap(), lib);
+    }
+
+    #[test]
+    fn work_variables_are_only_read_in_proofs() {
+        let mut lib = library();
+        lib.axioms[0].assertions[0] = implication(WellFormedFormula::WorkVariable(0), WellFormedFormula::Atomic(1));
+        let json = library_to_json(&lib);
+        assert!(matches!(library_from_json(&json), Err(JsonError::UnexpectedWorkVariable(_))));
+    }
+}
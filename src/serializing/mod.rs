@@ -3,6 +3,13 @@ pub use read_write::{
     read_file, write_lib
 };
 
+mod json;
+pub use json::JsonError;
+use json::{Json, parse_json};
+
+mod json_conversion;
+pub use json_conversion::{library_to_json, library_from_json};
+
 mod rpn;
 use rpn::{
     RpnBlock,
@@ -12,7 +19,7 @@ use rpn::{
 
 mod traits;
 use traits::{
-    Vectorizable, BinaryConvert
+    Vectorizable, BinaryConvert, SyntaxBinaryConvert, JsonConvert
 };
 
 // Impls
@@ -8,7 +8,7 @@ use crate::compiling::{
     Reference,
     Library
 };
-use super::BinaryConvert;
+use super::{BinaryConvert, SyntaxBinaryConvert};
 
 pub fn write_lib(path: String, lib: Library) -> io::Result<()> {
     let Library {
@@ -19,21 +19,21 @@ pub fn write_lib(path: String, lib: Library) -> io::Result<()> {
     } = lib;
     let mut file = File::create(path)?;
     let mut data = Vec::new();
-    for syntax in syntaxes {
+    for syntax in syntaxes.iter().cloned() {
         data.push(0xf0);
         data.append(&mut syntax.to_binary());
     };
     for definition in definitions {
         data.push(0xf1);
-        data.append(&mut definition.to_binary());
+        data.append(&mut definition.to_binary_syntaxes(&syntaxes));
     };
     for axiom in axioms {
         data.push(0xf2);
-        data.append(&mut axiom.to_binary());
+        data.append(&mut axiom.to_binary_syntaxes(&syntaxes));
     };
     for theorem in theorems {
         data.push(0xf3);
-        data.append(&mut theorem.to_binary());
+        data.append(&mut theorem.to_binary_syntaxes(&syntaxes));
     };
     data.push(0xf4);  // EOF
     file.write_all(&data)?;
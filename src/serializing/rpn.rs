@@ -1,5 +1,5 @@
 use crate::compiling::{
-    Syntax,
+    Syntax, Placeholder,
     WellFormedFormula, Object
 };
 
@@ -7,16 +7,37 @@ pub enum RpnBlock {
     WffAtomic(usize),
     WffComposite(usize),
     ObjectAtomic(usize),
-    ObjectComposite(usize)
+    ObjectComposite(usize),
+    WffRepetition,
+    ObjectRepetition,
+    // Number of parameters a variadic syntax has on top of its placeholders,
+    // placed right before its composite block
+    RepetitionCount(usize),
+    WffWork(usize),
+    ObjectWork(usize)
 }
 
-pub fn wff_to_rpn(wff: WellFormedFormula) -> Vec<RpnBlock> {
+/// The number of parameters a composite has on top of the placeholders of its syntax
+fn repetition_count(
+    syntax_ref: usize, wff_parameters: &Vec<WellFormedFormula>, object_parameters: &Vec<Object>,
+    syntaxes: &Vec<Syntax>
+) -> Option<usize> {
+    let syntax = syntaxes.get(syntax_ref)?;
+    let (repeated_index, _) = syntax.repetition()?;
+    match syntax.formula[repeated_index] {
+        Placeholder::WellFormedFormula(_) => wff_parameters.len().checked_sub(syntax.distinct_wff_count),
+        Placeholder::Object(_) => object_parameters.len().checked_sub(syntax.distinct_object_count),
+        _ => None
+    }
+}
+
+pub fn wff_to_rpn(wff: WellFormedFormula, syntaxes: &Vec<Syntax>) -> Vec<RpnBlock> {
     let mut res = Vec::new();
-    __wff_to_rpn(wff, &mut res);
+    __wff_to_rpn(wff, syntaxes, &mut res);
     res
 }
 
-fn __wff_to_rpn(wff: WellFormedFormula, res: &mut Vec<RpnBlock>) {
+fn __wff_to_rpn(wff: WellFormedFormula, syntaxes: &Vec<Syntax>, res: &mut Vec<RpnBlock>) {
     match wff {
         WellFormedFormula::Atomic(id) => res.push(RpnBlock::WffAtomic(id)),
         WellFormedFormula::SyntaxComposite {
@@ -24,18 +45,24 @@ fn __wff_to_rpn(wff: WellFormedFormula, res: &mut Vec<RpnBlock>) {
             wff_parameters,
             object_parameters
         } => {
+            let count = repetition_count(syntax_ref, &wff_parameters, &object_parameters, syntaxes);
             for param_wff in wff_parameters {
-                __wff_to_rpn(param_wff, res);
+                __wff_to_rpn(param_wff, syntaxes, res);
             };
             for param_obj in object_parameters {
-                __obj_to_rpn(param_obj, res);
+                __obj_to_rpn(param_obj, syntaxes, res);
+            };
+            if let Some(count) = count {
+                res.push(RpnBlock::RepetitionCount(count))
             };
             res.push(RpnBlock::WffComposite(syntax_ref))
-        }
+        },
+        WellFormedFormula::Repetition => res.push(RpnBlock::WffRepetition),
+        WellFormedFormula::WorkVariable(id) => res.push(RpnBlock::WffWork(id))
     }
 }
 
-fn __obj_to_rpn(obj: Object, res: &mut Vec<RpnBlock>) {
+fn __obj_to_rpn(obj: Object, syntaxes: &Vec<Syntax>, res: &mut Vec<RpnBlock>) {
     match obj {
         Object::Atomic(id) => res.push(RpnBlock::ObjectAtomic(id)),
         Object::SyntaxComposite {
@@ -43,31 +70,56 @@ fn __obj_to_rpn(obj: Object, res: &mut Vec<RpnBlock>) {
             wff_parameters,
             object_parameters
         } => {
+            let count = repetition_count(syntax_ref, &wff_parameters, &object_parameters, syntaxes);
             for param_wff in wff_parameters {
-                __wff_to_rpn(param_wff, res);
+                __wff_to_rpn(param_wff, syntaxes, res);
             };
             for param_obj in object_parameters {
-                __obj_to_rpn(param_obj, res);
+                __obj_to_rpn(param_obj, syntaxes, res);
+            };
+            if let Some(count) = count {
+                res.push(RpnBlock::RepetitionCount(count))
             };
             res.push(RpnBlock::ObjectComposite(syntax_ref))
-        }
+        },
+        Object::Repetition => res.push(RpnBlock::ObjectRepetition),
+        Object::WorkVariable(id) => res.push(RpnBlock::ObjectWork(id))
+    }
+}
+
+/// The number of wff and object parameters a composite of that syntax takes from the stacks
+fn parameter_counts(syntax: &Syntax, repetition_count: usize) -> (usize, usize) {
+    let Syntax {
+        syntax_type: _,
+        formula,
+        precedence: _,
+        distinct_wff_count: wffc,
+        distinct_object_count: objc,
+        latex: _
+    } = syntax;
+    match syntax.repetition().map(|(repeated_index, _)| &formula[repeated_index]) {
+        Some(Placeholder::WellFormedFormula(_)) => (wffc + repetition_count, *objc),
+        Some(Placeholder::Object(_)) => (*wffc, objc + repetition_count),
+        _ => (*wffc, *objc)
     }
 }
 
 pub fn rpn_to_wff(rpn: Vec<RpnBlock>, syntaxes: &Vec<Syntax>) -> Option<WellFormedFormula> {
     let mut wff_stack = Vec::new();
     let mut obj_stack = Vec::new();
+    let mut repetition_count = 0;
     for block in rpn {
         match block {
             RpnBlock::WffAtomic(id) => wff_stack.push(WellFormedFormula::Atomic(id)),
             RpnBlock::ObjectAtomic(id) => obj_stack.push(Object::Atomic(id)),
+            RpnBlock::WffRepetition => wff_stack.push(WellFormedFormula::Repetition),
+            RpnBlock::ObjectRepetition => obj_stack.push(Object::Repetition),
+            RpnBlock::RepetitionCount(count) => repetition_count = count,
+            RpnBlock::WffWork(id) => wff_stack.push(WellFormedFormula::WorkVariable(id)),
+            RpnBlock::ObjectWork(id) => obj_stack.push(Object::WorkVariable(id)),
             RpnBlock::WffComposite(syntax_ref) => {
-                let &Syntax {
-                    syntax_type: _,
-                    formula: _,
-                    distinct_wff_count: wffc,
-                    distinct_object_count: objc
-                } = syntaxes.get(syntax_ref)?;
+                let (wffc, objc) = parameter_counts(syntaxes.get(syntax_ref)?, repetition_count);
+                repetition_count = 0;
                 let new_wff_stack_len = wff_stack.len().checked_sub(wffc)?;
                 let new_obj_stack_len = obj_stack.len().checked_sub(objc)?;
                 let wff = WellFormedFormula::SyntaxComposite {
@@ -78,12 +130,8 @@ pub fn rpn_to_wff(rpn: Vec<RpnBlock>, syntaxes: &Vec<Syntax>) -> Option<WellForm
                 wff_stack.push(wff);
             },
             RpnBlock::ObjectComposite(syntax_ref) => {
-                let &Syntax {
-                    syntax_type: _,
-                    formula: _,
-                    distinct_wff_count: wffc,
-                    distinct_object_count: objc
-                } = syntaxes.get(syntax_ref)?;
+                let (wffc, objc) = parameter_counts(syntaxes.get(syntax_ref)?, repetition_count);
+                repetition_count = 0;
                 let new_wff_stack_len = wff_stack.len().checked_sub(wffc)?;
                 let new_obj_stack_len = obj_stack.len().checked_sub(objc)?;
                 let obj = Object::SyntaxComposite {
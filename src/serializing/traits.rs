@@ -1,8 +1,14 @@
 use crate::compiling::Syntax;
+use super::{Json, JsonError};
 
 pub trait BinaryConvert<const N: usize> where Self: Sized {
     fn to_binary(self) -> Vec<u8>;
     fn from_binary<I: Iterator<Item = u8>>(source: &mut I) -> Option<Self>;
+}
+
+// For the items whose formulas are written with the arities of the syntaxes
+pub trait SyntaxBinaryConvert where Self: Sized {
+    fn to_binary_syntaxes(self, syntaxes: &Vec<Syntax>) -> Vec<u8>;
     fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, syntaxes: &Vec<Syntax>) -> Option<Self>;
 }
 
@@ -13,3 +19,9 @@ pub trait Vectorizable where Self: Sized {
     const TERMINATOR: Self::BinaryForm;  // Terminator to use when in a vector
     const TERMINATOR2: Self::BinaryForm;  // Terminator to use when in a vector of vectors
 }
+
+pub trait JsonConvert where Self: Sized {
+    fn to_json(&self) -> Json;
+    // The path locates the value in the document for the errors, and the syntaxes are those of the formulas
+    fn from_json(json: &Json, path: &str, syntaxes: &[Syntax]) -> Result<Self, JsonError>;
+}
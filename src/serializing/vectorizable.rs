@@ -3,7 +3,7 @@ use super::{
     RpnBlock
 };
 use crate::compiling::{
-    Placeholder, Reference
+    Placeholder, Reference, Variable
 };
 
 impl Vectorizable for usize {
@@ -68,7 +68,12 @@ impl Vectorizable for RpnBlock {
             RpnBlock::WffAtomic(id) => {res[0] = 0x00; id as u32},
             RpnBlock::WffComposite(id) => {res[0] = 0x01; id as u32},
             RpnBlock::ObjectAtomic(id) => {res[0] = 0x02; id as u32},
-            RpnBlock::ObjectComposite(id) => {res[0] = 0x03; id as u32}
+            RpnBlock::ObjectComposite(id) => {res[0] = 0x03; id as u32},
+            RpnBlock::WffRepetition => {res[0] = 0x04; 0},
+            RpnBlock::ObjectRepetition => {res[0] = 0x05; 0},
+            RpnBlock::RepetitionCount(count) => {res[0] = 0x06; count as u32},
+            RpnBlock::WffWork(id) => {res[0] = 0x07; id as u32},
+            RpnBlock::ObjectWork(id) => {res[0] = 0x08; id as u32}
         };
         for (i, data) in (num as u32).to_le_bytes().into_iter().enumerate() {
             res[i+1] = data
@@ -86,6 +91,41 @@ impl Vectorizable for RpnBlock {
             0x01 => RpnBlock::WffComposite(u32::from_le_bytes(bytes) as usize),
             0x02 => RpnBlock::ObjectAtomic(u32::from_le_bytes(bytes) as usize),
             0x03 => RpnBlock::ObjectComposite(u32::from_le_bytes(bytes) as usize),
+            0x04 => RpnBlock::WffRepetition,
+            0x05 => RpnBlock::ObjectRepetition,
+            0x06 => RpnBlock::RepetitionCount(u32::from_le_bytes(bytes) as usize),
+            0x07 => RpnBlock::WffWork(u32::from_le_bytes(bytes) as usize),
+            0x08 => RpnBlock::ObjectWork(u32::from_le_bytes(bytes) as usize),
+            _ => return None
+        };
+        Some(res)
+    }
+    const TERMINATOR: Self::BinaryForm = [0xfe; 5];
+    const TERMINATOR2: Self::BinaryForm = [0xff; 5];
+}
+
+impl Vectorizable for Variable {
+    type BinaryForm = [u8; 5];
+    fn to_binary_in_vec(self) -> Self::BinaryForm {
+        let mut res = [0; 5];
+        let num = match self {
+            Variable::Wff(id) => {res[0] = 0x00; id as u32},
+            Variable::Object(id) => {res[0] = 0x01; id as u32}
+        };
+        for (i, data) in (num as u32).to_le_bytes().into_iter().enumerate() {
+            res[i+1] = data
+        };
+        res
+    }
+    fn from_binary_in_vec(source: Self::BinaryForm) -> Option<Self> {
+        let first_byte = source[0];
+        let mut bytes = [0; 4];
+        for (i, data) in source.into_iter().skip(1).enumerate() {
+            bytes[i] = data
+        };
+        let res = match first_byte {
+            0x00 => Variable::Wff(u32::from_le_bytes(bytes) as usize),
+            0x01 => Variable::Object(u32::from_le_bytes(bytes) as usize),
             _ => return None
         };
         Some(res)
@@ -102,7 +142,8 @@ impl Vectorizable for Reference {
             Reference::HypothesisReference(id) => {res[0] = 0x00; (id as u32, 0)},
             Reference::DefinitionReference(id) => {res[0] = 0x01; (id as u32, 0)},
             Reference::AxiomReference(id, sub_id) => {res[0] = 0x02; (id as u32, sub_id as u32)},
-            Reference::TheoremReference(id, sub_id) => {res[0] = 0x03; (id as u32, sub_id as u32)}
+            Reference::TheoremReference(id, sub_id) => {res[0] = 0x03; (id as u32, sub_id as u32)},
+            Reference::UnprovenReference => {res[0] = 0x04; (0, 0)}
         };
         for (i, data) in (id as u32).to_le_bytes().into_iter().enumerate() {
             res[i+1] = data
@@ -126,6 +167,7 @@ impl Vectorizable for Reference {
             0x01 => Reference::DefinitionReference(id),
             0x02 => Reference::AxiomReference(id, sub_id),
             0x03 => Reference::TheoremReference(id, sub_id),
+            0x04 => Reference::UnprovenReference,
             _ => return None
         };
         Some(res)
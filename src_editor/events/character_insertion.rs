@@ -18,6 +18,7 @@ fn update_file(line: Vec<char>, context: LineContext, editor_data: &mut EditorDa
         LineContext::Title
         | LineContext::Section
         | LineContext::Hypothesis
+        | LineContext::DistinctVariables
         | LineContext::ProofLine
         => Err(line),
         // Here we only parse the changed line
@@ -1,7 +1,8 @@
 use super::{
     Syntax, SyntaxType, Placeholder,
+    Precedence, Associativity,
     Definition, Axiom, Theorem,
-    LogicStep, Reference,
+    LogicStep, Reference, Variable,
     
     RpnBlock, rpn_to_wff,
 
@@ -105,7 +106,24 @@ impl FromBinary<0> for Syntax {
         let distinct_wff_count = usize::from_binary(source)?;
         let distinct_object_count = usize::from_binary(source)?;
         let formula = Vec::<Placeholder>::from_binary(source)?;
-        Some(Syntax { syntax_type, formula, distinct_wff_count, distinct_object_count })
+        let associativity = match source.next() {
+            Some(0x00) => None,
+            Some(0x01) => Some(Associativity::Left),
+            Some(0x02) => Some(Associativity::Right),
+            Some(0x03) => Some(Associativity::NonAssociative),
+            _ => return None
+        };
+        let precedence = match associativity {
+            Some(associativity) => Some(Precedence { level: usize::from_binary(source)?, associativity }),
+            None => None
+        };
+        // The editor writes formulas with their characters, not with their LaTeX templates
+        match source.next() {
+            Some(0x00) => (),
+            Some(0x01) => { Vec::<Placeholder>::from_binary(source)?; },
+            _ => return None
+        };
+        Some(Syntax { syntax_type, formula, precedence, distinct_wff_count, distinct_object_count })
     }
     fn from_binary_syntaxes<I: Iterator<Item = u8>>(source: &mut I, _syntaxes: &Vec<Syntax>) -> Option<Self> {
         Self::from_binary(source)
@@ -132,13 +150,15 @@ impl FromBinary<0> for Axiom {
         let distinct_object_count = usize::from_binary(source)?;
         let hypots_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
         let asserts_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
+        let distinct = Vec::<Vec<Variable>>::from_binary(source)?;
         let hypotheses = hypots_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
         let assertions = asserts_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
-        Some(Axiom { name, hypotheses, assertions, distinct_wff_count, distinct_object_count })
+        let distinct_variables = vecs_to_pairs(distinct)?;
+        Some(Axiom { name, hypotheses, assertions, distinct_variables, distinct_wff_count, distinct_object_count })
     }
 }
 
@@ -150,6 +170,7 @@ impl FromBinary<0> for Theorem {
         let distinct_object_count = usize::from_binary(source)?;
         let hypots_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
         let asserts_rpn = Vec::<Vec<RpnBlock>>::from_binary(source)?;
+        let distinct = Vec::<Vec<Variable>>::from_binary(source)?;
         let pr_hyps = Vec::<Vec<usize>>::from_binary(source)?;
         let pr_refs = Vec::<Reference>::from_binary(source)?;
         let pr_formulas = Vec::<Vec<RpnBlock>>::from_binary(source)?;
@@ -159,8 +180,9 @@ impl FromBinary<0> for Theorem {
         let assertions = asserts_rpn.into_iter()
             .map(|hyp| rpn_to_wff(hyp, syntaxes))
             .collect::<Option<Vec<_>>>()?;
+        let distinct_variables = vecs_to_pairs(distinct)?;
         let proof = transpose_3vec(pr_hyps, pr_refs, pr_formulas, syntaxes)?;
-        Some(Theorem { name, hypotheses, assertions, proof, distinct_wff_count, distinct_object_count })
+        Some(Theorem { name, hypotheses, assertions, distinct_variables, proof, distinct_wff_count, distinct_object_count })
     }
 }
 
@@ -180,3 +202,12 @@ fn transpose_3vec(
         ).collect::<Vec<_>>();
     Some(res)
 }
+
+fn vecs_to_pairs(vecs: Vec<Vec<Variable>>) -> Option<Vec<(Variable, Variable)>> {
+    vecs.into_iter()
+        .map(|pair| match pair[..] {
+            [v1, v2] => Some((v1, v2)),
+            _ => None
+        })
+        .collect()
+}
@@ -1,12 +1,18 @@
 mod types;
 pub use types::{
     Syntax, SyntaxType, Placeholder,
-    WellFormedFormula, Object,
+    Precedence, Associativity,
+    WellFormedFormula, Object, Variable,
     Definition, Axiom, Theorem,
     LogicStep, Reference,
     LibraryData
 };
 
+#[path = "../../src/compiling/earley.rs"]
+#[allow(dead_code)]  // The editor does not need to build objects
+mod earley;
+pub use earley::{Grammar, Token, ParseTree};
+
 mod rpn;
 use rpn::{rpn_to_wff, RpnBlock};
 
@@ -1,5 +1,5 @@
 use super::{
-    Syntax,
+    Syntax, Placeholder,
     WellFormedFormula, Object
 };
 
@@ -7,23 +7,42 @@ pub enum RpnBlock {
     WffAtomic(usize),
     WffComposite(usize),
     ObjectAtomic(usize),
-    ObjectComposite(usize)
+    ObjectComposite(usize),
+    WffRepetition,
+    ObjectRepetition,
+    RepetitionCount(usize)
+}
+
+/// The number of wff and object parameters a composite of that syntax takes from the stacks
+fn parameter_counts(syntax: &Syntax, repetition_count: usize) -> (usize, usize) {
+    let Syntax {
+        syntax_type: _,
+        formula,
+        precedence: _,
+        distinct_wff_count: wffc,
+        distinct_object_count: objc
+    } = syntax;
+    match syntax.repetition().map(|(repeated_index, _)| &formula[repeated_index]) {
+        Some(Placeholder::WellFormedFormula(_)) => (wffc + repetition_count, *objc),
+        Some(Placeholder::Object(_)) => (*wffc, objc + repetition_count),
+        _ => (*wffc, *objc)
+    }
 }
 
 pub fn rpn_to_wff(rpn: Vec<RpnBlock>, syntaxes: &Vec<Syntax>) -> Option<WellFormedFormula> {
     let mut wff_stack = Vec::new();
     let mut obj_stack = Vec::new();
+    let mut repetition_count = 0;
     for block in rpn {
         match block {
             RpnBlock::WffAtomic(id) => wff_stack.push(WellFormedFormula::Atomic(id)),
             RpnBlock::ObjectAtomic(id) => obj_stack.push(Object::Atomic(id)),
+            RpnBlock::WffRepetition => wff_stack.push(WellFormedFormula::Repetition),
+            RpnBlock::ObjectRepetition => obj_stack.push(Object::Repetition),
+            RpnBlock::RepetitionCount(count) => repetition_count = count,
             RpnBlock::WffComposite(syntax_ref) => {
-                let &Syntax {
-                    syntax_type: _,
-                    formula: _,
-                    distinct_wff_count: wffc,
-                    distinct_object_count: objc
-                } = syntaxes.get(syntax_ref)?;
+                let (wffc, objc) = parameter_counts(syntaxes.get(syntax_ref)?, repetition_count);
+                repetition_count = 0;
                 let new_wff_stack_len = wff_stack.len().checked_sub(wffc)?;
                 let new_obj_stack_len = obj_stack.len().checked_sub(objc)?;
                 let wff = WellFormedFormula::SyntaxComposite {
@@ -34,12 +53,8 @@ pub fn rpn_to_wff(rpn: Vec<RpnBlock>, syntaxes: &Vec<Syntax>) -> Option<WellForm
                 wff_stack.push(wff);
             },
             RpnBlock::ObjectComposite(syntax_ref) => {
-                let &Syntax {
-                    syntax_type: _,
-                    formula: _,
-                    distinct_wff_count: wffc,
-                    distinct_object_count: objc
-                } = syntaxes.get(syntax_ref)?;
+                let (wffc, objc) = parameter_counts(syntaxes.get(syntax_ref)?, repetition_count);
+                repetition_count = 0;
                 let new_wff_stack_len = wff_stack.len().checked_sub(wffc)?;
                 let new_obj_stack_len = obj_stack.len().checked_sub(objc)?;
                 let obj = Object::SyntaxComposite {
@@ -1,4 +1,4 @@
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Placeholder {
     LiteralChar(char),
     WellFormedFormula(usize),
@@ -12,14 +12,52 @@ pub enum SyntaxType {
     Object
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssociative
+}
+
+/// The higher the level, the tighter the syntax binds its parameters
+#[derive(Debug, Clone, Copy)]
+pub struct Precedence {
+    pub level: usize,
+    pub associativity: Associativity
+}
+
 #[derive(Debug)]
 pub struct Syntax {
     pub syntax_type: SyntaxType,
     pub formula: Vec<Placeholder>,
+    pub precedence: Option<Precedence>,
     pub distinct_wff_count: usize,
     pub distinct_object_count: usize
 }
 
+impl Syntax {
+    /// If the syntax is variadic, returns the indices in its formula
+    /// of the placeholder that gets repeated and of the repetition character
+    pub fn repetition(&self) -> Option<(usize, usize)> {
+        let rep_index = self.formula.iter()
+            .position(|pl| *pl == Placeholder::Repetition)?;
+        let repeated_index = self.formula[..rep_index].iter()
+            .rposition(|pl| !matches!(pl, Placeholder::LiteralChar(_)))?;
+        Some((repeated_index, rep_index))
+    }
+}
+
+#[cfg(test)]
+impl Syntax {
+    /// Built the same way as the syntaxes of the compiler, for the tests of the grammar
+    pub fn new(
+        syntax_type: SyntaxType, formula: Vec<Placeholder>, precedence: Option<Precedence>,
+        distinct_wff_count: usize, distinct_object_count: usize
+    ) -> Syntax {
+        Syntax { syntax_type, formula, precedence, distinct_wff_count, distinct_object_count }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum WellFormedFormula {
     Atomic(usize),
@@ -27,7 +65,11 @@ pub enum WellFormedFormula {
         syntax_ref: usize,
         wff_parameters: Vec<WellFormedFormula>,
         object_parameters: Vec<Object>
-    }
+    },
+    // Stands for any number of formulas in the parameters of a variadic syntax
+    Repetition,
+    // A formula of an incomplete proof that is not known yet
+    WorkVariable(usize)
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -37,7 +79,17 @@ pub enum Object {
         syntax_ref: usize,
         wff_parameters: Vec<WellFormedFormula>,
         object_parameters: Vec<Object>
-    }
+    },
+    // Stands for any number of objects in the parameters of a variadic syntax
+    Repetition,
+    // An object of an incomplete proof that is not known yet
+    WorkVariable(usize)
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Variable {
+    Wff(usize),
+    Object(usize)
 }
 
 #[derive(Debug)]
@@ -53,6 +105,7 @@ pub struct Axiom {
     pub name: String,
     pub hypotheses: Vec<WellFormedFormula>,
     pub assertions: Vec<WellFormedFormula>,
+    pub distinct_variables: Vec<(Variable, Variable)>,
     pub distinct_wff_count: usize,
     pub distinct_object_count: usize
 }
@@ -77,6 +130,7 @@ pub struct Theorem {
     pub name: String,
     pub hypotheses: Vec<WellFormedFormula>,
     pub assertions: Vec<WellFormedFormula>,
+    pub distinct_variables: Vec<(Variable, Variable)>,
     pub proof: Vec<LogicStep>,
     pub distinct_wff_count: usize,
     pub distinct_object_count: usize
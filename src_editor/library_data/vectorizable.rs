@@ -1,5 +1,5 @@
 use super::{
-    Placeholder, Reference,
+    Placeholder, Reference, Variable,
     RpnBlock
 };
 
@@ -56,6 +56,28 @@ impl Vectorizable for RpnBlock {
             0x01 => RpnBlock::WffComposite(u32::from_le_bytes(bytes) as usize),
             0x02 => RpnBlock::ObjectAtomic(u32::from_le_bytes(bytes) as usize),
             0x03 => RpnBlock::ObjectComposite(u32::from_le_bytes(bytes) as usize),
+            0x04 => RpnBlock::WffRepetition,
+            0x05 => RpnBlock::ObjectRepetition,
+            0x06 => RpnBlock::RepetitionCount(u32::from_le_bytes(bytes) as usize),
+            _ => return None
+        };
+        Some(res)
+    }
+    const TERMINATOR: Self::BinaryForm = [0xfe; 5];
+    const TERMINATOR2: Self::BinaryForm = [0xff; 5];
+}
+
+impl Vectorizable for Variable {
+    type BinaryForm = [u8; 5];
+    fn from_binary_in_vec(source: Self::BinaryForm) -> Option<Self> {
+        let first_byte = source[0];
+        let mut bytes = [0; 4];
+        for (i, data) in source.into_iter().skip(1).enumerate() {
+            bytes[i] = data
+        };
+        let res = match first_byte {
+            0x00 => Variable::Wff(u32::from_le_bytes(bytes) as usize),
+            0x01 => Variable::Object(u32::from_le_bytes(bytes) as usize),
             _ => return None
         };
         Some(res)
@@ -11,14 +11,18 @@ pub use parser::parse_file;
 mod sections;
 use sections::{
     parse_syntax_section,
+    parse_precedence_section,
     parse_definition_section,
+    parse_latex_section,
     parse_hypotesis_section,
     parse_assertion_section,
+    parse_distinct_section,
     parse_proof_section
 };
 
 mod formula;
 pub use formula::{
     parse_new_syntax,
-    parse_formula
+    parse_formula,
+    parse_distinct_variables
 };
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
+use std::iter::Peekable;
 use super::{
     FileLine, LineContext, FileType,
     ColorInfo, Color,
     parse_syntax_section,
+    parse_precedence_section,
     parse_definition_section,
+    parse_latex_section,
     parse_hypotesis_section,
     parse_assertion_section,
+    parse_distinct_section,
     parse_proof_section
 };
 use crate::library_data::{
@@ -59,6 +63,20 @@ pub fn parse_title(line: Vec<char>) -> (FileLine, FileType) {
     )
 }
 
+fn next_section_is_precedence<I: Iterator<Item = Vec<Vec<char>>>>(sections: &mut Peekable<I>) -> bool {
+    sections.peek()
+        .and_then(|section| section.first())
+        .map(|header| header.iter().collect::<String>().starts_with("# Precedence"))
+        .unwrap_or(false)
+}
+
+fn next_section_is_latex<I: Iterator<Item = Vec<Vec<char>>>>(sections: &mut Peekable<I>) -> bool {
+    sections.peek()
+        .and_then(|section| section.first())
+        .map(|header| header.iter().collect::<String>() == "# LaTeX")
+        .unwrap_or(false)
+}
+
 pub fn parse_file(
     lines: Vec<Vec<char>>, lib_data: &LibraryData, references: &HashMap<String, Reference>
 ) -> Vec<FileLine> {
@@ -94,13 +112,32 @@ pub fn parse_file(
                 return result_lines;
             };
             let (
-                mut syntax_lines, new_syntax
+                mut syntax_lines, mut new_syntax
             ) = parse_syntax_section(syntax_section, SyntaxType::Formula);
             result_lines.append( &mut syntax_lines );
-            if let Some(definition_section) = sections.next() {
-                result_lines.append(
-                    &mut parse_definition_section(definition_section, lib_data, new_syntax)
-                );
+            // The precedence section is optional
+            let mut sections = sections.by_ref().peekable();
+            if next_section_is_precedence(&mut sections) {
+                if let Some(precedence_section) = sections.next() {
+                    let (
+                        mut precedence_lines, precedence
+                    ) = parse_precedence_section(precedence_section);
+                    result_lines.append( &mut precedence_lines );
+                    if let Some(syntax) = &mut new_syntax {
+                        syntax.precedence = precedence;
+                    };
+                };
+            };
+            // The definition and the LaTeX template are optional too, the template coming last
+            if !next_section_is_latex(&mut sections) {
+                if let Some(definition_section) = sections.next() {
+                    result_lines.append(
+                        &mut parse_definition_section(definition_section, lib_data, new_syntax)
+                    );
+                };
+            };
+            if let Some(latex_section) = sections.next() {
+                result_lines.append( &mut parse_latex_section(latex_section) );
             };
         },
         FileType::SyntaxDefinitionObject => {
@@ -108,13 +145,32 @@ pub fn parse_file(
                 return result_lines;
             };
             let (
-                mut syntax_lines, new_syntax
+                mut syntax_lines, mut new_syntax
             ) = parse_syntax_section(syntax_section, SyntaxType::Object);
             result_lines.append( &mut syntax_lines );
-            if let Some(definition_section) = sections.next() {
-                result_lines.append(
-                    &mut parse_definition_section(definition_section, lib_data, new_syntax)
-                );
+            // The precedence section is optional
+            let mut sections = sections.by_ref().peekable();
+            if next_section_is_precedence(&mut sections) {
+                if let Some(precedence_section) = sections.next() {
+                    let (
+                        mut precedence_lines, precedence
+                    ) = parse_precedence_section(precedence_section);
+                    result_lines.append( &mut precedence_lines );
+                    if let Some(syntax) = &mut new_syntax {
+                        syntax.precedence = precedence;
+                    };
+                };
+            };
+            // The definition and the LaTeX template are optional too, the template coming last
+            if !next_section_is_latex(&mut sections) {
+                if let Some(definition_section) = sections.next() {
+                    result_lines.append(
+                        &mut parse_definition_section(definition_section, lib_data, new_syntax)
+                    );
+                };
+            };
+            if let Some(latex_section) = sections.next() {
+                result_lines.append( &mut parse_latex_section(latex_section) );
             };
         },
         FileType::Axiom => {
@@ -132,6 +188,9 @@ pub fn parse_file(
                     &mut parse_assertion_section(assertion_section, lib_data, context)
                 );
             };
+            if let Some(distinct_section) = sections.next() {
+                result_lines.append( &mut parse_distinct_section(distinct_section) );
+            };
         },
         FileType::Theorem => {
             let Some(hypothesis_section) = sections.next() else {
@@ -147,6 +206,17 @@ pub fn parse_file(
                     &mut parse_assertion_section(assertion_section, lib_data, context)
                 );
             };
+            // The distinct variables section is optional in theorems
+            let mut sections = sections.by_ref().peekable();
+            let next_is_distinct = sections.peek()
+                .and_then(|section| section.first())
+                .map(|header| header.iter().collect::<String>() == "# Distinct")
+                .unwrap_or(false);
+            if next_is_distinct {
+                if let Some(distinct_section) = sections.next() {
+                    result_lines.append( &mut parse_distinct_section(distinct_section) );
+                };
+            };
             if let Some(proof_section) = sections.next() {
                 result_lines.append(
                     &mut parse_proof_section(proof_section, lib_data, references, hypot_names)
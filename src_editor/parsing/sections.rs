@@ -4,11 +4,13 @@ use super::{
     FileLine, LineContext,
     ColorInfo, Color,
     parse_new_syntax,
-    parse_formula
+    parse_formula,
+    parse_distinct_variables
 };
 use crate::library_data::{
     LibraryData, Reference,
-    Syntax, SyntaxType
+    Syntax, SyntaxType,
+    Precedence, Associativity
 };
 
 pub fn parse_syntax_section(section: Vec<Vec<char>>, syntax_type: SyntaxType) -> (Vec<FileLine>, Option<Syntax>) {
@@ -164,6 +166,96 @@ pub fn parse_assertion_section(
     result_lines
 }
 
+pub fn parse_distinct_section(section: Vec<Vec<char>>) -> Vec<FileLine> {
+    let mut lines = section.into_iter();
+
+    let section_name_line = match lines.next() {
+        Some(first_line) => {
+            let section_name = first_line.into_iter().collect::<String>();
+            let name_color = match section_name == "# Distinct" {
+                true => ColorInfo::fg_color(Color::White).bold_underlined(),
+                false => ColorInfo::fg_color(Color::Red)
+            };
+            let chars = section_name.chars().collect::<Vec<_>>();
+            let colors = chars.iter().map(|_| name_color).collect();
+            FileLine { context: LineContext::Section, chars, colors }
+        },
+        None => return vec![]
+    };
+
+    let mut result_lines = vec![ section_name_line ];
+    for line in lines {
+        result_lines.push( parse_distinct_variables(line) );
+    };
+    result_lines
+}
+
+pub fn parse_latex_section(section: Vec<Vec<char>>) -> Vec<FileLine> {
+    let mut lines = section.into_iter();
+
+    let section_name_line = match lines.next() {
+        Some(first_line) => {
+            let section_name = first_line.into_iter().collect::<String>();
+            let name_color = match section_name == "# LaTeX" {
+                true => ColorInfo::fg_color(Color::White).bold_underlined(),
+                false => ColorInfo::fg_color(Color::Red)
+            };
+            let chars = section_name.chars().collect::<Vec<_>>();
+            let colors = chars.iter().map(|_| name_color).collect();
+            FileLine { context: LineContext::Section, chars, colors }
+        },
+        None => return vec![]
+    };
+    // The template is LaTeX, which the editor does not check
+    let template_line = match lines.next() {
+        Some(line) => {
+            let colors = line.iter().map(|_| ColorInfo::NO_COLOR).collect();
+            FileLine { context: LineContext::Raw, chars: line, colors }
+        },
+        None => return vec![ section_name_line ]
+    };
+
+    let mut result_lines = vec![ section_name_line, template_line ];
+    for line in lines {
+        let colors = line.iter().map(|_| ColorInfo::fg_color(Color::Red)).collect();
+        result_lines.push( FileLine { context: LineContext::UnexpectedLine, chars: line, colors } );
+    };
+    result_lines
+}
+
+pub fn parse_precedence_section(section: Vec<Vec<char>>) -> (Vec<FileLine>, Option<Precedence>) {
+    let mut lines = section.into_iter();
+
+    let (section_name_line, precedence) = match lines.next() {
+        Some(first_line) => {
+            let section_name = first_line.into_iter().collect::<String>();
+            let (level, associativity) = match section_name.split_whitespace().collect::<Vec<_>>()[..] {
+                ["#", "Precedence", level] => (level, Associativity::NonAssociative),
+                ["#", "Precedence", level, "left"] => (level, Associativity::Left),
+                ["#", "Precedence", level, "right"] => (level, Associativity::Right),
+                ["#", "Precedence", level, "none"] => (level, Associativity::NonAssociative),
+                _ => ("", Associativity::NonAssociative)
+            };
+            let precedence = level.parse::<usize>().ok()
+                .map(|level| Precedence { level, associativity });
+            let name_color = match precedence {
+                Some(_) => ColorInfo::fg_color(Color::White).bold_underlined(),
+                None => ColorInfo::fg_color(Color::Red)
+            };
+            let chars = section_name.chars().collect::<Vec<_>>();
+            let colors = chars.iter().map(|_| name_color).collect();
+            (FileLine { context: LineContext::Section, chars, colors }, precedence)
+        },
+        None => return (vec![], None)
+    };
+
+    let mut result_lines = vec![ section_name_line ];
+    for line in lines {
+        let colors = line.iter().map(|_| ColorInfo::fg_color(Color::Red)).collect();
+        result_lines.push( FileLine { context: LineContext::UnexpectedLine, chars: line, colors } );
+    };
+    (result_lines, precedence)
+}
 
 fn parse_used_hypots(used_hypots: &str, line_no: &str) -> Vec<(char, ColorInfo)> {
     let line_num = match line_no.parse::<usize>() {
@@ -199,7 +291,8 @@ fn theo_is_valid(
     theo_ref: &str, hypot_names: &Vec<String>,
     lib_data: &LibraryData, references: &HashMap<String, Reference>
 ) -> bool {
-    if hypot_names.contains(&theo_ref.to_owned()) {
+    // A step left to be proven
+    if hypot_names.contains(&theo_ref.to_owned()) || theo_ref == "?" {
         return true;
     };
     let (name, sub_id) = match theo_ref.split_once('.') {
@@ -275,10 +368,13 @@ pub fn parse_proof_section(
         if theo_ref_len > max_theo_ref_len { max_theo_ref_len = theo_ref_len; };
 
         let context = LineContext::ProofLine;
-        let line = parts.next().unwrap_or("").chars().collect::<Vec<_>>();
-        let resulting_formula = parse_formula(
-            line, lib_data, None, context
-        );
+        let resulting_formula = match parts.next().unwrap_or("") {
+            // The formula is left to be inferred by the compiler
+            "?" => FileLine { context, chars: vec!['?'], colors: vec![ColorInfo::NO_COLOR] },
+            line => parse_formula(
+                line.chars().collect(), lib_data, None, context
+            )
+        };
 
         preparsed_lines.push((
             (line_no, line_no_color),
@@ -17,6 +17,7 @@ pub enum LineContext {
     UnprovenAssertion,
     // ProvenAssertion,  // Not implemented for now
     AssumedAssertion,
+    DistinctVariables,
     ProofLine,
     UnexpectedLine
 }